@@ -1,15 +1,10 @@
 #![no_std]
 #![no_main]
-#![allow(static_mut_refs)]
-extern crate alloc;
 
 pub mod clock;
-pub mod heap;
 pub mod mutex;
 
 use crate::clock::SystemClock;
-use crate::heap::Heap;
-use bytes::Bytes;
 use core::cell::RefCell;
 use core::fmt::Write;
 use embedded_can::{Id, StandardId};
@@ -17,8 +12,8 @@ use embedded_hal::delay::DelayNs;
 use fugit::RateExtU32;
 use mcp2517::can::{CanController, MCP2517};
 use mcp2517::config::{
-    BitRateConfig, ClockConfiguration, ClockOutputDivisor, Configuration, FifoConfiguration, PLLSetting, RequestMode,
-    SystemClockDivisor,
+    BitRateConfig, BusOffRecovery, ClockConfiguration, ClockOutputDivisor, Configuration, DataBitRateConfig,
+    FifoConfiguration, FifoTable, InterruptConfiguration, IoConfiguration, PLLSetting, RequestMode, SystemClockDivisor,
 };
 use mcp2517::filter::Filter;
 use mcp2517::message::{Can20, TxMessage};
@@ -43,8 +38,6 @@ const XTAL_FREQ_HZ: u32 = 12_000_000u32;
 
 #[entry]
 fn main() -> ! {
-    Heap::init();
-
     let mut pac = pac::Peripherals::take().unwrap();
     let mut watchdog = Watchdog::new(pac.WATCHDOG);
 
@@ -108,6 +101,7 @@ fn main() -> ! {
         system_clock: SystemClockDivisor::DivideBy2,
         pll: PLLSetting::DirectXTALOscillator,
         disable_clock: false,
+        low_power_mode: false,
     };
 
     // Setup fifo config
@@ -117,8 +111,18 @@ fn main() -> ! {
     let config = Configuration {
         clock: clk_config,
         fifo: fifo_config,
+        fifo_table: FifoTable::default(),
+        txq: None,
+        tef: None,
+        timestamp: None,
+        interrupts: InterruptConfiguration::default(),
+        io: IoConfiguration::default(),
+        ecc: None,
+        wake_up_filter: None,
+        bus_off_recovery: BusOffRecovery::Automatic,
         mode: RequestMode::InternalLoopback,
         bit_rate: BitRateConfig::default(),
+        data_bit_rate: DataBitRateConfig::default(),
     };
 
     let _ = can_controller.reset();
@@ -136,21 +140,19 @@ fn main() -> ! {
     // Create message frame
     let payload_8 = [0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8];
     let message_type = Can20::<8> {};
-    let pl_8bytes = Bytes::copy_from_slice(&payload_8);
-    let can_message = TxMessage::new(message_type, pl_8bytes, can_id).unwrap();
+    let can_message = TxMessage::new(message_type, &payload_8, can_id).unwrap();
 
-    let mut receive_buffer = [0u8; 8];
     loop {
         let _ = can_controller.transmit(&can_message, true);
         uart.write_raw(b"can message sent\n\r").unwrap();
 
         timer.delay_ms(500);
 
-        match can_controller.receive(&mut receive_buffer, true) {
-            Ok(_) => {
+        match can_controller.receive::<8>(true) {
+            Ok(message) => {
                 uart.write_fmt(format_args!("can message received\n\r")).unwrap();
 
-                for val in receive_buffer {
+                for val in message.get_payload() {
                     uart.write_fmt(format_args!("{val}\n\r")).unwrap();
                 }
             }
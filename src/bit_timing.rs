@@ -0,0 +1,282 @@
+//! # Bit timing calculation
+//! [BitTiming] calculates the bit time segments of the nominal (CiNBTCFG) or data phase (CiDBTCFG)
+//! for any SYSCLK frequency, bit rate, sample point and synchronization jump width (SJW).
+//!
+//! The calculator searches the baud rate prescaler (BRP) resulting in the lowest bit rate error. On equal
+//! error, the closest sample point and then the highest number of time quanta (TQ) per bit is preferred.
+//! The sample point is given in per mill of the bit time. SJW is given in time quanta and limited to the
+//! length of phase segment 2.
+//!
+//! As the calculation is a `const fn`, the bit timing may be evaluated at compile time:
+//!```
+//!# use mcp2517::bit_timing::BitTiming;
+//!# use mcp2517::config::{BitRateConfig, CanBaudRate, SysClk};
+//!#
+//! // 33.333 kbps at 24 MHz SYSCLK with a sample point of 87.5%
+//! const TIMING: BitTiming = match BitTiming::nominal(24_000_000, 33_333, 875, 2) {
+//!     Ok(timing) => timing,
+//!     Err(_) => panic!("invalid bit timing"),
+//! };
+//!
+//! assert_eq!(33_333, TIMING.bit_rate());
+//! assert_eq!(10, TIMING.error_ppm());
+//!
+//! let bit_rate_config = BitRateConfig {
+//!     sys_clk: SysClk::MHz20,
+//!     can_speed: CanBaudRate::Custom(TIMING),
+//! };
+//!```
+
+/// Limits of the bit time segments in time quanta
+#[derive(Copy, Clone, Debug)]
+struct SegmentLimits {
+    tseg1_min: u32,
+    tseg1_max: u32,
+    tseg2_min: u32,
+    tseg2_max: u32,
+    sjw_max: u32,
+}
+
+/// Limits of nominal bit time configuration register (CiNBTCFG)
+const NOMINAL_LIMITS: SegmentLimits = SegmentLimits {
+    tseg1_min: 2,
+    tseg1_max: 256,
+    tseg2_min: 1,
+    tseg2_max: 128,
+    sjw_max: 128,
+};
+
+/// Limits of data bit time configuration register (CiDBTCFG)
+const DATA_LIMITS: SegmentLimits = SegmentLimits {
+    tseg1_min: 1,
+    tseg1_max: 32,
+    tseg2_min: 1,
+    tseg2_max: 16,
+    sjw_max: 16,
+};
+
+/// Maximum baud rate prescaler
+const BRP_MAX: u32 = 256;
+
+/// Possible errors of the bit timing calculation
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BitTimingError {
+    /// SYSCLK frequency or bit rate is zero
+    InvalidFrequency,
+    /// Sample point is not within 1 and 999 per mill
+    InvalidSamplePoint,
+    /// SJW is zero or exceeds the maximum of the phase
+    InvalidSjw,
+    /// Bit rate is not reachable with the given SYSCLK frequency
+    NoValidTiming,
+    /// Custom bit timing was calculated for a different SYSCLK frequency than configured
+    SysClkMismatch,
+}
+
+/// Calculated bit timing of nominal or data phase
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BitTiming {
+    /// SYSCLK frequency in Hz the timing is calculated for
+    sys_clk: u32,
+    /// Baud rate prescaler
+    brp: u16,
+    /// Time segment 1 (propagation segment + phase segment 1) in TQ
+    tseg1: u16,
+    /// Time segment 2 (phase segment 2) in TQ
+    tseg2: u16,
+    /// Synchronization jump width in TQ
+    sjw: u16,
+    /// Actual bit rate in bits per second
+    bit_rate: u32,
+    /// Deviation of the actual bit rate in parts per million
+    error_ppm: u32,
+}
+
+impl BitTiming {
+    /// Calculates the bit timing of the nominal phase (arbitration phase and CAN 2.0 frames)
+    ///
+    /// * `sys_clk`: SYSCLK frequency in Hz
+    /// * `bit_rate`: target bit rate in bits per second
+    /// * `sample_point`: sample point in per mill of the bit time
+    /// * `sjw`: synchronization jump width in TQ (1 to 128)
+    pub const fn nominal(sys_clk: u32, bit_rate: u32, sample_point: u16, sjw: u16) -> Result<Self, BitTimingError> {
+        Self::calculate(sys_clk, bit_rate, sample_point, sjw, NOMINAL_LIMITS)
+    }
+
+    /// Calculates the bit timing of the data phase of bit rate switched CAN FD frames
+    ///
+    /// * `sys_clk`: SYSCLK frequency in Hz
+    /// * `bit_rate`: target bit rate in bits per second
+    /// * `sample_point`: sample point in per mill of the bit time
+    /// * `sjw`: synchronization jump width in TQ (1 to 16)
+    pub const fn data(sys_clk: u32, bit_rate: u32, sample_point: u16, sjw: u16) -> Result<Self, BitTimingError> {
+        Self::calculate(sys_clk, bit_rate, sample_point, sjw, DATA_LIMITS)
+    }
+
+    /// SYSCLK frequency in Hz the timing is calculated for
+    pub const fn sys_clk(&self) -> u32 {
+        self.sys_clk
+    }
+
+    /// Baud rate prescaler, so TQ = BRP / SYSCLK
+    pub const fn brp(&self) -> u16 {
+        self.brp
+    }
+
+    /// Length of time segment 1 (propagation segment + phase segment 1) in TQ
+    pub const fn tseg1(&self) -> u16 {
+        self.tseg1
+    }
+
+    /// Length of time segment 2 (phase segment 2) in TQ
+    pub const fn tseg2(&self) -> u16 {
+        self.tseg2
+    }
+
+    /// Synchronization jump width in TQ
+    pub const fn sjw(&self) -> u16 {
+        self.sjw
+    }
+
+    /// Actual bit rate in bits per second (rounded)
+    pub const fn bit_rate(&self) -> u32 {
+        self.bit_rate
+    }
+
+    /// Deviation of the actual bit rate from the target bit rate in parts per million
+    pub const fn error_ppm(&self) -> u32 {
+        self.error_ppm
+    }
+
+    /// Number of TQ per bit
+    pub const fn time_quanta(&self) -> u16 {
+        1 + self.tseg1 + self.tseg2
+    }
+
+    /// Actual sample point in per mill of the bit time
+    pub const fn sample_point(&self) -> u16 {
+        ((1 + self.tseg1) as u32 * 1000 / self.time_quanta() as u32) as u16
+    }
+
+    /// Returns the register values of BRP, TSEG1, TSEG2 and SJW
+    pub const fn register_values(&self) -> [u8; 4] {
+        [
+            (self.brp - 1) as u8,
+            (self.tseg1 - 1) as u8,
+            (self.tseg2 - 1) as u8,
+            (self.sjw - 1) as u8,
+        ]
+    }
+
+    const fn calculate(
+        sys_clk: u32,
+        bit_rate: u32,
+        sample_point: u16,
+        sjw: u16,
+        limits: SegmentLimits,
+    ) -> Result<Self, BitTimingError> {
+        if sys_clk == 0 || bit_rate == 0 {
+            return Err(BitTimingError::InvalidFrequency);
+        }
+
+        if sample_point == 0 || sample_point >= 1000 {
+            return Err(BitTimingError::InvalidSamplePoint);
+        }
+
+        if sjw == 0 || sjw as u32 > limits.sjw_max {
+            return Err(BitTimingError::InvalidSjw);
+        }
+
+        let min_tq = 1 + limits.tseg1_min + limits.tseg2_min;
+        let max_tq = 1 + limits.tseg1_max + limits.tseg2_max;
+
+        let mut best: Option<Self> = None;
+        let mut brp = 1;
+
+        while brp <= BRP_MAX {
+            let divisor = brp as u64 * bit_rate as u64;
+            let tq = (sys_clk as u64 + divisor / 2) / divisor;
+
+            // Number of TQ is only decreasing with higher prescalers
+            if tq < min_tq as u64 {
+                break;
+            }
+
+            if tq <= max_tq as u64 {
+                if let Some(timing) = Self::split(sys_clk, bit_rate, brp, tq as u32, sample_point, sjw, limits) {
+                    // Bit rate error takes precedence over sample point deviation
+                    let improved = match best {
+                        Some(best) => {
+                            timing.error_ppm < best.error_ppm
+                                || (timing.error_ppm == best.error_ppm
+                                    && timing.sample_point().abs_diff(sample_point)
+                                        < best.sample_point().abs_diff(sample_point))
+                        }
+                        None => true,
+                    };
+
+                    if improved {
+                        best = Some(timing);
+                    }
+
+                    if timing.error_ppm == 0 && timing.sample_point() == sample_point {
+                        break;
+                    }
+                }
+            }
+
+            brp += 1;
+        }
+
+        match best {
+            Some(timing) => Ok(timing),
+            None => Err(BitTimingError::NoValidTiming),
+        }
+    }
+
+    /// Splits the given number of TQ in time segments matching the sample point
+    const fn split(
+        sys_clk: u32,
+        bit_rate: u32,
+        brp: u32,
+        tq: u32,
+        sample_point: u16,
+        sjw: u16,
+        limits: SegmentLimits,
+    ) -> Option<Self> {
+        // Sync segment + TSEG1
+        let sample_tq = (tq * sample_point as u32 + 500) / 1000;
+
+        let mut tseg2 = tq.saturating_sub(sample_tq);
+        if tseg2 < limits.tseg2_min {
+            tseg2 = limits.tseg2_min;
+        }
+        if tseg2 > limits.tseg2_max {
+            tseg2 = limits.tseg2_max;
+        }
+
+        let mut tseg1 = tq - 1 - tseg2;
+        if tseg1 > limits.tseg1_max {
+            tseg1 = limits.tseg1_max;
+            tseg2 = tq - 1 - tseg1;
+        }
+
+        if tseg1 < limits.tseg1_min || tseg2 > limits.tseg2_max {
+            return None;
+        }
+
+        let cycles = brp as u64 * tq as u64;
+        let target = cycles * bit_rate as u64;
+        let deviation = target.abs_diff(sys_clk as u64);
+
+        Some(Self {
+            sys_clk,
+            brp: brp as u16,
+            tseg1: tseg1 as u16,
+            tseg2: tseg2 as u16,
+            sjw: if (sjw as u32) < tseg2 { sjw } else { tseg2 as u16 },
+            bit_rate: ((sys_clk as u64 + cycles / 2) / cycles) as u32,
+            error_ppm: (deviation * 1_000_000 / target) as u32,
+        })
+    }
+}
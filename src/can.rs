@@ -17,37 +17,61 @@
 //! // Configure CAN controller
 //! can_controller.configure(&can_config, &sys_clk).unwrap();
 //! ```
+//!
+//! Blocking transmit and receive operations wait without timeout, unless the driver is created with a timeout:
+//!```
+//!# use mcp2517::can::MCP2517;
+//!# use mcp2517::example::*;
+//!# use embedded_time::duration::Milliseconds;
+//!#
+//! let spi_dev = ExampleSPIDevice::default();
+//!
+//! let can_controller = MCP2517::with_timeout(spi_dev, ExampleClock::default(), Milliseconds::new(100));
+//! ```
+//!
+//! The driver assumes a MCP2517FD by default. Pin-compatible variants enabling additional features
+//! (e.g. low-power mode) are set explicitly or detected on creation:
+//!```
+//!# use mcp2517::can::MCP2517;
+//!# use mcp2517::example::*;
+//!# use mcp2517::status::ChipVariant;
+//!#
+//! let mut can_controller = MCP2517::<_, ExampleClock>::detect(ExampleSPIDevice::default()).unwrap();
+//! assert_eq!(ChipVariant::MCP2517FD, can_controller.variant());
+//!
+//! // MCP251863 is detected as MCP2518FD
+//! can_controller.set_variant(ChipVariant::MCP251863);
+//! ```
 
-use crate::config::{ClockConfiguration, Configuration};
+use crate::bit_timing::BitTimingError;
+use crate::config::{BusOffRecovery, ClockConfiguration, Configuration, Fifo, GpioPin, PLLSetting};
 use crate::filter::Filter;
-use crate::message::{MessageType, TxMessage};
-use crate::registers::{FifoControlReg1, FifoStatusReg0, C1NBTCFG};
-use crate::status::{OperationMode, OperationStatus, OscillatorStatus};
+use crate::message::{MessageType, RxHeader, RxMessage, TransmitEvent, TxHeader, TxMessage};
+use crate::registers::{
+    crc16, fifo_control_register, fifo_status_register, fifo_user_address_register, filter_control_register_byte,
+    filter_mask_register, filter_object_register, FifoControlReg1, FifoStatusReg0, Operation, RegisterWrite,
+    CRC16_INIT, MAX_FIFO_INDEX, RAM_SIZE, REGISTER_C1BDIAG0, REGISTER_C1CON, REGISTER_C1INT, REGISTER_C1RXOVIF,
+    REGISTER_C1TBC, REGISTER_C1TEFCON, REGISTER_C1TEFSTA, REGISTER_C1TEFUA, REGISTER_C1TREC, REGISTER_C1TXATIF,
+    REGISTER_C1TXQCON, REGISTER_C1TXQSTA, REGISTER_C1TXREQ, REGISTER_C1VEC, REGISTER_CRC, REGISTER_DEVID,
+    REGISTER_ECCCON, REGISTER_ECCSTAT, REGISTER_IOCON, REGISTER_OSC,
+};
+use crate::status::{
+    AbortedTransmissions, BusDiagnostics, ChipVariant, DeviceId, EccStatus, ErrorState, Interrupt, Interrupts,
+    OperationMode, OperationStatus, OscillatorStatus, TxOutcome, TxStatus,
+};
 use byteorder::{BigEndian, ByteOrder, LittleEndian};
 use core::fmt::Debug;
-use core::marker::PhantomData;
-use embedded_hal::spi::{Operation as SpiOperation, SpiDevice};
+use embedded_can::ErrorKind;
+use embedded_hal::spi::{ErrorType, Operation as SpiOperation, SpiDevice};
 use embedded_time::duration::Milliseconds;
-use embedded_time::Clock;
+use embedded_time::{Clock, Instant};
 use log::debug;
 
-const REGISTER_C1CON: u16 = 0x000;
-
-const REGISTER_OSC: u16 = 0xE00;
-
-const REGISTER_C1NBTCFG: u16 = 0x004;
-
-/// FIFO index for receiving CAN messages
-const FIFO_RX_INDEX: u8 = 1;
-
-/// FIFO index for transmitting CAN messages
-const FIFO_TX_INDEX: u8 = 2;
-
 #[derive(Debug)]
-pub enum SpiError<D: SpiDevice<u8>> {
+pub enum SpiError<D: ErrorType> {
     BusError(D::Error),
 }
-impl<D: SpiDevice<u8>> PartialEq for SpiError<D> {
+impl<D: ErrorType> PartialEq for SpiError<D> {
     fn eq(&self, other: &Self) -> bool {
         matches!((self, other), (Self::BusError(_), Self::BusError(_)))
     }
@@ -55,7 +79,7 @@ impl<D: SpiDevice<u8>> PartialEq for SpiError<D> {
 
 /// Possible CAN errors during Configuration/Transmission/Reception
 #[derive(Debug, PartialEq)]
-pub enum CanError<D: SpiDevice<u8>> {
+pub enum CanError<D: ErrorType> {
     /// SPI bus transfer error
     BusErr(SpiError<D>),
     /// Internal clock error
@@ -72,11 +96,38 @@ pub enum CanError<D: SpiDevice<u8>> {
     InvalidBufferSize(usize),
     /// RX fifo empty error
     RxFifoEmptyErr,
+    /// Transmit event FIFO empty error
+    TefEmptyErr,
     /// TX fifo buffer full error
     TxFifoFullErr,
+    /// Error while waiting for the interrupt pin
+    InterruptPinError,
+    /// Configured FIFOs exceed the message RAM of 2048 bytes (required bytes)
+    RamSizeExceeded(usize),
+    /// Controller entered bus-off state while waiting for transmission.
+    /// Contains the kind of the bus errors recorded in CiBDIAG1, if any.
+    BusOff(Option<ErrorKind>),
+    /// Blocking transmission not completed within timeout
+    TransmitTimeout,
+    /// Blocking transmission completed without transmitting the message (final transmit status)
+    TransmissionFailed(TxStatus),
+    /// Pending transmissions not aborted within timeout
+    AbortTimeout,
+    /// Oscillator not ready within timeout of 2 ms after wake-up
+    OscillatorTimeout,
+    /// Low-power mode is not supported by the MCP2517FD
+    LowPowerModeUnsupported,
+    /// DEVID register contains an unknown device ID (register value)
+    UnknownDevice(u8),
+    /// No message received within timeout of blocking reception
+    ReceiveTimeout,
+    /// CRC of SPI read transfer does not match received data
+    CrcMismatch,
+    /// Configured data bit timing is not valid for the configured SYSCLK frequency
+    InvalidBitTiming(BitTimingError),
 }
 
-impl<D: SpiDevice<u8>> From<SpiError<D>> for CanError<D> {
+impl<D: ErrorType> From<SpiError<D>> for CanError<D> {
     fn from(value: SpiError<D>) -> Self {
         CanError::BusErr(value)
     }
@@ -87,8 +138,35 @@ pub struct MCP2517<D: SpiDevice<u8>, CLK: Clock> {
     /// Device on SPI bus
     device: D,
 
-    /// System clock
-    clock: PhantomData<CLK>,
+    /// System clock bounding blocking operations, which are unbounded if None
+    clock: Option<CLK>,
+
+    /// Timeout of blocking operations
+    timeout: Milliseconds,
+
+    /// Sequence number assigned to the next transmitted message
+    sequence: u8,
+
+    /// True if timestamps are stored in the transmit event FIFO
+    tef_timestamp: bool,
+
+    /// Bit mask of FIFO indices storing timestamps of received messages
+    rx_timestamps: u32,
+
+    /// Configured operation mode, which is restored on bus-off recovery
+    mode: OperationMode,
+
+    /// Handling of bus-off state
+    bus_off_recovery: BusOffRecovery,
+
+    /// Configured oscillator settings, restored on wake-up
+    oscillator: ClockConfiguration,
+
+    /// True if register and RAM accesses use CRC protected SPI instructions
+    spi_crc: bool,
+
+    /// Controller variant
+    variant: ChipVariant,
 }
 
 /// Trait for CAN controller
@@ -96,16 +174,43 @@ pub trait CanController {
     type Error;
 
     /// Transmit CAN message
-    /// * `blocking`: if true, function blocks until TX fifo buffer is empty and till TXREQ bit is cleared
+    /// * `blocking`: if true, function blocks until TX fifo buffer is empty and till TXREQ bit is cleared.
+    ///   Blocking is bounded by the timeout given to [MCP2517::with_timeout].
+    ///
+    /// Returns the [TxOutcome] of the transmission, which is [TxStatus::Pending] if not blocking.
     fn transmit<const L: usize, T: MessageType<L>>(
         &mut self,
         message: &TxMessage<T, L>,
         blocking: bool,
-    ) -> Result<(), Self::Error>;
+    ) -> Result<TxOutcome, Self::Error>;
+
+    /// Transmit CAN message using the given transmit FIFO
+    /// * `blocking`: if true, function blocks until TX fifo buffer is empty and till TXREQ bit is cleared
+    fn transmit_fifo<const L: usize, T: MessageType<L>>(
+        &mut self,
+        fifo: Fifo,
+        message: &TxMessage<T, L>,
+        blocking: bool,
+    ) -> Result<TxOutcome, Self::Error>;
 
-    /// Receive CAN message
+    /// Transmit CAN message using the transmit queue (TXQ), which must be enabled by configuration.
+    /// Messages are transmitted in order of ID priority, regardless of the order they are loaded in.
+    /// * `blocking`: if true, function blocks until TXQ is empty and till TXREQ bit is cleared
+    fn transmit_txq<const L: usize, T: MessageType<L>>(
+        &mut self,
+        message: &TxMessage<T, L>,
+        blocking: bool,
+    ) -> Result<TxOutcome, Self::Error>;
+
+    /// Receive CAN message, where `L` is the payload buffer size (must be a multiple of 4)
+    /// * `blocking`: if true, function blocks until RX fifo contains at least one message.
+    ///   Blocking is bounded by the timeout given to [MCP2517::with_timeout].
+    fn receive<const L: usize>(&mut self, blocking: bool) -> Result<RxMessage<L>, Self::Error>;
+
+    /// Receive CAN message from the given receive FIFO, where `L` is the payload buffer size (must be a multiple of 4)
     /// * `blocking`: if true, function blocks until RX fifo contains at least one message
-    fn receive<const L: usize>(&mut self, data: &mut [u8; L], blocking: bool) -> Result<(), Self::Error>;
+    fn receive_fifo<const L: usize>(&mut self, fifo: Fifo, blocking: bool) -> Result<RxMessage<L>, Self::Error>;
+
     /// Set corresponding filter and mask registers
     fn set_filter_object(&mut self, filter: Filter) -> Result<(), Self::Error>;
 }
@@ -121,74 +226,62 @@ where
         &mut self,
         message: &TxMessage<T, L>,
         blocking: bool,
-    ) -> Result<(), Self::Error> {
-        let fifo_status_reg = Self::fifo_status_register(FIFO_TX_INDEX);
-
-        // Check if TX fifo is full
-        while !self.fifo_tfnrfnif(fifo_status_reg)? {
-            if !blocking {
-                return Err(CanError::TxFifoFullErr);
-            }
-        }
-
-        // make sure length of payload is consistent with CAN operation mode
-        let operation_status = self.read_operation_status()?;
-
-        if message.buff.len() > 8 && operation_status.mode != OperationMode::NormalCANFD {
-            return Err(CanError::InvalidPayloadLength(message.buff.len()));
-        }
-
-        // get address in which to write next message in TX FIFO (should not be read in configuration mode)
-        let user_address = self.read32(Self::fifo_user_address_register(FIFO_TX_INDEX))?;
-
-        // calculate address of next Message Object according to
-        // Equation 4-1 in MCP251XXFD Family Reference Manual
-        let address = user_address + 0x400;
-
-        // get address of TX FIFO control register byte 1
-        let fifo_control_reg1 = Self::fifo_control_register(FIFO_TX_INDEX) + 1;
-
-        // load message in TX FIFO
-        self.write_fifo::<T, L>(address as u16, message)?;
+    ) -> Result<TxOutcome, Self::Error> {
+        self.transmit_fifo(Fifo::TX, message, blocking)
+    }
 
-        // Request transmission (set txreq) and set uinc in TX FIFO control register byte 1
-        self.write_register(fifo_control_reg1, 0x03)?;
+    fn transmit_fifo<const L: usize, T: MessageType<L>>(
+        &mut self,
+        fifo: Fifo,
+        message: &TxMessage<T, L>,
+        blocking: bool,
+    ) -> Result<TxOutcome, Self::Error> {
+        self.transmit_message(fifo_control_register(fifo.index()), message, blocking)
+    }
 
-        // block till TXREQ is cleared confirming that all messages in TX FIFO are transmitted
-        if blocking {
-            while !self.txfifo_cleared(fifo_control_reg1)? {}
-        }
+    fn transmit_txq<const L: usize, T: MessageType<L>>(
+        &mut self,
+        message: &TxMessage<T, L>,
+        blocking: bool,
+    ) -> Result<TxOutcome, Self::Error> {
+        self.transmit_message(REGISTER_C1TXQCON, message, blocking)
+    }
 
-        Ok(())
+    fn receive<const L: usize>(&mut self, blocking: bool) -> Result<RxMessage<L>, Self::Error> {
+        self.receive_fifo(Fifo::RX, blocking)
     }
 
-    fn receive<const L: usize>(&mut self, data: &mut [u8; L], blocking: bool) -> Result<(), Self::Error> {
-        let fifo_status_reg = Self::fifo_status_register(FIFO_RX_INDEX);
+    fn receive_fifo<const L: usize>(&mut self, fifo: Fifo, blocking: bool) -> Result<RxMessage<L>, Self::Error> {
+        let fifo_status_reg = fifo_status_register(fifo.index());
+        let deadline = if blocking { self.deadline()? } else { None };
 
         // Make sure RX fifo is not empty
         while !self.fifo_tfnrfnif(fifo_status_reg)? {
             if !blocking {
                 return Err(CanError::RxFifoEmptyErr);
             }
+
+            self.check_deadline(deadline, CanError::ReceiveTimeout)?;
         }
 
-        let user_address = self.read32(Self::fifo_user_address_register(FIFO_RX_INDEX))?;
+        let user_address = self.read32(fifo_user_address_register(fifo.index()))?;
 
         let address = 0x400 + user_address;
 
         // read message object
-        self.read_fifo(address as u16, data)?;
+        let timestamp = self.rx_timestamps & (1 << fifo.index()) != 0;
+        let message = self.read_fifo(address as u16, timestamp)?;
 
         // set UINC bit for incrementing the FIFO head by a single message
-        self.write_register(Self::fifo_control_register(FIFO_RX_INDEX) + 1, 1)?;
+        self.write_register(fifo_control_register(fifo.index()) + 1, 1)?;
 
-        Ok(())
+        Ok(message)
     }
 
     /// Set corresponding filter and mask registers
     fn set_filter_object(&mut self, filter: Filter) -> Result<(), Self::Error> {
-        let filter_object_reg = Self::filter_object_register(filter.index);
-        let filter_mask_reg = Self::filter_mask_register(filter.index);
+        let filter_object_reg = filter_object_register(filter.index);
+        let filter_mask_reg = filter_mask_register(filter.index);
 
         self.disable_filter(filter.index)?;
 
@@ -199,7 +292,7 @@ where
 
         self.write32(filter_mask_reg, mask_value)?;
 
-        let filter_control_reg = Self::filter_control_register_byte(filter.index);
+        let filter_control_reg = filter_control_register_byte(filter.index);
 
         self.write_register(filter_control_reg, (1 << 7) | 1)?;
 
@@ -212,54 +305,173 @@ where
     D: SpiDevice,
     CLK: Clock,
 {
+    /// Creates a new driver. Blocking operations wait without timeout.
     pub fn new(spi_dev: D) -> Self {
         Self {
             device: spi_dev,
-            clock: Default::default(),
+            clock: None,
+            timeout: Milliseconds::new(0),
+            sequence: 0,
+            tef_timestamp: false,
+            rx_timestamps: 0,
+            mode: OperationMode::NormalCANFD,
+            bus_off_recovery: BusOffRecovery::default(),
+            oscillator: ClockConfiguration::default(),
+            spi_crc: false,
+            variant: ChipVariant::default(),
+        }
+    }
+
+    /// Creates a new driver and detects the controller variant by [Self::detect_variant].
+    /// Blocking operations wait without timeout.
+    pub fn detect(spi_dev: D) -> Result<Self, CanError<D>> {
+        let mut controller = Self::new(spi_dev);
+        controller.detect_variant()?;
+
+        Ok(controller)
+    }
+
+    /// Creates a new driver with bounded blocking operations. Blocking transmission returns
+    /// [CanError::TransmitTimeout] and blocking reception returns [CanError::ReceiveTimeout]
+    /// if the operation is not completed within the given timeout.
+    pub fn with_timeout(spi_dev: D, clock: CLK, timeout: Milliseconds) -> Self {
+        Self {
+            clock: Some(clock),
+            timeout,
+            ..Self::new(spi_dev)
         }
     }
 
+    /// Enables/disables CRC protected SPI instructions (READ_CRC, WRITE_CRC and WRITE_SAFE) for all
+    /// register and RAM accesses. Corrupted reads are reported by [CanError::CrcMismatch], while corrupted
+    /// writes are discarded by the device and flagged by [Interrupt::SpiCrc].
+    pub fn set_spi_crc(&mut self, enabled: bool) {
+        self.spi_crc = enabled;
+    }
+
+    /// Sets the controller variant, which defaults to [ChipVariant::MCP2517FD].
+    /// The variant is detected by [Self::detect_variant] instead.
+    pub fn set_variant(&mut self, variant: ChipVariant) {
+        self.variant = variant;
+    }
+
+    /// Returns the controller variant
+    pub fn variant(&self) -> ChipVariant {
+        self.variant
+    }
+
+    /// Detects the controller variant and uses it. As the MCP2517FD does not reliably report a distinct
+    /// device ID, the variant is detected by probing the LPMEN bit of the OSC register, which is only implemented
+    /// by MCP2518FD and MCP251863. The OSC register is restored afterward.
+    ///
+    /// MCP251863 can't be distinguished from MCP2518FD, so it is detected as MCP2518FD, unless
+    /// [ChipVariant::MCP251863] was set before.
+    pub fn detect_variant(&mut self) -> Result<ChipVariant, CanError<D>> {
+        let oscillator = self.read_register(REGISTER_OSC)?;
+
+        // LPMEN is read as zero by the MCP2517FD
+        self.write_register(REGISTER_OSC, oscillator | (1 << 3))?;
+        let low_power_mode = self.read_register(REGISTER_OSC)? & (1 << 3) != 0;
+        self.write_register(REGISTER_OSC, oscillator)?;
+
+        self.variant = match (low_power_mode, self.variant) {
+            (false, _) => ChipVariant::MCP2517FD,
+            (true, ChipVariant::MCP251863) => ChipVariant::MCP251863,
+            (true, _) => ChipVariant::MCP2518FD,
+        };
+
+        Ok(self.variant)
+    }
+
+    /// Reads and returns the device ID (DEVID register).
+    /// The MCP2517FD does not reliably report a distinct device ID, so the variant is detected by
+    /// [Self::detect_variant] instead.
+    pub fn read_device_id(&mut self) -> Result<DeviceId, CanError<D>> {
+        let data = self.read_register(REGISTER_DEVID)?;
+
+        DeviceId::from_register(data).ok_or(CanError::UnknownDevice(data))
+    }
+
     /// Configures the controller with the given settings
     pub fn configure(&mut self, config: &Configuration, clock: &CLK) -> Result<(), CanError<D>> {
+        let ram_usage = config.ram_usage();
+        if ram_usage > RAM_SIZE {
+            return Err(CanError::RamSizeExceeded(ram_usage));
+        }
+
+        if config.clock.low_power_mode && !self.variant.supports_low_power_mode() {
+            return Err(CanError::LowPowerModeUnsupported);
+        }
+
+        let writes = config.register_writes().map_err(CanError::InvalidBitTiming)?;
+
         self.enable_mode(OperationMode::Configuration, clock, CanError::ConfigurationModeTimeout)?;
 
-        self.write_register(REGISTER_OSC, config.clock.as_register())?;
+        for write in writes {
+            match write {
+                RegisterWrite::Byte(register, value) => self.write_register(register, value)?,
+                RegisterWrite::Word(register, value) => self.write32(register, value)?,
+                RegisterWrite::InitializeRam => self.initialize_ram()?,
+            }
+        }
 
-        let nbr_values = config.bit_rate.calculate_values();
-        let nbr_reg = C1NBTCFG::from_bytes(nbr_values).into();
+        self.enable_mode(config.mode.to_operation_mode(), clock, CanError::RequestModeTimeout)?;
 
-        self.write32(REGISTER_C1NBTCFG, nbr_reg)?;
+        self.sequence = 0;
+        self.tef_timestamp = config.tef.is_some_and(|tef| tef.timestamp);
+        self.rx_timestamps = config.rx_timestamp_fifos();
+        self.mode = config.mode.to_operation_mode();
+        self.bus_off_recovery = config.bus_off_recovery;
+        self.oscillator = config.clock;
 
-        self.write_register(
-            Self::fifo_control_register(FIFO_RX_INDEX) + 3,
-            config.fifo.as_rx_register_3(),
-        )?;
+        Ok(())
+    }
 
-        self.write_register(
-            Self::fifo_control_register(FIFO_TX_INDEX) + 2,
-            config.fifo.as_tx_register_2(),
-        )?;
+    /// Returns the sequence number assigned to the next transmitted message.
+    /// Sequence numbers are incremented on each transmission and wrap around after 127.
+    pub fn next_sequence(&self) -> u8 {
+        self.sequence
+    }
 
-        self.write_register(
-            Self::fifo_control_register(FIFO_TX_INDEX) + 3,
-            config.fifo.as_tx_register_3(),
-        )?;
+    /// Reads the oldest event of the transmit event FIFO (TEF), which must be enabled by configuration.
+    /// Returns [CanError::TefEmptyErr] if no message was transmitted since the last call.
+    pub fn read_transmit_event(&mut self) -> Result<TransmitEvent, CanError<D>> {
+        let tef_status = self.read_register(REGISTER_C1TEFSTA)?;
 
-        self.write_register(
-            Self::fifo_control_register(FIFO_TX_INDEX),
-            config.fifo.as_tx_register_0(),
-        )?;
+        // TEFNEIF: TEF not empty
+        if tef_status & 1 == 0 {
+            return Err(CanError::TefEmptyErr);
+        }
 
-        self.enable_filter(FIFO_RX_INDEX, 0)?;
+        let user_address = self.read32(REGISTER_C1TEFUA)?;
+        let address = (user_address + 0x400) as u16;
 
-        self.enable_mode(config.mode.to_operation_mode(), clock, CanError::RequestModeTimeout)?;
+        // header is followed by the optional timestamp
+        let mut event = [0u8; 12];
+        let length = if self.tef_timestamp { 12 } else { 8 };
+        self.read_buffer(address, &mut event[..length])?;
 
-        Ok(())
+        let mut header = [0u8; 8];
+        header.copy_from_slice(&event[..8]);
+
+        // header words are transferred LSB first, bitfield expects MSB first
+        for word in header.chunks_exact_mut(4) {
+            let num = LittleEndian::read_u32(word);
+            BigEndian::write_u32(word, num);
+        }
+
+        // set UINC bit for incrementing the TEF tail by a single event
+        self.write_register(REGISTER_C1TEFCON + 1, 1)?;
+
+        Ok(TransmitEvent {
+            header: TxHeader::from_bytes(header),
+            timestamp: self.tef_timestamp.then(|| LittleEndian::read_u32(&event[8..])),
+        })
     }
 
     /// Disable corresponding filter
     pub fn disable_filter(&mut self, filter_index: u8) -> Result<(), CanError<D>> {
-        let filter_reg = Self::filter_control_register_byte(filter_index);
+        let filter_reg = filter_control_register_byte(filter_index);
         self.write_register(filter_reg, 0x00)?;
 
         Ok(())
@@ -272,6 +484,134 @@ where
         Ok(OperationStatus::from_register(data))
     }
 
+    /// Reads the pending interrupts (CiVEC, CiINT, CiRXIF and CiTXIF) in a single transfer
+    pub fn read_interrupts(&mut self) -> Result<Interrupts, CanError<D>> {
+        let mut data = [0u8; 16];
+        self.read_buffer(REGISTER_C1VEC, &mut data)?;
+
+        let mut registers = [0u32; 4];
+        for (register, word) in registers.iter_mut().zip(data.chunks_exact(4)) {
+            *register = LittleEndian::read_u32(word);
+        }
+
+        Ok(Interrupts::from_registers(registers))
+    }
+
+    /// Clears the flag of the given interrupt.
+    ///
+    /// Receive and transmit FIFO interrupts are cleared by reading/loading messages, so this is a no-op for
+    /// [Interrupt::Receive], [Interrupt::Transmit] and [Interrupt::TransmitQueue]. The same applies to the
+    /// TEF not empty interrupt, while a pending TEF overflow is cleared. Receive overflow and transmit attempt
+    /// interrupts are cleared for all FIFOs.
+    pub fn clear_interrupt(&mut self, interrupt: Interrupt) -> Result<(), CanError<D>> {
+        // Flags are cleared by writing zero, writing one has no effect
+        match interrupt {
+            Interrupt::Receive(_) | Interrupt::Transmit(_) | Interrupt::TransmitQueue => {}
+            Interrupt::TimeBaseOverflow => self.write_register(REGISTER_C1INT, !(1 << 2))?,
+            Interrupt::ModeChange => self.write_register(REGISTER_C1INT, !(1 << 3))?,
+            Interrupt::SystemError => self.write_register(REGISTER_C1INT + 1, !(1 << 4))?,
+            Interrupt::BusError => self.write_register(REGISTER_C1INT + 1, !(1 << 5))?,
+            Interrupt::WakeUp => self.write_register(REGISTER_C1INT + 1, !(1 << 6))?,
+            Interrupt::InvalidMessage => self.write_register(REGISTER_C1INT + 1, !(1 << 7))?,
+            Interrupt::TransmitEvent => self.write_register(REGISTER_C1TEFSTA, !(1 << 3))?,
+            Interrupt::Ecc => self.write_register(REGISTER_ECCSTAT, 0x0)?,
+            Interrupt::SpiCrc => self.write_register(REGISTER_CRC + 2, 0x0)?,
+            Interrupt::ReceiveOverflow => {
+                let pending = self.read32(REGISTER_C1RXOVIF)?;
+                let status_reg0: u8 = FifoStatusReg0::from(0xFF).with_rxovif(false).into();
+
+                for index in (1..=MAX_FIFO_INDEX).filter(|index| pending & (1 << index) != 0) {
+                    self.write_register(fifo_status_register(index), status_reg0)?;
+                }
+            }
+            Interrupt::TransmitAttempt => {
+                let pending = self.read32(REGISTER_C1TXATIF)?;
+                let status_reg0: u8 = FifoStatusReg0::from(0xFF).with_txatif(false).into();
+
+                if pending & 1 != 0 {
+                    self.write_register(REGISTER_C1TXQSTA, status_reg0)?;
+                }
+
+                for index in (1..=MAX_FIFO_INDEX).filter(|index| pending & (1 << index) != 0) {
+                    self.write_register(fifo_status_register(index), status_reg0)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads and returns the error counters and fault confinement state (CiTREC)
+    pub fn read_error_state(&mut self) -> Result<ErrorState, CanError<D>> {
+        let register = self.read32(REGISTER_C1TREC)?;
+
+        Ok(ErrorState::from_register(register))
+    }
+
+    /// Reads and returns the bus diagnostics (CiBDIAG0 and CiBDIAG1)
+    pub fn read_bus_diagnostics(&mut self) -> Result<BusDiagnostics, CanError<D>> {
+        let diag0 = self.read32(REGISTER_C1BDIAG0)?;
+        let diag1 = self.read32(REGISTER_C1BDIAG0 + 4)?;
+
+        Ok(BusDiagnostics::from_registers(diag0, diag1))
+    }
+
+    /// Resets the bus diagnostic counters and error flags
+    pub fn clear_bus_diagnostics(&mut self) -> Result<(), CanError<D>> {
+        self.write32(REGISTER_C1BDIAG0, 0x0)?;
+        self.write32(REGISTER_C1BDIAG0 + 4, 0x0)?;
+
+        Ok(())
+    }
+
+    /// Restarts the operation after bus-off state was reported by [CanError::BusOff].
+    /// Depending on the configured [BusOffRecovery] policy:
+    /// * `Automatic`: Nothing to do, as the controller recovers on its own
+    /// * `Manual`: Configured operation mode is requested
+    /// * `Delayed`: Blocks for the given delay, afterward the configured operation mode is requested
+    pub fn recover_bus_off(&mut self, clock: &CLK) -> Result<(), CanError<D>> {
+        match self.bus_off_recovery {
+            BusOffRecovery::Automatic => return Ok(()),
+            BusOffRecovery::Manual => {}
+            BusOffRecovery::Delayed(delay) => {
+                let target = clock.try_now()?.checked_add(delay).ok_or(CanError::ClockError)?;
+
+                while clock.try_now()? < target {}
+            }
+        }
+
+        self.enable_mode(self.mode, clock, CanError::RequestModeTimeout)
+    }
+
+    /// Reads and returns the ECC errors of the message RAM
+    pub fn read_ecc_status(&mut self) -> Result<EccStatus, CanError<D>> {
+        let register = self.read32(REGISTER_ECCSTAT)?;
+
+        Ok(EccStatus::from_register(register))
+    }
+
+    /// Test hook for validating ECC error handling. Writes the given word to the RAM address with ECC disabled,
+    /// storing the given parity bits instead of the calculated ones. Reading the word with ECC enabled reports
+    /// an ECC error if the parity does not match. ECC configuration is restored afterward.
+    pub fn inject_ecc_error(&mut self, address: u16, value: u32, parity: u8) -> Result<(), CanError<D>> {
+        self.verify_ram_address(address, 4)?;
+
+        let ecc_control = self.read32(REGISTER_ECCCON)?;
+
+        // bits 14-8 -> PARITY, bit 0 -> ECCEN
+        let injection_control = (ecc_control & !0x7F01) | (((parity & 0x7F) as u32) << 8);
+        self.write32(REGISTER_ECCCON, injection_control)?;
+        self.write32(address, value)?;
+        self.write32(REGISTER_ECCCON, ecc_control)?;
+
+        Ok(())
+    }
+
+    /// Reads and returns the current value of the time base counter (CiTBC)
+    pub fn read_time_base(&mut self) -> Result<u32, CanError<D>> {
+        self.read32(REGISTER_C1TBC)
+    }
+
     /// Reads and returns the oscillator status
     pub fn read_oscillator_status(&mut self) -> Result<OscillatorStatus, CanError<D>> {
         let data = self.read_register(REGISTER_OSC + 1)?;
@@ -279,6 +619,68 @@ where
         Ok(OscillatorStatus::from_register(data))
     }
 
+    /// Requests sleep mode, all pending transmissions are aborted. The controller is woken up by [Self::wake]
+    /// or by bus activity, which is signaled by [Interrupt::WakeUp] if enabled by configuration.
+    ///
+    /// If [low-power mode](ClockConfiguration::low_power_mode) is enabled, the controller enters LPM instead.
+    /// As SPI is not available in LPM, the mode transition is not verified.
+    pub fn enter_sleep(&mut self, clock: &CLK) -> Result<(), CanError<D>> {
+        if self.oscillator.low_power_mode {
+            return self.write_register(REGISTER_C1CON + 3, OperationMode::Sleep as u8 | (1 << 3));
+        }
+
+        self.enable_mode(OperationMode::Sleep, clock, CanError::RequestModeTimeout)
+    }
+
+    /// Wakes the controller from sleep mode or LPM by enabling the oscillator and waits max. 2 ms
+    /// until the oscillator (and PLL, if enabled) is ready again. The controller enters configuration
+    /// mode on wake-up, so afterward the configured operation mode is requested.
+    ///
+    /// Returns true if the controller was woken up by bus activity. The wake-up interrupt is cleared.
+    ///
+    /// In case of LPM, the controller is reset on wake-up and must be configured again, so the operation
+    /// mode is not requested and false is returned.
+    pub fn wake(&mut self, clock: &CLK) -> Result<bool, CanError<D>> {
+        let oscillator = ClockConfiguration {
+            disable_clock: false,
+            ..self.oscillator
+        };
+        self.write_register(REGISTER_OSC, oscillator.as_register())?;
+
+        let target = clock.try_now()?.checked_add(Milliseconds::new(2)).ok_or(CanError::ClockError)?;
+
+        loop {
+            let status = self.read_oscillator_status()?;
+            if status.clock_ready && (status.pll_ready || oscillator.pll == PLLSetting::DirectXTALOscillator) {
+                break;
+            }
+
+            if clock.try_now()? > target {
+                debug!("Oscillator not ready within timeout after wake-up: {status:?}");
+                return Err(CanError::OscillatorTimeout);
+            }
+        }
+
+        if oscillator.low_power_mode {
+            return Ok(false);
+        }
+
+        // WAKIF
+        let bus_activity = self.read_register(REGISTER_C1INT + 1)? & (1 << 6) != 0;
+        if bus_activity {
+            self.clear_interrupt(Interrupt::WakeUp)?;
+        }
+
+        self.enable_mode(self.mode, clock, CanError::RequestModeTimeout)?;
+
+        Ok(bus_activity)
+    }
+
+    /// Returns true if low-power mode is entered instead of sleep mode
+    pub(crate) fn low_power_mode(&self) -> bool {
+        self.oscillator.low_power_mode
+    }
+
     /// Reads and returns the current clock configuration
     pub fn read_clock_configuration(&mut self) -> Result<ClockConfiguration, CanError<D>> {
         let data = self.read_register(REGISTER_OSC)?;
@@ -286,9 +688,35 @@ where
         Ok(ClockConfiguration::from_register(data))
     }
 
+    /// Sets the output level (LATx) of the given GPIO pin (true = high).
+    /// The pin level only changes if the pin is configured as [output](crate::config::PinMode::Output).
+    pub fn set_gpio(&mut self, pin: GpioPin, level: bool) -> Result<(), CanError<D>> {
+        let latch = self.read_register(REGISTER_IOCON + 1)?;
+        let mask = 1 << pin as u8;
+
+        let latch = match level {
+            true => latch | mask,
+            false => latch & !mask,
+        };
+
+        self.write_register(REGISTER_IOCON + 1, latch)
+    }
+
+    /// Reads the current level of the given GPIO pin (true = high)
+    pub fn read_gpio(&mut self, pin: GpioPin) -> Result<bool, CanError<D>> {
+        let data = self.read_register(REGISTER_IOCON + 2)?;
+
+        Ok(data & (1 << pin as u8) != 0)
+    }
+
     /// Enters the given mode, aborts all running transactions
     /// and waits max. 2 ms for the given mode to be reached
-    fn enable_mode(&mut self, mode: OperationMode, clock: &CLK, timeout_error: CanError<D>) -> Result<(), CanError<D>> {
+    pub(crate) fn enable_mode(
+        &mut self,
+        mode: OperationMode,
+        clock: &CLK,
+        timeout_error: CanError<D>,
+    ) -> Result<(), CanError<D>> {
         self.write_register(REGISTER_C1CON + 3, mode as u8 | (1 << 3))?;
 
         let target = clock.try_now()?.checked_add(Milliseconds::new(2)).ok_or(CanError::ClockError)?;
@@ -307,9 +735,104 @@ where
         Ok(())
     }
 
+    /// Loads the message in the given TX FIFO without requesting its transmission.
+    /// If [auto RTR](crate::config::FifoSettings::auto_rtr) is enabled for the FIFO, the message is
+    /// transmitted by the controller once a remote frame matching a filter pointing to the FIFO is received.
+    /// Returns [CanError::TxFifoFullErr] if the FIFO is full.
+    pub fn load_fifo<const L: usize, T: MessageType<L>>(
+        &mut self,
+        fifo: Fifo,
+        message: &TxMessage<T, L>,
+    ) -> Result<(), CanError<D>> {
+        let control_register = fifo_control_register(fifo.index());
+
+        if !self.fifo_tfnrfnif(control_register + 4)? {
+            return Err(CanError::TxFifoFullErr);
+        }
+
+        self.load_message(control_register, message)?;
+
+        // Set uinc in TX FIFO control register byte 1, transmission is requested by remote frame
+        self.write_register(control_register + 1, 0x01)?;
+
+        // sequence number is 7 bits wide
+        self.sequence = (self.sequence + 1) & 0x7F;
+
+        Ok(())
+    }
+
+    /// Aborts the pending transmission of the given TX FIFO and waits max. 100 ms until the abort is completed.
+    /// A message already being transmitted is not interrupted. Aborted messages are discarded by resetting the FIFO.
+    ///
+    /// Returns true if messages were aborted (TXABT), false if no transmission was pending.
+    /// Returns [CanError::AbortTimeout] if the abort or the FIFO reset is not completed within the timeout.
+    pub fn abort_fifo(&mut self, fifo: Fifo, clock: &CLK) -> Result<bool, CanError<D>> {
+        let control_register = fifo_control_register(fifo.index());
+
+        // Clearing TXREQ requests the abort
+        self.write_register(control_register + 1, 0x00)?;
+
+        let target = clock
+            .try_now()?
+            .checked_add(Milliseconds::new(100))
+            .ok_or(CanError::ClockError)?;
+
+        while !self.txfifo_cleared(control_register + 1)? {
+            if clock.try_now()? > target {
+                return Err(CanError::AbortTimeout);
+            }
+        }
+
+        let aborted = FifoStatusReg0::from(self.read_register(control_register + 4)?).txabt();
+        if aborted {
+            self.reset_fifo(control_register, clock, target)?;
+        }
+
+        Ok(aborted)
+    }
+
+    /// Aborts the pending transmissions of all TX FIFOs and the TXQ (ABAT) and waits max. 100 ms until the abort
+    /// is completed. Messages already being transmitted are not interrupted. Aborted messages are discarded by
+    /// resetting the FIFOs.
+    ///
+    /// Returns [CanError::AbortTimeout] if the abort or a FIFO reset is not completed within the timeout.
+    pub fn abort_all(&mut self, clock: &CLK) -> Result<AbortedTransmissions, CanError<D>> {
+        let pending = self.read32(REGISTER_C1TXREQ)?;
+        let control_byte3 = self.read_register(REGISTER_C1CON + 3)?;
+
+        self.write_register(REGISTER_C1CON + 3, control_byte3 | (1 << 3))?;
+
+        let target = clock
+            .try_now()?
+            .checked_add(Milliseconds::new(100))
+            .ok_or(CanError::ClockError)?;
+
+        // ABAT is cleared by the controller once all pending transmissions are aborted
+        while self.read_register(REGISTER_C1CON + 3)? & (1 << 3) != 0 {
+            if clock.try_now()? > target {
+                return Err(CanError::AbortTimeout);
+            }
+        }
+
+        let mut aborted = 0;
+        for index in (0..=MAX_FIFO_INDEX).filter(|index| pending & (1 << index) != 0) {
+            let control_register = match index {
+                0 => REGISTER_C1TXQCON,
+                _ => fifo_control_register(index),
+            };
+
+            if FifoStatusReg0::from(self.read_register(control_register + 4)?).txabt() {
+                self.reset_fifo(control_register, clock, target)?;
+                aborted |= 1 << index;
+            }
+        }
+
+        Ok(AbortedTransmissions::from_register(aborted))
+    }
+
     /// Enable filter for corresponding RX FIFO
     pub fn enable_filter(&mut self, fifo_index: u8, filter_index: u8) -> Result<(), CanError<D>> {
-        let filter_control_reg = Self::filter_control_register_byte(filter_index);
+        let filter_control_reg = filter_control_register_byte(filter_index);
 
         // Filter must be disabled to modify FmBP
         self.disable_filter(filter_index)?;
@@ -323,8 +846,121 @@ where
         Ok(())
     }
 
+    /// Loads the message in the TX FIFO/TXQ of the given control register and requests transmission.
+    /// Status and user address registers are following the control register.
+    fn transmit_message<const L: usize, T: MessageType<L>>(
+        &mut self,
+        control_register: u16,
+        message: &TxMessage<T, L>,
+        blocking: bool,
+    ) -> Result<TxOutcome, CanError<D>> {
+        let fifo_status_reg = control_register + 4;
+        let deadline = if blocking { self.deadline()? } else { None };
+
+        // Check if TX fifo is full
+        while !self.fifo_tfnrfnif(fifo_status_reg)? {
+            if !blocking {
+                return Err(CanError::TxFifoFullErr);
+            }
+
+            self.check_bus_off()?;
+            self.check_deadline(deadline, CanError::TransmitTimeout)?;
+        }
+
+        self.load_message(control_register, message)?;
+
+        // Status flags remain set until cleared, so the flags of previous transmissions are cleared
+        if blocking {
+            let status = FifoStatusReg0::from(0xFF)
+                .with_txabt(false)
+                .with_txlarb(false)
+                .with_txerr(false)
+                .with_txatif(false);
+            self.write_register(fifo_status_reg, status.into())?;
+        }
+
+        // Request transmission (set txreq) and set uinc in TX FIFO control register byte 1
+        self.write_register(control_register + 1, 0x03)?;
+
+        // sequence number is 7 bits wide
+        self.sequence = (self.sequence + 1) & 0x7F;
+
+        if !blocking {
+            return Ok(TxOutcome::PENDING);
+        }
+
+        let mut arbitration_lost = false;
+        let mut error_detected = false;
+
+        // block till TXREQ is cleared confirming that all messages in TX FIFO are transmitted
+        loop {
+            let cleared = self.txfifo_cleared(control_register + 1)?;
+            if !cleared {
+                self.check_bus_off()?;
+            }
+
+            let status = FifoStatusReg0::from(self.read_register(fifo_status_reg)?);
+            arbitration_lost |= status.txlarb();
+            error_detected |= status.txerr();
+
+            if cleared {
+                // TXABT may be set along with TXATIF, so exhausted attempts are checked first
+                let status = if status.txatif() {
+                    TxStatus::AttemptsExhausted
+                } else if status.txabt() {
+                    TxStatus::Aborted
+                } else {
+                    TxStatus::Transmitted
+                };
+
+                return Ok(TxOutcome {
+                    status,
+                    arbitration_lost,
+                    error_detected,
+                });
+            }
+
+            self.check_deadline(deadline, CanError::TransmitTimeout)?;
+        }
+    }
+
+    /// Writes the message to the next message object of the TX FIFO/TXQ of the given control register.
+    /// FIFO pointer is not incremented.
+    fn load_message<const L: usize, T: MessageType<L>>(
+        &mut self,
+        control_register: u16,
+        message: &TxMessage<T, L>,
+    ) -> Result<(), CanError<D>> {
+        // make sure length of payload is consistent with CAN operation mode
+        let operation_status = self.read_operation_status()?;
+
+        if message.length > 8 && operation_status.mode != OperationMode::NormalCANFD {
+            return Err(CanError::InvalidPayloadLength(message.length));
+        }
+
+        // get address in which to write next message in TX FIFO (should not be read in configuration mode)
+        let user_address = self.read32(control_register + 8)?;
+
+        // calculate address of next Message Object according to
+        // Equation 4-1 in MCP251XXFD Family Reference Manual
+        let address = user_address + 0x400;
+
+        // load message in TX FIFO
+        self.write_fifo::<T, L>(address as u16, message)
+    }
+
     /// Writes a single register byte
-    fn write_register(&mut self, register: u16, value: u8) -> Result<(), SpiError<D>> {
+    fn write_register(&mut self, register: u16, value: u8) -> Result<(), CanError<D>> {
+        if self.spi_crc {
+            let command = Operation::WriteSafe.command(register);
+            let crc = crc16(crc16(CRC16_INIT, &command), &[value]).to_be_bytes();
+
+            let buffer = [command[0], command[1], value, crc[0], crc[1]];
+            self.device.write(&buffer).map_err(SpiError::BusError)?;
+
+            return Ok(());
+        }
+
         let mut buffer = self.cmd_buffer(register, Operation::Write);
         buffer[2] = value;
 
@@ -333,7 +969,21 @@ where
     }
 
     /// 4-byte SFR write
-    fn write32(&mut self, register: u16, value: u32) -> Result<(), SpiError<D>> {
+    fn write32(&mut self, register: u16, value: u32) -> Result<(), CanError<D>> {
+        if self.spi_crc {
+            let command = Operation::WriteCrc.crc_command(register, 4);
+            let value_bytes = value.to_le_bytes();
+            let crc = crc16(crc16(CRC16_INIT, &command), &value_bytes).to_be_bytes();
+
+            let mut buffer = [0u8; 9];
+            buffer[..3].copy_from_slice(&command);
+            buffer[3..7].copy_from_slice(&value_bytes);
+            buffer[7..].copy_from_slice(&crc);
+            self.device.write(&buffer).map_err(SpiError::BusError)?;
+
+            return Ok(());
+        }
+
         let mut buffer = [0u8; 6];
         let command = (register & 0x0FFF) | ((Operation::Write as u16) << 12);
 
@@ -348,6 +998,41 @@ where
         Ok(())
     }
 
+    /// Zeroes the entire message RAM
+    fn initialize_ram(&mut self) -> Result<(), CanError<D>> {
+        let data = [0u8; 64];
+
+        for address in (0x400..0x400 + RAM_SIZE as u16).step_by(data.len()) {
+            self.write_buffer(address, &data)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes consecutive bytes starting at the given SFR or RAM address
+    fn write_buffer(&mut self, register: u16, data: &[u8]) -> Result<(), CanError<D>> {
+        if !self.spi_crc {
+            let command = Operation::Write.command(register);
+
+            let mut operations = [SpiOperation::Write(&command), SpiOperation::Write(data)];
+            self.device.transaction(&mut operations).map_err(SpiError::BusError)?;
+
+            return Ok(());
+        }
+
+        let command = Operation::WriteCrc.crc_command(register, data.len());
+        let crc = crc16(crc16(CRC16_INIT, &command), data).to_be_bytes();
+
+        let mut operations = [
+            SpiOperation::Write(&command),
+            SpiOperation::Write(data),
+            SpiOperation::Write(&crc),
+        ];
+        self.device.transaction(&mut operations).map_err(SpiError::BusError)?;
+
+        Ok(())
+    }
+
     /// Reset internal register to default and switch to Configuration mode
     pub fn reset(&mut self) -> Result<(), CanError<D>> {
         let mut buffer = self.cmd_buffer(0u16, Operation::Reset);
@@ -361,63 +1046,118 @@ where
     where
         T: MessageType<L>,
     {
-        self.verify_ram_address(register, message.buff.len())?;
+        self.verify_ram_address(register, message.length)?;
 
         let mut buffer = [0u8; 10];
         let command = (register & 0x0FFF) | ((Operation::Write as u16) << 12);
 
-        // copy message data into mutable buffer
-        let mut data = [0u8; L];
-        data[..message.buff.len()].copy_from_slice(&message.buff);
+        // payload buffer is zero padded to the size L
+        let data = &message.buff;
 
         buffer[0] = (command >> 8) as u8;
         buffer[1] = (command & 0xFF) as u8;
-        buffer[2..].copy_from_slice(&message.header.into_bytes());
+        buffer[2..].copy_from_slice(&message.header.with_sequence(self.sequence).into_bytes());
 
         for word in buffer[2..].chunks_exact_mut(4) {
             let num = BigEndian::read_u32(word);
             LittleEndian::write_u32(word, num);
         }
-        let mut operations = [SpiOperation::Write(&buffer), SpiOperation::Write(&data)];
+
+        if self.spi_crc {
+            let command = Operation::WriteCrc.crc_command(register, buffer.len() - 2 + L);
+            let crc = crc16(crc16(crc16(CRC16_INIT, &command), &buffer[2..]), data).to_be_bytes();
+
+            let mut operations = [
+                SpiOperation::Write(&command),
+                SpiOperation::Write(&buffer[2..]),
+                SpiOperation::Write(data),
+                SpiOperation::Write(&crc),
+            ];
+            self.device.transaction(&mut operations).map_err(SpiError::BusError)?;
+
+            return Ok(());
+        }
+
+        let mut operations = [SpiOperation::Write(&buffer), SpiOperation::Write(data)];
         self.device.transaction(&mut operations).map_err(SpiError::BusError)?;
 
         Ok(())
     }
 
-    /// Read message from RX FIFO
-    pub(crate) fn read_fifo<const L: usize>(&mut self, register: u16, data: &mut [u8; L]) -> Result<(), CanError<D>> {
+    /// Read message object (header, optional timestamp and payload) from RX FIFO
+    pub(crate) fn read_fifo<const L: usize>(
+        &mut self,
+        register: u16,
+        timestamp: bool,
+    ) -> Result<RxMessage<L>, CanError<D>> {
         if L % 4 != 0 {
             return Err(CanError::InvalidBufferSize(L));
         }
 
-        // Skip Transmit message object header
-        let payload_address = register + 8;
         let mut buffer = [0u8; 2];
+        let mut header = [0u8; 8];
+        let mut timestamp_word = [0u8; 4];
+        let mut data = [0u8; L];
 
-        let command = (payload_address & 0x0FFF) | ((Operation::Read as u16) << 12);
+        let command = (register & 0x0FFF) | ((Operation::Read as u16) << 12);
 
         buffer[0] = (command >> 8) as u8;
         buffer[1] = (command & 0xFF) as u8;
 
-        let mut operations = [SpiOperation::Write(&buffer), SpiOperation::Read(data)];
-        self.device.transaction(&mut operations).map_err(SpiError::BusError)?;
+        // timestamp is stored between header and payload
+        if self.spi_crc {
+            let mut meta = [0u8; 12];
+            let meta_length = if timestamp { 12 } else { 8 };
+            let command = Operation::ReadCrc.crc_command(register, meta_length + L);
+            let mut crc = [0u8; 2];
+
+            let mut operations = [
+                SpiOperation::Write(&command),
+                SpiOperation::Read(&mut meta[..meta_length]),
+                SpiOperation::Read(&mut data),
+                SpiOperation::Read(&mut crc),
+            ];
+            self.device.transaction(&mut operations).map_err(SpiError::BusError)?;
+
+            Self::verify_crc(crc16(crc16(CRC16_INIT, &command), &meta[..meta_length]), &data, crc)?;
+
+            header.copy_from_slice(&meta[..8]);
+            timestamp_word.copy_from_slice(&meta[8..]);
+        } else if timestamp {
+            let mut operations = [
+                SpiOperation::Write(&buffer),
+                SpiOperation::Read(&mut header),
+                SpiOperation::Read(&mut timestamp_word),
+                SpiOperation::Read(&mut data),
+            ];
+            self.device.transaction(&mut operations).map_err(SpiError::BusError)?;
+        } else {
+            let mut operations = [
+                SpiOperation::Write(&buffer),
+                SpiOperation::Read(&mut header),
+                SpiOperation::Read(&mut data),
+            ];
+            self.device.transaction(&mut operations).map_err(SpiError::BusError)?;
+        }
 
-        Ok(())
+        // header words are transferred LSB first, bitfield expects MSB first
+        for word in header.chunks_exact_mut(4) {
+            let num = LittleEndian::read_u32(word);
+            BigEndian::write_u32(word, num);
+        }
+
+        Ok(RxMessage {
+            header: RxHeader::from_bytes(header),
+            timestamp: timestamp.then(|| u32::from_le_bytes(timestamp_word)),
+            buff: data,
+        })
     }
 
     /// 4-byte SFR read
     fn read32(&mut self, register: u16) -> Result<u32, CanError<D>> {
-        // create cmd buffer (2 bytes cmd+addr)
-        let mut buffer = [0u8; 2];
         // payload received buffer
         let mut data = [0u8; 4];
-        let command = (register & 0x0FFF) | ((Operation::Read as u16) << 12);
-
-        buffer[0] = (command >> 8) as u8;
-        buffer[1] = (command & 0xFF) as u8;
-
-        let mut operations = [SpiOperation::Write(&buffer), SpiOperation::Read(&mut data)];
-        self.device.transaction(&mut operations).map_err(SpiError::BusError)?;
+        self.read_buffer(register, &mut data)?;
 
         // SFR addresses are at the LSB of the registers
         // so last read byte is the MSB of the register
@@ -426,6 +1166,49 @@ where
         Ok(result)
     }
 
+    /// Reads consecutive bytes starting at the given SFR or RAM address
+    fn read_buffer(&mut self, register: u16, buffer: &mut [u8]) -> Result<(), CanError<D>> {
+        if !self.spi_crc {
+            let command = Operation::Read.command(register);
+
+            let mut operations = [SpiOperation::Write(&command), SpiOperation::Read(buffer)];
+            self.device.transaction(&mut operations).map_err(SpiError::BusError)?;
+
+            return Ok(());
+        }
+
+        let command = Operation::ReadCrc.crc_command(register, buffer.len());
+        let mut result = Ok(());
+
+        // Reads reporting a CRC mismatch due to an erratum of the variant are repeated
+        for _ in 0..self.variant.crc_read_attempts() {
+            let mut crc = [0u8; 2];
+
+            let mut operations = [
+                SpiOperation::Write(&command),
+                SpiOperation::Read(buffer),
+                SpiOperation::Read(&mut crc),
+            ];
+            self.device.transaction(&mut operations).map_err(SpiError::BusError)?;
+
+            result = Self::verify_crc(crc16(CRC16_INIT, &command), buffer, crc);
+            if result.is_ok() {
+                break;
+            }
+        }
+
+        result
+    }
+
+    /// Compares the received CRC with the CRC of the given intermediate value updated with the remaining data
+    fn verify_crc(crc: u16, data: &[u8], received: [u8; 2]) -> Result<(), CanError<D>> {
+        if crc16(crc, data) != u16::from_be_bytes(received) {
+            return Err(CanError::CrcMismatch);
+        }
+
+        Ok(())
+    }
+
     /// Verify address within RAM bounds
     fn verify_ram_address(&self, addr: u16, data_length: usize) -> Result<(), CanError<D>> {
         if addr < 0x400 || (addr + (data_length as u16)) > 0xBFF {
@@ -436,10 +1219,17 @@ where
     }
 
     /// Reads a single register byte
-    fn read_register(&mut self, register: u16) -> Result<u8, SpiError<D>> {
+    fn read_register(&mut self, register: u16) -> Result<u8, CanError<D>> {
+        if self.spi_crc {
+            let mut data = [0u8; 1];
+            self.read_buffer(register, &mut data)?;
+
+            return Ok(data[0]);
+        }
+
         let mut buffer = self.cmd_buffer(register, Operation::Read);
 
-        self.transfer(&mut buffer)
+        Ok(self.transfer(&mut buffer)?)
     }
 
     /// Executes a SPI transfer with three bytes buffer and returns the last byte received
@@ -471,58 +1261,72 @@ where
         Ok(false)
     }
 
-    /// Returns true if `TXREQ` bit of TX fifo is cleared i.e. all messages contained are transmitted
-    fn txfifo_cleared(&mut self, fifo_ctrl_reg: u16) -> Result<bool, CanError<D>> {
-        // read TX FIFO control register byte 1
-        let txfifo_control_byte1 = self.read_register(fifo_ctrl_reg)?;
-        let txfifo_control_reg = FifoControlReg1::from(txfifo_control_byte1);
+    /// Returns [CanError::BusOff] if the controller is in bus-off state.
+    /// Unless recovering automatically, configuration mode is requested, which aborts all pending transmissions.
+    fn check_bus_off(&mut self) -> Result<(), CanError<D>> {
+        // TXBO bit of CiTREC
+        if self.read_register(REGISTER_C1TREC + 2)? & (1 << 5) == 0 {
+            return Ok(());
+        }
 
-        if txfifo_control_reg.txreq() {
-            return Ok(false);
+        let diagnostics = BusDiagnostics::from_registers(0, self.read32(REGISTER_C1BDIAG0 + 4)?);
+
+        if self.bus_off_recovery != BusOffRecovery::Automatic {
+            self.write_register(REGISTER_C1CON + 3, OperationMode::Configuration as u8 | (1 << 3))?;
         }
-        Ok(true)
-    }
 
-    /// Returns the configuration register address for the given FIFO index
-    fn fifo_control_register(fifo_index: u8) -> u16 {
-        0x05C + 12 * (fifo_index as u16 - 1)
+        Err(CanError::BusOff(diagnostics.error_kind()))
     }
 
-    /// Returns the status register address for the given FIFO index
-    fn fifo_status_register(fifo_index: u8) -> u16 {
-        0x60 + 12 * (fifo_index as u16 - 1)
-    }
+    /// Returns the deadline of a blocking operation starting now, None if blocking operations are unbounded
+    fn deadline(&self) -> Result<Option<Instant<CLK>>, CanError<D>> {
+        let Some(clock) = &self.clock else {
+            return Ok(None);
+        };
 
-    /// Returns the address of fifo user address register for the given index
-    fn fifo_user_address_register(fifo_index: u8) -> u16 {
-        0x64 + 12 * (fifo_index as u16 - 1)
+        let deadline = clock.try_now()?.checked_add(self.timeout).ok_or(CanError::ClockError)?;
+        Ok(Some(deadline))
     }
 
-    /// returns the filter control register address byte of the corresponding filter
-    fn filter_control_register_byte(filter_index: u8) -> u16 {
-        0x1D0 + filter_index as u16
-    }
+    /// Returns the given timeout error if the deadline of a blocking operation is exceeded
+    fn check_deadline(&self, deadline: Option<Instant<CLK>>, timeout_error: CanError<D>) -> Result<(), CanError<D>> {
+        if let (Some(clock), Some(deadline)) = (&self.clock, deadline) {
+            if clock.try_now()? > deadline {
+                return Err(timeout_error);
+            }
+        }
 
-    /// returns the filter object register address of corresponding filter
-    fn filter_object_register(filter_index: u8) -> u16 {
-        0x1F0 + 8 * (filter_index as u16)
+        Ok(())
     }
 
-    /// returns the filter mask register address of corresponding filter
-    fn filter_mask_register(filter_index: u8) -> u16 {
-        0x1F4 + 8 * (filter_index as u16)
+    /// Resets the FIFO of the given control register (FRESET) and waits until the reset is completed
+    /// or the given target instant is exceeded
+    fn reset_fifo(&mut self, control_register: u16, clock: &CLK, target: Instant<CLK>) -> Result<(), CanError<D>> {
+        self.write_register(control_register + 1, 1 << 2)?;
+
+        while FifoControlReg1::from(self.read_register(control_register + 1)?).freset() {
+            if clock.try_now()? > target {
+                return Err(CanError::AbortTimeout);
+            }
+        }
+
+        Ok(())
     }
-}
 
-/// Register operation type
-#[derive(Copy, Clone)]
-enum Operation {
-    Reset = 0b0000,
-    Write = 0b0010,
-    Read = 0b0011,
+    /// Returns true if `TXREQ` bit of TX fifo is cleared i.e. all messages contained are transmitted
+    fn txfifo_cleared(&mut self, fifo_ctrl_reg: u16) -> Result<bool, CanError<D>> {
+        // read TX FIFO control register byte 1
+        let txfifo_control_byte1 = self.read_register(fifo_ctrl_reg)?;
+        let txfifo_control_reg = FifoControlReg1::from(txfifo_control_byte1);
+
+        if txfifo_control_reg.txreq() {
+            return Ok(false);
+        }
+        Ok(true)
+    }
 }
 
-impl<D: SpiDevice> From<embedded_time::clock::Error> for CanError<D> {
+impl<D: ErrorType> From<embedded_time::clock::Error> for CanError<D> {
     fn from(_error: embedded_time::clock::Error) -> Self {
         CanError::ClockError
     }
@@ -0,0 +1,608 @@
+//!# Async CAN controller device
+//! Async variant of [MCP2517](crate::can::MCP2517) based on [embedded_hal_async::spi::SpiDevice].
+//! Instead of polling the FIFO status via SPI, the driver sleeps until the controller signals
+//! the awaited condition on the given interrupt pin (INT), using [embedded_hal_async::digital::Wait].
+//!
+//! The driver is available when the `async` feature is enabled. Configuration and register encoding
+//! is shared with the blocking driver.
+//!
+//! Please note, that the INT pin is asserted as long as any enabled interrupt is pending.
+//! So received messages should be fetched, otherwise waiting for TX FIFO space wakes up immediately.
+//!
+//!```
+//!# use mcp2517::can_async::MCP2517Async;
+//!# use mcp2517::config::Configuration;
+//!# use mcp2517::example::*;
+//!# use mcp2517::message::{Can20, TxMessage};
+//!# use embedded_can::{Id, StandardId};
+//!#
+//!# embassy_futures::block_on(async {
+//! let sys_clk = ExampleClock::default();
+//! let spi_dev = ExampleSPIDevice::default();
+//! let interrupt_pin = ExampleInterruptPin::default();
+//!
+//! let mut controller = MCP2517Async::new(spi_dev, interrupt_pin);
+//! controller.configure(&Configuration::default(), &sys_clk).await.unwrap();
+//!
+//! let can_id = Id::Standard(StandardId::new(0x55).unwrap());
+//! let payload = &[0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8];
+//! let message = TxMessage::new(Can20::<8> {}, payload, can_id).unwrap();
+//!
+//! // Waits until TX FIFO has space for the message
+//! controller.transmit(&message).await.unwrap();
+//!
+//! // Waits until all messages of TX FIFO are transmitted
+//! controller.flush().await.unwrap();
+//!
+//! // Waits until a message is received
+//! let received = controller.receive::<8>().await.unwrap();
+//! assert_eq!(received.get_payload(), [0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]);
+//!# });
+//! ```
+use crate::can::{CanError, SpiError};
+use crate::config::{Configuration, Fifo};
+use crate::filter::Filter;
+use crate::message::{MessageType, RxHeader, RxMessage, TransmitEvent, TxHeader, TxMessage};
+use crate::registers::{
+    crc16, fifo_control_register, fifo_status_register, fifo_user_address_register, filter_control_register_byte,
+    filter_mask_register, filter_object_register, FifoControlReg0, FifoControlReg1, FifoStatusReg0, Operation,
+    RegisterWrite, CRC16_INIT, FIFO_RX_INDEX, RAM_SIZE, REGISTER_C1CON, REGISTER_C1INT, REGISTER_C1TEFCON,
+    REGISTER_C1TEFSTA, REGISTER_C1TEFUA, REGISTER_C1TXQCON,
+};
+use crate::status::{OperationMode, OperationStatus};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use core::marker::PhantomData;
+use embedded_hal::spi::Operation as SpiOperation;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::spi::SpiDevice;
+use embedded_time::duration::Milliseconds;
+use embedded_time::Clock;
+use log::debug;
+
+/// Async MCP2517 CAN controller device
+pub struct MCP2517Async<D: SpiDevice<u8>, INT: Wait, CLK: Clock> {
+    /// Device on SPI bus
+    device: D,
+
+    /// Pin connected to INT output of controller
+    interrupt: INT,
+
+    /// System clock
+    clock: PhantomData<CLK>,
+
+    /// Sequence number assigned to the next transmitted message
+    sequence: u8,
+
+    /// True if timestamps are stored in the transmit event FIFO
+    tef_timestamp: bool,
+
+    /// Bit mask of FIFO indices storing timestamps of received messages
+    rx_timestamps: u32,
+
+    /// True if CRC protected SPI instructions are used
+    spi_crc: bool,
+}
+
+impl<D, INT, CLK> MCP2517Async<D, INT, CLK>
+where
+    D: SpiDevice<u8>,
+    INT: Wait,
+    CLK: Clock,
+{
+    pub fn new(spi_dev: D, interrupt_pin: INT) -> Self {
+        Self {
+            device: spi_dev,
+            interrupt: interrupt_pin,
+            clock: Default::default(),
+            sequence: 0,
+            tef_timestamp: false,
+            rx_timestamps: 0,
+            spi_crc: false,
+        }
+    }
+
+    /// Enables/disables CRC protected SPI instructions (READ_CRC, WRITE_CRC and WRITE_SAFE) for all
+    /// register and RAM accesses. Corrupted reads are reported by [CanError::CrcMismatch], while corrupted
+    /// writes are discarded by the device and flagged by [Interrupt::SpiCrc](crate::status::Interrupt::SpiCrc).
+    pub fn set_spi_crc(&mut self, enabled: bool) {
+        self.spi_crc = enabled;
+    }
+
+    /// Configures the controller with the given settings.
+    /// In addition, the RX FIFO not empty interrupt is enabled.
+    pub async fn configure(&mut self, config: &Configuration, clock: &CLK) -> Result<(), CanError<D>> {
+        let ram_usage = config.ram_usage();
+        if ram_usage > RAM_SIZE {
+            return Err(CanError::RamSizeExceeded(ram_usage));
+        }
+
+        let writes = config.register_writes().map_err(CanError::InvalidBitTiming)?;
+
+        self.enable_mode(OperationMode::Configuration, clock, CanError::ConfigurationModeTimeout)
+            .await?;
+
+        // RX FIFO not empty interrupt, set on top of the configured RX FIFO settings
+        let rx_control_register = fifo_control_register(FIFO_RX_INDEX);
+        let rx_control_reg0: u8 = FifoControlReg0::new().with_tfnrfnie(true).into();
+
+        for write in writes {
+            match write {
+                RegisterWrite::Byte(register, value) => self.write_register(register, value).await?,
+                RegisterWrite::Word(register, value) if register == rx_control_register => {
+                    self.write32(register, value | rx_control_reg0 as u32).await?
+                }
+                RegisterWrite::Word(register, value) => self.write32(register, value).await?,
+                RegisterWrite::InitializeRam => self.initialize_ram().await?,
+            }
+        }
+
+        // Without FIFO table entry, the first RX FIFO control byte is not written by the configuration
+        if config.fifo_table.get(Fifo::RX).is_none() {
+            self.write_register(rx_control_register, rx_control_reg0).await?;
+        }
+
+        // RXIE and TXIE in addition to the configured interrupts, TX FIFO interrupts are enabled on demand
+        let int_enable_reg0 = (config.interrupts.as_int_register() >> 16) as u8;
+        self.write_register(REGISTER_C1INT + 2, int_enable_reg0 | 0b0000_0011).await?;
+
+        self.enable_mode(config.mode.to_operation_mode(), clock, CanError::RequestModeTimeout)
+            .await?;
+
+        self.sequence = 0;
+        self.tef_timestamp = config.tef.is_some_and(|tef| tef.timestamp);
+        self.rx_timestamps = config.rx_timestamp_fifos();
+
+        Ok(())
+    }
+
+    /// Waits until the TX FIFO has space for the message and requests transmission
+    pub async fn transmit<const L: usize, T: MessageType<L>>(
+        &mut self,
+        message: &TxMessage<T, L>,
+    ) -> Result<(), CanError<D>> {
+        self.transmit_fifo(Fifo::TX, message).await
+    }
+
+    /// Waits until the given transmit FIFO has space for the message and requests transmission
+    pub async fn transmit_fifo<const L: usize, T: MessageType<L>>(
+        &mut self,
+        fifo: Fifo,
+        message: &TxMessage<T, L>,
+    ) -> Result<(), CanError<D>> {
+        self.transmit_message(fifo_control_register(fifo.index()), message).await
+    }
+
+    /// Waits until the transmit queue (TXQ) has space for the message and requests transmission.
+    /// TXQ must be enabled by configuration.
+    pub async fn transmit_txq<const L: usize, T: MessageType<L>>(
+        &mut self,
+        message: &TxMessage<T, L>,
+    ) -> Result<(), CanError<D>> {
+        self.transmit_message(REGISTER_C1TXQCON, message).await
+    }
+
+    /// Waits until all messages in TX FIFO are transmitted
+    pub async fn flush(&mut self) -> Result<(), CanError<D>> {
+        if !self.txfifo_cleared().await? {
+            self.wait_for_tx_fifo_interrupt(
+                fifo_control_register(Fifo::TX.index()),
+                FifoControlReg0::new().with_tferffie(true),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Waits until RX FIFO contains at least one message and returns it
+    pub async fn receive<const L: usize>(&mut self) -> Result<RxMessage<L>, CanError<D>> {
+        self.receive_fifo(Fifo::RX).await
+    }
+
+    /// Waits until the given receive FIFO contains at least one message and returns it.
+    /// The FIFO not empty interrupt must be enabled to get notified about the reception.
+    pub async fn receive_fifo<const L: usize>(&mut self, fifo: Fifo) -> Result<RxMessage<L>, CanError<D>> {
+        let status_reg = fifo_status_register(fifo.index());
+
+        while !self.fifo_tfnrfnif(status_reg).await? {
+            self.interrupt.wait_for_low().await.map_err(|_| CanError::InterruptPinError)?;
+        }
+
+        let user_address = self.read32(fifo_user_address_register(fifo.index())).await?;
+        let timestamp = self.rx_timestamps & (1 << fifo.index()) != 0;
+        let message = self.read_fifo(0x400 + user_address as u16, timestamp).await?;
+
+        // set UINC bit for incrementing the FIFO head by a single message
+        self.write_register(fifo_control_register(fifo.index()) + 1, 1).await?;
+
+        Ok(message)
+    }
+
+    /// Set corresponding filter and mask registers
+    pub async fn set_filter_object(&mut self, filter: Filter) -> Result<(), CanError<D>> {
+        let filter_control_reg = filter_control_register_byte(filter.index);
+
+        self.write_register(filter_control_reg, 0x00).await?;
+
+        self.write32(filter_object_register(filter.index), u32::from(filter.filter_bits))
+            .await?;
+        self.write32(filter_mask_register(filter.index), u32::from(filter.mask_bits))
+            .await?;
+
+        self.write_register(filter_control_reg, (1 << 7) | FIFO_RX_INDEX).await?;
+
+        Ok(())
+    }
+
+    /// Reads and returns the operation status
+    pub async fn read_operation_status(&mut self) -> Result<OperationStatus, CanError<D>> {
+        let data = self.read_register(REGISTER_C1CON + 2).await?;
+
+        Ok(OperationStatus::from_register(data))
+    }
+
+    /// Returns the sequence number assigned to the next transmitted message.
+    /// Sequence numbers are incremented on each transmission and wrap around after 127.
+    pub fn next_sequence(&self) -> u8 {
+        self.sequence
+    }
+
+    /// Reads the oldest event of the transmit event FIFO (TEF), which must be enabled by configuration.
+    /// Returns [CanError::TefEmptyErr] if no message was transmitted since the last call.
+    pub async fn read_transmit_event(&mut self) -> Result<TransmitEvent, CanError<D>> {
+        // TEFNEIF: TEF not empty
+        if self.read_register(REGISTER_C1TEFSTA).await? & 1 == 0 {
+            return Err(CanError::TefEmptyErr);
+        }
+
+        let user_address = self.read32(REGISTER_C1TEFUA).await?;
+        let address = (user_address + 0x400) as u16;
+
+        // header is followed by the optional timestamp
+        let mut event = [0u8; 12];
+        let length = if self.tef_timestamp { 12 } else { 8 };
+        self.read_buffer(address, &mut event[..length]).await?;
+
+        let mut header = [0u8; 8];
+        header.copy_from_slice(&event[..8]);
+
+        for word in header.chunks_exact_mut(4) {
+            let num = LittleEndian::read_u32(word);
+            BigEndian::write_u32(word, num);
+        }
+
+        // set UINC bit for incrementing the TEF tail by a single event
+        self.write_register(REGISTER_C1TEFCON + 1, 1).await?;
+
+        Ok(TransmitEvent {
+            header: TxHeader::from_bytes(header),
+            timestamp: self.tef_timestamp.then(|| LittleEndian::read_u32(&event[8..])),
+        })
+    }
+
+    /// Reset internal register to default and switch to Configuration mode
+    pub async fn reset(&mut self) -> Result<(), CanError<D>> {
+        let mut buffer = [0u8; 3];
+        buffer[..2].copy_from_slice(&Operation::Reset.command(0));
+
+        self.device.transfer_in_place(&mut buffer).await.map_err(SpiError::BusError)?;
+
+        Ok(())
+    }
+
+    /// Waits until the TX FIFO/TXQ of the given control register has space for the message and requests
+    /// transmission. Status and user address registers are following the control register.
+    async fn transmit_message<const L: usize, T: MessageType<L>>(
+        &mut self,
+        control_register: u16,
+        message: &TxMessage<T, L>,
+    ) -> Result<(), CanError<D>> {
+        if !self.fifo_tfnrfnif(control_register + 4).await? {
+            self.wait_for_tx_fifo_interrupt(control_register, FifoControlReg0::new().with_tfnrfnie(true))
+                .await?;
+        }
+
+        // make sure length of payload is consistent with CAN operation mode
+        let operation_status = self.read_operation_status().await?;
+
+        if message.length > 8 && operation_status.mode != OperationMode::NormalCANFD {
+            return Err(CanError::InvalidPayloadLength(message.length));
+        }
+
+        let user_address = self.read32(control_register + 8).await?;
+        let address = user_address + 0x400;
+
+        self.write_fifo::<T, L>(address as u16, message).await?;
+
+        // Request transmission (set txreq) and set uinc in TX FIFO control register byte 1
+        self.write_register(control_register + 1, 0x03).await?;
+
+        // sequence number is 7 bits wide
+        self.sequence = (self.sequence + 1) & 0x7F;
+
+        Ok(())
+    }
+
+    /// Enables the given TX FIFO/TXQ interrupt and waits until the interrupt flag is set.
+    /// Interrupt is disabled afterward.
+    async fn wait_for_tx_fifo_interrupt(
+        &mut self,
+        control_register: u16,
+        interrupt: FifoControlReg0,
+    ) -> Result<(), CanError<D>> {
+        let status_reg = control_register + 4;
+        let interrupt_mask: u8 = interrupt.into();
+        let control_reg0 = self.read_register(control_register).await?;
+
+        self.write_register(control_register, control_reg0 | interrupt_mask).await?;
+
+        // Flag bits are at the same position as the corresponding enable bits
+        while self.read_register(status_reg).await? & interrupt_mask == 0 {
+            self.interrupt.wait_for_low().await.map_err(|_| CanError::InterruptPinError)?;
+        }
+
+        self.write_register(control_register, control_reg0).await?;
+
+        Ok(())
+    }
+
+    /// Enters the given mode, aborts all running transactions
+    /// and waits max. 2 ms for the given mode to be reached
+    async fn enable_mode(
+        &mut self,
+        mode: OperationMode,
+        clock: &CLK,
+        timeout_error: CanError<D>,
+    ) -> Result<(), CanError<D>> {
+        self.write_register(REGISTER_C1CON + 3, mode as u8 | (1 << 3)).await?;
+
+        let target = clock.try_now()?.checked_add(Milliseconds::new(2)).ok_or(CanError::ClockError)?;
+
+        while self.read_operation_status().await?.mode != mode {
+            if clock.try_now()? > target {
+                debug!("Device did not enter config mode within timeout. Current mode: {mode:?}");
+                return Err(timeout_error);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns if the TX/RX fifo not full/empty flag is set
+    async fn fifo_tfnrfnif(&mut self, fifo_reg_addr: u16) -> Result<bool, CanError<D>> {
+        let fifo_status_reg0 = FifoStatusReg0::from(self.read_register(fifo_reg_addr).await?);
+
+        Ok(fifo_status_reg0.tfnrfnif())
+    }
+
+    /// Returns true if `TXREQ` bit of TX fifo is cleared i.e. all messages contained are transmitted
+    async fn txfifo_cleared(&mut self) -> Result<bool, CanError<D>> {
+        let control_byte1 = self.read_register(fifo_control_register(Fifo::TX.index()) + 1).await?;
+
+        Ok(!FifoControlReg1::from(control_byte1).txreq())
+    }
+
+    /// Insert message object in TX FIFO
+    async fn write_fifo<T, const L: usize>(
+        &mut self,
+        register: u16,
+        message: &TxMessage<T, L>,
+    ) -> Result<(), CanError<D>>
+    where
+        T: MessageType<L>,
+    {
+        if register < 0x400 || (register + (message.length as u16)) > 0xBFF {
+            return Err(CanError::InvalidRamAddress(register));
+        }
+
+        let mut buffer = [0u8; 10];
+        buffer[..2].copy_from_slice(&Operation::Write.command(register));
+        buffer[2..].copy_from_slice(&message.header.with_sequence(self.sequence).into_bytes());
+
+        for word in buffer[2..].chunks_exact_mut(4) {
+            let num = BigEndian::read_u32(word);
+            LittleEndian::write_u32(word, num);
+        }
+
+        // payload buffer is zero padded to the size L
+        let data = &message.buff;
+
+        if self.spi_crc {
+            let command = Operation::WriteCrc.crc_command(register, buffer.len() - 2 + L);
+            let crc = crc16(crc16(crc16(CRC16_INIT, &command), &buffer[2..]), data).to_be_bytes();
+
+            let mut operations = [
+                SpiOperation::Write(&command),
+                SpiOperation::Write(&buffer[2..]),
+                SpiOperation::Write(data),
+                SpiOperation::Write(&crc),
+            ];
+            self.device.transaction(&mut operations).await.map_err(SpiError::BusError)?;
+
+            return Ok(());
+        }
+
+        let mut operations = [SpiOperation::Write(&buffer), SpiOperation::Write(data)];
+        self.device.transaction(&mut operations).await.map_err(SpiError::BusError)?;
+
+        Ok(())
+    }
+
+    /// Read message object (header and payload) from RX FIFO
+    async fn read_fifo<const L: usize>(&mut self, register: u16, timestamp: bool) -> Result<RxMessage<L>, CanError<D>> {
+        if L % 4 != 0 {
+            return Err(CanError::InvalidBufferSize(L));
+        }
+
+        let command = Operation::Read.command(register);
+        let mut header = [0u8; 8];
+        let mut timestamp_word = [0u8; 4];
+        let mut data = [0u8; L];
+
+        // timestamp is stored between header and payload
+        if self.spi_crc {
+            let mut meta = [0u8; 12];
+            let meta_length = if timestamp { 12 } else { 8 };
+            let command = Operation::ReadCrc.crc_command(register, meta_length + L);
+            let mut crc = [0u8; 2];
+
+            let mut operations = [
+                SpiOperation::Write(&command),
+                SpiOperation::Read(&mut meta[..meta_length]),
+                SpiOperation::Read(&mut data),
+                SpiOperation::Read(&mut crc),
+            ];
+            self.device.transaction(&mut operations).await.map_err(SpiError::BusError)?;
+
+            Self::verify_crc(crc16(crc16(CRC16_INIT, &command), &meta[..meta_length]), &data, crc)?;
+
+            header.copy_from_slice(&meta[..8]);
+            timestamp_word.copy_from_slice(&meta[8..]);
+        } else if timestamp {
+            let mut operations = [
+                SpiOperation::Write(&command),
+                SpiOperation::Read(&mut header),
+                SpiOperation::Read(&mut timestamp_word),
+                SpiOperation::Read(&mut data),
+            ];
+            self.device.transaction(&mut operations).await.map_err(SpiError::BusError)?;
+        } else {
+            let mut operations = [
+                SpiOperation::Write(&command),
+                SpiOperation::Read(&mut header),
+                SpiOperation::Read(&mut data),
+            ];
+            self.device.transaction(&mut operations).await.map_err(SpiError::BusError)?;
+        }
+
+        for word in header.chunks_exact_mut(4) {
+            let num = LittleEndian::read_u32(word);
+            BigEndian::write_u32(word, num);
+        }
+
+        Ok(RxMessage {
+            header: RxHeader::from_bytes(header),
+            timestamp: timestamp.then(|| u32::from_le_bytes(timestamp_word)),
+            buff: data,
+        })
+    }
+
+    /// Writes a single register byte
+    async fn write_register(&mut self, register: u16, value: u8) -> Result<(), SpiError<D>> {
+        if self.spi_crc {
+            let command = Operation::WriteSafe.command(register);
+            let crc = crc16(crc16(CRC16_INIT, &command), &[value]).to_be_bytes();
+
+            let buffer = [command[0], command[1], value, crc[0], crc[1]];
+            return self.device.write(&buffer).await.map_err(SpiError::BusError);
+        }
+
+        let mut buffer = [0u8; 3];
+        buffer[..2].copy_from_slice(&Operation::Write.command(register));
+        buffer[2] = value;
+
+        self.device.transfer_in_place(&mut buffer).await.map_err(SpiError::BusError)
+    }
+
+    /// Reads a single register byte
+    async fn read_register(&mut self, register: u16) -> Result<u8, CanError<D>> {
+        if self.spi_crc {
+            let mut data = [0u8; 1];
+            self.read_buffer(register, &mut data).await?;
+
+            return Ok(data[0]);
+        }
+
+        let mut buffer = [0u8; 3];
+        buffer[..2].copy_from_slice(&Operation::Read.command(register));
+
+        self.device.transfer_in_place(&mut buffer).await.map_err(SpiError::BusError)?;
+
+        Ok(buffer[2])
+    }
+
+    /// 4-byte SFR write
+    async fn write32(&mut self, register: u16, value: u32) -> Result<(), SpiError<D>> {
+        if self.spi_crc {
+            return self.write_buffer(register, &value.to_le_bytes()).await;
+        }
+
+        let mut buffer = [0u8; 6];
+        buffer[..2].copy_from_slice(&Operation::Write.command(register));
+        buffer[2..].copy_from_slice(&value.to_le_bytes());
+
+        self.device.write(&buffer).await.map_err(SpiError::BusError)
+    }
+
+    /// Zeroes the entire message RAM
+    async fn initialize_ram(&mut self) -> Result<(), SpiError<D>> {
+        let data = [0u8; 64];
+
+        for address in (0x400..0x400 + RAM_SIZE as u16).step_by(data.len()) {
+            self.write_buffer(address, &data).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes consecutive bytes starting at the given SFR or RAM address
+    async fn write_buffer(&mut self, register: u16, data: &[u8]) -> Result<(), SpiError<D>> {
+        if !self.spi_crc {
+            let command = Operation::Write.command(register);
+
+            let mut operations = [SpiOperation::Write(&command), SpiOperation::Write(data)];
+            return self.device.transaction(&mut operations).await.map_err(SpiError::BusError);
+        }
+
+        let command = Operation::WriteCrc.crc_command(register, data.len());
+        let crc = crc16(crc16(CRC16_INIT, &command), data).to_be_bytes();
+
+        let mut operations = [
+            SpiOperation::Write(&command),
+            SpiOperation::Write(data),
+            SpiOperation::Write(&crc),
+        ];
+        self.device.transaction(&mut operations).await.map_err(SpiError::BusError)
+    }
+
+    /// 4-byte SFR read
+    async fn read32(&mut self, register: u16) -> Result<u32, CanError<D>> {
+        let mut data = [0u8; 4];
+        self.read_buffer(register, &mut data).await?;
+
+        Ok(u32::from_le_bytes(data))
+    }
+
+    /// Reads consecutive bytes starting at the given SFR or RAM address
+    async fn read_buffer(&mut self, register: u16, buffer: &mut [u8]) -> Result<(), CanError<D>> {
+        if !self.spi_crc {
+            let command = Operation::Read.command(register);
+
+            let mut operations = [SpiOperation::Write(&command), SpiOperation::Read(buffer)];
+            self.device.transaction(&mut operations).await.map_err(SpiError::BusError)?;
+
+            return Ok(());
+        }
+
+        let command = Operation::ReadCrc.crc_command(register, buffer.len());
+        let mut crc = [0u8; 2];
+
+        let mut operations = [
+            SpiOperation::Write(&command),
+            SpiOperation::Read(buffer),
+            SpiOperation::Read(&mut crc),
+        ];
+        self.device.transaction(&mut operations).await.map_err(SpiError::BusError)?;
+
+        Self::verify_crc(crc16(CRC16_INIT, &command), buffer, crc)
+    }
+
+    /// Compares the received CRC with the CRC of the given intermediate value updated with the remaining data
+    fn verify_crc(crc: u16, data: &[u8], received: [u8; 2]) -> Result<(), CanError<D>> {
+        if crc16(crc, data) != u16::from_be_bytes(received) {
+            return Err(CanError::CrcMismatch);
+        }
+
+        Ok(())
+    }
+}
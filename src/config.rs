@@ -1,8 +1,8 @@
 //! # CAN Module configuration
 //! The [Configuration] struct provides an abstraction for configuring the CAN module registers.
 //! ## Fifo configuration
-//! The following example shows a FIFO buffer configuration. At the moment, there is one TX Fifo and one
-//! RX Fifo. The configuration sets the max payload size of messages transmitted/received in both Fifo buffers
+//! The following example shows a FIFO buffer configuration of the default TX Fifo (FIFO 2) and RX Fifo (FIFO 1).
+//! The configuration sets the max payload size of messages transmitted/received in both Fifo buffers
 //! to 8 bytes. The number of message the RX Fifo buffer can hold is 10 while it is 32 for the TX Fifo.
 //!
 //! The priority for the messages in the TX Fifo are given the highest possible priority (32) and the retransmission
@@ -19,6 +19,147 @@
 //!    tx_size: 32,
 //! };
 //!```
+//! ## FIFO table
+//! Additional FIFOs (up to FIFO 31) are configured by [FifoTable]. Entries for FIFO 1 and 2 take precedence
+//! over [FifoConfiguration]. The configured FIFOs must fit in the 2 KB message RAM, which is validated when
+//! configuring the controller. Messages are transmitted/received using the FIFO handle, e.g. by
+//! [transmit_fifo](crate::can::CanController::transmit_fifo).
+//!```
+//!# use mcp2517::config::{Fifo, FifoDirection, FifoSettings, FifoTable, PayloadSize, RetransmissionAttempts};
+//!#
+//! let high_priority_fifo = Fifo::new(3).unwrap();
+//! let event_fifo = Fifo::new(4).unwrap();
+//!
+//! let fifo_table = FifoTable::default()
+//!     .with(high_priority_fifo, FifoSettings {
+//!         direction: FifoDirection::Transmit,
+//!         size: 8,
+//!         pl_size: PayloadSize::SixtyFourBytes,
+//!         priority: 31,
+//!         attempts: RetransmissionAttempts::Three,
+//!         ..Default::default()
+//!     })
+//!     .with(event_fifo, FifoSettings {
+//!         direction: FifoDirection::Receive,
+//!         size: 16,
+//!         timestamp: true,
+//!         ..Default::default()
+//!     });
+//!```
+//! ## Transmit queue
+//! The transmit queue (TXQ) is enabled by [Configuration::txq]. In contrast to a TX FIFO, the TXQ transmits
+//! the message with the highest priority ID first, regardless of the order the messages are loaded in.
+//! Messages are loaded by [transmit_txq](crate::can::CanController::transmit_txq).
+//!```
+//!# use mcp2517::config::{Configuration, PayloadSize, RetransmissionAttempts, TxQueueConfiguration};
+//!#
+//! let config = Configuration {
+//!     txq: Some(TxQueueConfiguration {
+//!         size: 16,
+//!         pl_size: PayloadSize::EightBytes,
+//!         priority: 31,
+//!         attempts: RetransmissionAttempts::Unlimited,
+//!     }),
+//!     ..Default::default()
+//! };
+//!```
+//! ## Transmit event FIFO
+//! The transmit event FIFO (TEF) is enabled by [Configuration::tef]. Each transmitted message is numbered
+//! by the driver, its sequence number and ID are stored in the TEF once the message is sent on the bus.
+//!```
+//!# use mcp2517::config::{Configuration, TefConfiguration};
+//!#
+//! let config = Configuration {
+//!     tef: Some(TefConfiguration {
+//!         size: 8,
+//!         timestamp: false,
+//!     }),
+//!     ..Default::default()
+//! };
+//!```
+//! ## Timestamping
+//! The time base counter is enabled by [Configuration::timestamp]. Timestamps are stored in transmit events if
+//! enabled by [TefConfiguration::timestamp] and in received messages of FIFOs enabled by [FifoSettings::timestamp].
+//! Timestamps are counter ticks, which are converted by [TimestampConfiguration::to_duration] based on the
+//! SYSCLK frequency of [Configuration::sys_clk].
+//!```
+//!# use mcp2517::config::{BitRateConfig, CanBaudRate, Configuration, SysClk, TimestampConfiguration, TimestampEdge};
+//!# use embedded_time::duration::Nanoseconds;
+//!#
+//! // Counter is incremented every microsecond
+//! let timestamp = TimestampConfiguration {
+//!     prescaler: 40,
+//!     edge: TimestampEdge::StartOfFrame,
+//! };
+//!
+//! let config = Configuration {
+//!     timestamp: Some(timestamp),
+//!     bit_rate: BitRateConfig {
+//!         sys_clk: SysClk::Mhz40,
+//!         can_speed: CanBaudRate::Kpbs500,
+//!     },
+//!     ..Default::default()
+//! };
+//!
+//! assert_eq!(Nanoseconds::<u64>::new(1_500_000), timestamp.to_duration(config.sys_clk(), 1_500));
+//!```
+//! ## Interrupts
+//! Interrupts signaled on the INT pin are enabled by [Configuration::interrupts]. FIFO interrupts are
+//! additionally enabled per FIFO by [FifoSettings::interrupts].
+//!```
+//!# use mcp2517::config::{Configuration, Fifo, FifoInterrupts, FifoSettings, FifoTable, InterruptConfiguration};
+//!#
+//! let config = Configuration {
+//!     fifo_table: FifoTable::default().with(Fifo::RX, FifoSettings {
+//!         size: 16,
+//!         interrupts: FifoInterrupts {
+//!             not_empty_or_not_full: true,
+//!             ..Default::default()
+//!         },
+//!         ..Default::default()
+//!     }),
+//!     interrupts: InterruptConfiguration {
+//!         receive: true,
+//!         bus_error: true,
+//!         ..Default::default()
+//!     },
+//!     ..Default::default()
+//! };
+//!```
+//! ## I/O pins
+//! INT0/GPIO0 and INT1/GPIO1 are configured by [Configuration::io] as interrupt outputs or GPIOs. GPIO0 may
+//! control the standby pin of the transceiver, which is driven high while the controller is in sleep mode.
+//!```
+//!# use mcp2517::config::{Configuration, IoConfiguration, OutputDriver, PinMode};
+//!#
+//! let config = Configuration {
+//!     io: IoConfiguration {
+//!         transceiver_standby: true,
+//!         pin1: PinMode::Output(false),
+//!         interrupt_output: OutputDriver::OpenDrain,
+//!         ..Default::default()
+//!     },
+//!     ..Default::default()
+//! };
+//!```
+//! ## ECC
+//! ECC of the message RAM is enabled by [Configuration::ecc]. The entire message RAM is zeroed during
+//! configuration, so reading unused message objects does not cause ECC errors.
+//!```
+//!# use mcp2517::config::{Configuration, EccConfiguration, InterruptConfiguration};
+//!#
+//! let config = Configuration {
+//!     ecc: Some(EccConfiguration {
+//!         single_error_interrupt: false,
+//!         double_error_interrupt: true,
+//!     }),
+//!     interrupts: InterruptConfiguration {
+//!         ecc: true,
+//!         ..Default::default()
+//!     },
+//!     ..Default::default()
+//! };
+//!```
 //! ## Clock configuration
 //! The CAN system clock is determined through setting the `system_clock` and `pll`. In this example,
 //! the pll setting used is to directly use the crystal oscillator without any multiplication, and the
@@ -34,11 +175,14 @@
 //!    system_clock: SystemClockDivisor::DivideBy1,
 //!    pll: PLLSetting::DirectXTALOscillator,
 //!    disable_clock: false,
+//!    low_power_mode: false,
 //! };
 //!```
 //! ## Bit rate configuration
 //! It is recommended to use a SYSCLK frequency of 20 MHz or 40 MHz for the MCP2517FD CAN chip.
 //! Based on the SYSCLK frequency used and the baud rate chosen, the CiNBTCFG regsiter values are configured.
+//! Other SYSCLK frequencies, bit rates or sample points are supported by a custom bit timing calculated
+//! by [BitTiming].
 //!```
 //!# use mcp2517::config::{BitRateConfig,CanBaudRate,SysClk};
 //!#
@@ -46,12 +190,43 @@
 //!    sys_clk: SysClk::MHz20,
 //!    can_speed: CanBaudRate::Kpbs500
 //! };
+//!```
+//! ## Data bit rate configuration
+//! Bit rate switched CAN FD frames are transmitted with the data bit rate during the data phase.
+//! The CiDBTCFG register values are configured based on the chosen data baud rate and the SYSCLK frequency
+//! of the nominal [BitRateConfig], see [Configuration::sys_clk].
+//! In addition, the transmitter delay compensation (CiTDC) is configured, which is required for data bit rates
+//! above 1 Mbps. In automatic mode, the offset is set to the data phase sample point.
+//!```
+//!# use mcp2517::config::{BitRateConfig,CanBaudRate,Configuration,DataBaudRate,DataBitRateConfig,SysClk,TdcMode};
+//!#
+//! let config = Configuration {
+//!     bit_rate: BitRateConfig {
+//!         sys_clk: SysClk::Mhz40,
+//!         can_speed: CanBaudRate::Kpbs500,
+//!     },
+//!     data_bit_rate: DataBitRateConfig {
+//!         can_speed: DataBaudRate::Mbps2,
+//!         tdc: TdcMode::Automatic,
+//!     },
+//!     ..Default::default()
+//! };
 //!
-//!
+//! assert_eq!(40_000_000, config.sys_clk());
+//! assert_eq!(2_000_000, config.data_bit_timing().unwrap().bit_rate());
+//!```
+use crate::bit_timing::{BitTiming, BitTimingError};
+use crate::registers::{
+    fifo_control_register, filter_control_register_byte, FifoControlReg0, FifoControlReg2, FifoControlReg3,
+    RegisterWrite, C1DBTCFG, C1NBTCFG, C1TDC, FIFO_RX_INDEX, FIFO_TX_INDEX, MAX_FIFO_INDEX, REGISTER_C1CON,
+    REGISTER_C1DBTCFG, REGISTER_C1INT, REGISTER_C1NBTCFG, REGISTER_C1TDC, REGISTER_C1TEFCON, REGISTER_C1TSCON,
+    REGISTER_C1TXQCON, REGISTER_ECCCON, REGISTER_IOCON, REGISTER_OSC,
+};
 use crate::status::OperationMode;
+use embedded_time::duration::{Milliseconds, Nanoseconds};
 
 /// Entire configuration currently supported
-#[derive(Default, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Configuration {
     /// Oscillator/Clock configuration
     pub clock: ClockConfiguration,
@@ -59,11 +234,208 @@ pub struct Configuration {
     /// TX/RX FIFO configuration
     pub fifo: FifoConfiguration,
 
+    /// Configuration of additional FIFOs
+    pub fifo_table: FifoTable,
+
+    /// Transmit queue (TXQ) configuration, TXQ is disabled if None
+    pub txq: Option<TxQueueConfiguration>,
+
+    /// Transmit event FIFO (TEF) configuration, TEF is disabled if None
+    pub tef: Option<TefConfiguration>,
+
+    /// Time base counter configuration used for timestamping, counter is disabled if None
+    pub timestamp: Option<TimestampConfiguration>,
+
+    /// Interrupts signaled on the INT pin
+    pub interrupts: InterruptConfiguration,
+
+    /// Configuration of the INT0/GPIO0, INT1/GPIO1, CLKO/SOF and interrupt pins
+    pub io: IoConfiguration,
+
+    /// ECC of the message RAM, ECC is disabled if None
+    pub ecc: Option<EccConfiguration>,
+
+    /// Filter of bus activity waking the controller from sleep mode, filter is disabled if None.
+    /// Defaults to the enabled filter with [WakeUpFilterTime::T11], as after reset.
+    pub wake_up_filter: Option<WakeUpFilterTime>,
+
+    /// Handling of bus-off state detected during blocking transmission
+    pub bus_off_recovery: BusOffRecovery,
+
     /// Target request/operation mode
     pub mode: RequestMode,
 
     /// Bit rate config
     pub bit_rate: BitRateConfig,
+
+    /// Bit rate config of CAN FD data phase
+    pub data_bit_rate: DataBitRateConfig,
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Self {
+            clock: ClockConfiguration::default(),
+            fifo: FifoConfiguration::default(),
+            fifo_table: FifoTable::default(),
+            txq: None,
+            tef: None,
+            timestamp: None,
+            interrupts: InterruptConfiguration::default(),
+            io: IoConfiguration::default(),
+            ecc: None,
+            wake_up_filter: Some(WakeUpFilterTime::default()),
+            bus_off_recovery: BusOffRecovery::default(),
+            mode: RequestMode::default(),
+            bit_rate: BitRateConfig::default(),
+            data_bit_rate: DataBitRateConfig::default(),
+        }
+    }
+}
+
+impl Configuration {
+    /// Returns the SYSCLK frequency in Hz, which is given by the nominal bit rate configuration.
+    /// For a custom nominal bit timing, this is the SYSCLK frequency the timing was calculated for.
+    pub const fn sys_clk(&self) -> u32 {
+        self.bit_rate.timing().sys_clk()
+    }
+
+    /// Returns the bit timing of the CAN FD data phase, based on the SYSCLK frequency of [Configuration::sys_clk]
+    pub const fn data_bit_timing(&self) -> Result<BitTiming, BitTimingError> {
+        self.data_bit_rate.timing(self.sys_clk())
+    }
+
+    /// Returns the register writes applying this configuration, in order of execution.
+    /// Requires the controller to be in configuration mode.
+    pub(crate) fn register_writes(&self) -> Result<impl Iterator<Item = RegisterWrite> + '_, BitTimingError> {
+        let data_timing = self.data_bit_timing()?;
+        let nbr_reg = C1NBTCFG::from_bytes(self.bit_rate.calculate_values()).into();
+        let dbr_reg = C1DBTCFG::from_bytes(data_timing.register_values()).into();
+        let filter_control_reg = filter_control_register_byte(0);
+
+        let rx_fifo_writes = match self.fifo_table.get(Fifo::RX) {
+            Some(_) => None,
+            None => Some(RegisterWrite::Byte(
+                fifo_control_register(FIFO_RX_INDEX) + 3,
+                self.fifo.as_rx_register_3(),
+            )),
+        };
+
+        let tx_fifo_writes = match self.fifo_table.get(Fifo::TX) {
+            Some(_) => None,
+            None => Some([
+                RegisterWrite::Byte(fifo_control_register(FIFO_TX_INDEX) + 2, self.fifo.as_tx_register_2()),
+                RegisterWrite::Byte(fifo_control_register(FIFO_TX_INDEX) + 3, self.fifo.as_tx_register_3()),
+                RegisterWrite::Byte(fifo_control_register(FIFO_TX_INDEX), self.fifo.as_tx_register_0()),
+            ]),
+        };
+
+        let txq_writes = self
+            .txq
+            .map(|txq| RegisterWrite::Word(REGISTER_C1TXQCON, txq.as_control_register()));
+
+        let tef_writes = self.tef.map(|tef| {
+            // TEF interrupt is signaled once the TEF is not empty
+            let tef_interrupt = self.interrupts.transmit_event as u32;
+            RegisterWrite::Word(REGISTER_C1TEFCON, tef.as_control_register() | tef_interrupt)
+        });
+
+        let timestamp_writes = self
+            .timestamp
+            .map(|timestamp| RegisterWrite::Word(REGISTER_C1TSCON, timestamp.as_tscon_register()));
+
+        // RAM must be initialized once ECC is enabled, as reading uninitialized RAM causes ECC errors
+        let ecc_writes = self.ecc.map(|ecc| {
+            [
+                RegisterWrite::Word(REGISTER_ECCCON, ecc.as_ecccon_register()),
+                RegisterWrite::InitializeRam,
+            ]
+        });
+
+        let table_writes = self.fifo_table.iter().map(|(fifo, settings)| {
+            RegisterWrite::Word(fifo_control_register(fifo.index()), settings.as_control_register())
+        });
+
+        let writes = [
+            RegisterWrite::Byte(REGISTER_OSC, self.clock.as_register()),
+            RegisterWrite::Word(REGISTER_IOCON, self.io.as_iocon_register()),
+            RegisterWrite::Word(REGISTER_C1NBTCFG, nbr_reg),
+            RegisterWrite::Word(REGISTER_C1DBTCFG, dbr_reg),
+            RegisterWrite::Word(REGISTER_C1TDC, self.data_bit_rate.as_tdc_register(&data_timing)),
+            RegisterWrite::Byte(REGISTER_C1CON + 1, self.as_con_register_1()),
+            RegisterWrite::Byte(REGISTER_C1CON + 2, self.as_con_register_2()),
+        ]
+        .into_iter()
+        .chain(txq_writes)
+        .chain(tef_writes)
+        .chain(timestamp_writes)
+        .chain([RegisterWrite::Word(REGISTER_C1INT, self.interrupts.as_int_register())])
+        .chain(ecc_writes.into_iter().flatten())
+        .chain(rx_fifo_writes)
+        .chain(tx_fifo_writes.into_iter().flatten())
+        .chain(table_writes)
+        .chain([
+            // Filter 0 must be disabled to modify the FIFO index it is pointing to
+            RegisterWrite::Byte(filter_control_reg, 0x00),
+            RegisterWrite::Byte(filter_control_reg, FIFO_RX_INDEX),
+            RegisterWrite::Byte(filter_control_reg, (1 << 7) | FIFO_RX_INDEX),
+        ]);
+
+        Ok(writes)
+    }
+
+    /// Encodes the configuration for the second CiCON register byte.
+    /// Bits not covered by the configuration (BRSDIS) keep their reset value.
+    fn as_con_register_1(&self) -> u8 {
+        // bits 2-1 -> WFT, bit 0 -> WAKFIL
+        match self.wake_up_filter {
+            Some(filter_time) => (CON_REGISTER_1_RESET & !0b111) | ((filter_time as u8) << 1) | 1,
+            None => CON_REGISTER_1_RESET & !1,
+        }
+    }
+
+    /// Encodes the configuration for the third CiCON register byte
+    fn as_con_register_2(&self) -> u8 {
+        // bit 4 -> TXQ enable
+        // bit 3 -> TEF enable
+        // bit 0 -> retransmission attempts are restricted by FIFO configuration
+        let txq_enable = match self.txq {
+            Some(_) => 0b0001_0000,
+            None => 0b0000_0000,
+        };
+
+        let tef_enable = match self.tef {
+            Some(_) => 0b0000_1000,
+            None => 0b0000_0000,
+        };
+
+        txq_enable | tef_enable | 0b0000_0001
+    }
+
+    /// Returns a bit mask of the FIFO indices storing timestamps of received messages
+    pub(crate) fn rx_timestamp_fifos(&self) -> u32 {
+        self.fifo_table
+            .iter()
+            .filter(|(_, settings)| settings.direction == FifoDirection::Receive && settings.timestamp)
+            .fold(0, |mask, (fifo, _)| mask | (1 << fifo.index()))
+    }
+
+    /// Returns the number of message RAM bytes allocated by the TEF, TXQ and FIFOs
+    pub fn ram_usage(&self) -> usize {
+        let tef_usage = self.tef.map(|tef| tef.ram_usage()).unwrap_or(0);
+        let txq_usage = self.txq.map(|txq| txq.ram_usage()).unwrap_or(0);
+
+        let fifo_usage: usize = (1..=MAX_FIFO_INDEX)
+            .map(|index| match (self.fifo_table.get(Fifo(index)), index) {
+                (Some(settings), _) => settings.ram_usage(),
+                (None, FIFO_RX_INDEX) => self.fifo.rx_ram_usage(),
+                (None, FIFO_TX_INDEX) => self.fifo.tx_ram_usage(),
+                (None, _) => FifoSettings::default().ram_usage(),
+            })
+            .sum();
+
+        tef_usage + txq_usage + fifo_usage
+    }
 }
 
 /// Oscillator/Clock configuration
@@ -78,6 +450,11 @@ pub struct ClockConfiguration {
     /// Disable clock/oscillator?
     pub disable_clock: bool,
 
+    /// Enter low-power mode (LPM) instead of sleep mode, supported by MCP2518FD and MCP251863 only
+    /// (see [set_variant](crate::can::MCP2517::set_variant)).
+    /// Register contents are lost in LPM, so the controller must be configured again after wake-up.
+    pub low_power_mode: bool,
+
     /// PLL configuration
     pub pll: PLLSetting,
 }
@@ -89,6 +466,7 @@ impl ClockConfiguration {
             clock_output: ClockOutputDivisor::from_register(register),
             system_clock: SystemClockDivisor::from_register(register),
             disable_clock: register & (1 << 2) != 0,
+            low_power_mode: register & (1 << 3) != 0,
             pll: PLLSetting::from_register(register),
         }
     }
@@ -99,6 +477,7 @@ impl ClockConfiguration {
 
         register |= (self.clock_output as u8) << 5;
         register |= (self.system_clock as u8) << 4;
+        register |= (self.low_power_mode as u8) << 3;
         register |= (self.disable_clock as u8) << 2;
         register |= self.pll as u8;
 
@@ -107,20 +486,15 @@ impl ClockConfiguration {
 }
 
 /// Divisor for clock output
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
 pub enum ClockOutputDivisor {
     DivideBy10 = 0b11,
     DivideBy4 = 0b10,
     DivideBy2 = 0b01,
+    #[default]
     DivideBy1 = 0b00,
 }
 
-impl Default for ClockOutputDivisor {
-    fn default() -> Self {
-        Self::DivideBy1
-    }
-}
-
 impl ClockOutputDivisor {
     /// Maps register values to configuration
     pub(crate) fn from_register(register: u8) -> Self {
@@ -134,18 +508,13 @@ impl ClockOutputDivisor {
 }
 
 /// Divisor for system clock
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
 pub enum SystemClockDivisor {
     DivideBy2 = 0b1,
+    #[default]
     DivideBy1 = 0b0,
 }
 
-impl Default for SystemClockDivisor {
-    fn default() -> Self {
-        Self::DivideBy1
-    }
-}
-
 impl SystemClockDivisor {
     /// Maps register values to configuration
     pub(crate) fn from_register(register: u8) -> Self {
@@ -158,20 +527,15 @@ impl SystemClockDivisor {
 }
 
 /// PLL configuration
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
 pub enum PLLSetting {
     /// System clock from 10x PLL
     TenTimesPLL = 0b1,
     /// System clock comes directly from XTAL oscillator
+    #[default]
     DirectXTALOscillator = 0b0,
 }
 
-impl Default for PLLSetting {
-    fn default() -> Self {
-        Self::DirectXTALOscillator
-    }
-}
-
 impl PLLSetting {
     /// Maps register values to configuration
     pub(crate) fn from_register(register: u8) -> Self {
@@ -260,30 +624,584 @@ impl FifoConfiguration {
         (Self::limit_size(self.tx_size) - 1) | ((self.pl_size as u8) << 5)
     }
 
+    /// Returns the number of message RAM bytes allocated by the RX FIFO
+    fn rx_ram_usage(&self) -> usize {
+        Self::limit_size(self.rx_size) as usize * message_object_size(self.pl_size, false)
+    }
+
+    /// Returns the number of message RAM bytes allocated by the TX FIFO
+    fn tx_ram_usage(&self) -> usize {
+        Self::limit_size(self.tx_size) as usize * message_object_size(self.pl_size, false)
+    }
+
     /// Limits the size to valid values
     fn limit_size(size: u8) -> u8 {
         size.clamp(1, 32)
     }
 }
 
-/// Number of retransmission attempts
+impl PayloadSize {
+    /// Returns the number of payload bytes
+    pub const fn bytes(self) -> usize {
+        match self {
+            PayloadSize::EightBytes => 8,
+            PayloadSize::TwelveBytes => 12,
+            PayloadSize::SixteenBytes => 16,
+            PayloadSize::TwentyBytes => 20,
+            PayloadSize::TwentyFourBytes => 24,
+            PayloadSize::ThirtyTwoBytes => 32,
+            PayloadSize::FortyEightBytes => 48,
+            PayloadSize::SixtyFourBytes => 64,
+        }
+    }
+}
+
+/// Handle of a FIFO (1 to 31)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Fifo(u8);
+
+impl Fifo {
+    /// FIFO receiving messages of filter 0, configured by [FifoConfiguration]
+    pub const RX: Fifo = Fifo(FIFO_RX_INDEX);
+
+    /// FIFO transmitting messages, configured by [FifoConfiguration]
+    pub const TX: Fifo = Fifo(FIFO_TX_INDEX);
+
+    /// Returns the handle of the given FIFO, or None if the index is not within 1 and 31
+    pub const fn new(index: u8) -> Option<Self> {
+        match index {
+            1..=MAX_FIFO_INDEX => Some(Self(index)),
+            _ => None,
+        }
+    }
+
+    /// FIFO index (1 to 31)
+    pub const fn index(self) -> u8 {
+        self.0
+    }
+}
+
+/// Direction of a FIFO
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FifoDirection {
+    /// FIFO receives messages
+    #[default]
+    Receive,
+    /// FIFO transmits messages
+    Transmit,
+}
+
+/// Configuration of a single FIFO
 #[derive(Copy, Clone, Debug)]
-pub enum RetransmissionAttempts {
-    Disabled = 0b00,
-    Three = 0b01,
-    Unlimited = 0b10,
+pub struct FifoSettings {
+    /// Transmit or receive FIFO
+    pub direction: FifoDirection,
+
+    /// FIFO size in messages: 1 - 32.
+    /// Value is limited to 1 - 32 messages if a different value is given.
+    pub size: u8,
+
+    /// Number of payload bytes in message
+    pub pl_size: PayloadSize,
+
+    /// Transmission priority (0 = Lowest, 31 = Highest), only used by transmit FIFOs.
+    /// Value is limited to 31 if a higher value is given
+    pub priority: u8,
+
+    /// Number of retransmission attempts, only used by transmit FIFOs
+    pub attempts: RetransmissionAttempts,
+
+    /// Stores the timestamp of received messages, only used by receive FIFOs
+    pub timestamp: bool,
+
+    /// Automatically transmits the loaded message when a matching remote frame is received,
+    /// only used by transmit FIFOs
+    pub auto_rtr: bool,
+
+    /// FIFO interrupts, signaled by [Interrupt::Receive](crate::status::Interrupt::Receive) or
+    /// [Interrupt::Transmit](crate::status::Interrupt::Transmit)
+    pub interrupts: FifoInterrupts,
 }
 
-impl Default for RetransmissionAttempts {
+impl Default for FifoSettings {
     fn default() -> Self {
-        Self::Unlimited
+        Self {
+            direction: FifoDirection::Receive,
+            size: 1,
+            pl_size: PayloadSize::EightBytes,
+            priority: 0,
+            attempts: RetransmissionAttempts::default(),
+            timestamp: false,
+            auto_rtr: false,
+            interrupts: FifoInterrupts::default(),
+        }
     }
 }
 
-/// Request mode. This is basically a subset of operation mode, filtered to request modes
+impl FifoSettings {
+    /// Encodes the settings for the FIFO control register
+    pub(crate) fn as_control_register(&self) -> u32 {
+        let is_tx = self.direction == FifoDirection::Transmit;
+
+        let control_reg0 = FifoControlReg0::new()
+            .with_txen(is_tx)
+            .with_rtren(is_tx && self.auto_rtr)
+            .with_rxtsen(!is_tx && self.timestamp)
+            .with_txatie(is_tx && self.interrupts.attempts_exhausted)
+            .with_rxovie(!is_tx && self.interrupts.overflow)
+            .with_tferffie(self.interrupts.full_or_empty)
+            .with_tfhrfhie(self.interrupts.half)
+            .with_tfnrfnie(self.interrupts.not_empty_or_not_full);
+        let control_reg2 = FifoControlReg2::new()
+            .with_txat(self.attempts as u8)
+            .with_txpri(self.priority.min(31));
+        let control_reg3 = FifoControlReg3::new().with_plsize(self.pl_size as u8).with_fifo_size(self.size);
+
+        u32::from_le_bytes([control_reg0.into(), 0x0, control_reg2.into(), control_reg3.into()])
+    }
+
+    /// Returns the number of message RAM bytes allocated by this FIFO
+    pub(crate) fn ram_usage(&self) -> usize {
+        let timestamp = self.direction == FifoDirection::Receive && self.timestamp;
+
+        self.size.clamp(1, 32) as usize * message_object_size(self.pl_size, timestamp)
+    }
+}
+
+/// Interrupt sources of a single FIFO
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FifoInterrupts {
+    /// Receive FIFO is not empty / transmit FIFO is not full
+    pub not_empty_or_not_full: bool,
+
+    /// Receive FIFO is half full / transmit FIFO is half empty
+    pub half: bool,
+
+    /// Receive FIFO is full / transmit FIFO is empty
+    pub full_or_empty: bool,
+
+    /// Receive FIFO overflow, only used by receive FIFOs
+    pub overflow: bool,
+
+    /// Retransmission attempts exhausted, only used by transmit FIFOs
+    pub attempts_exhausted: bool,
+}
+
+/// Configuration of FIFO 1 to 31. Configured FIFOs take precedence over [FifoConfiguration] for FIFO 1 and 2.
+///
+/// Unconfigured FIFOs 3 to 31 keep their reset configuration (receive FIFO of one message with 8 bytes payload),
+/// so the controller should be reset before applying a new configuration.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FifoTable {
+    /// Settings by FIFO index - 1
+    fifos: [Option<FifoSettings>; MAX_FIFO_INDEX as usize],
+}
+
+impl FifoTable {
+    /// Sets the configuration of the given FIFO
+    pub fn with(mut self, fifo: Fifo, settings: FifoSettings) -> Self {
+        self.set(fifo, settings);
+        self
+    }
+
+    /// Sets the configuration of the given FIFO
+    pub fn set(&mut self, fifo: Fifo, settings: FifoSettings) {
+        self.fifos[fifo.index() as usize - 1] = Some(settings);
+    }
+
+    /// Returns the configuration of the given FIFO, if configured
+    pub fn get(&self, fifo: Fifo) -> Option<&FifoSettings> {
+        self.fifos[fifo.index() as usize - 1].as_ref()
+    }
+
+    /// Iterates over all configured FIFOs
+    pub fn iter(&self) -> impl Iterator<Item = (Fifo, &FifoSettings)> {
+        self.fifos
+            .iter()
+            .zip(1..)
+            .filter_map(|(settings, index)| Some((Fifo(index), settings.as_ref()?)))
+    }
+}
+
+/// Transmit queue (TXQ) configuration. The TXQ transmits the message with the highest priority ID first,
+/// regardless of the order in which the messages are loaded.
 #[derive(Copy, Clone, Debug)]
+pub struct TxQueueConfiguration {
+    /// Queue size in messages: 1 - 32.
+    /// Value is limited to 1 - 32 messages if a different value is given.
+    pub size: u8,
+
+    /// Number of payload bytes in message
+    pub pl_size: PayloadSize,
+
+    /// Transmission priority compared to transmit FIFOs (0 = Lowest, 31 = Highest).
+    /// Value is limited to 31 if a higher value is given
+    pub priority: u8,
+
+    /// Number of retransmission attempts
+    pub attempts: RetransmissionAttempts,
+}
+
+impl Default for TxQueueConfiguration {
+    fn default() -> Self {
+        Self {
+            size: 1,
+            pl_size: PayloadSize::EightBytes,
+            priority: 0,
+            attempts: RetransmissionAttempts::default(),
+        }
+    }
+}
+
+impl TxQueueConfiguration {
+    /// Encodes the configuration for the TXQ control register
+    pub(crate) fn as_control_register(&self) -> u32 {
+        let control_reg2 = FifoControlReg2::new()
+            .with_txat(self.attempts as u8)
+            .with_txpri(self.priority.min(31));
+        let control_reg3 = FifoControlReg3::new().with_plsize(self.pl_size as u8).with_fifo_size(self.size);
+
+        u32::from_le_bytes([0x0, 0x0, control_reg2.into(), control_reg3.into()])
+    }
+
+    /// Returns the number of message RAM bytes allocated by the TXQ
+    pub(crate) fn ram_usage(&self) -> usize {
+        self.size.clamp(1, 32) as usize * message_object_size(self.pl_size, false)
+    }
+}
+
+/// Transmit event FIFO (TEF) configuration. The TEF stores the ID and sequence number of each transmitted message,
+/// which are read by [read_transmit_event](crate::can::MCP2517::read_transmit_event).
+#[derive(Copy, Clone, Debug)]
+pub struct TefConfiguration {
+    /// FIFO size in events: 1 - 32.
+    /// Value is limited to 1 - 32 events if a different value is given.
+    pub size: u8,
+
+    /// Stores the timestamp of transmitted messages
+    pub timestamp: bool,
+}
+
+impl Default for TefConfiguration {
+    fn default() -> Self {
+        Self {
+            size: 1,
+            timestamp: false,
+        }
+    }
+}
+
+impl TefConfiguration {
+    /// Encodes the configuration for the TEF control register
+    pub(crate) fn as_control_register(&self) -> u32 {
+        // bit 5 -> timestamp enable
+        let control_reg0 = (self.timestamp as u8) << 5;
+        let control_reg3 = FifoControlReg3::new().with_fifo_size(self.size);
+
+        u32::from_le_bytes([control_reg0, 0x0, 0x0, control_reg3.into()])
+    }
+
+    /// Returns the number of message RAM bytes allocated by the TEF
+    pub(crate) fn ram_usage(&self) -> usize {
+        let object_size = if self.timestamp { 12 } else { 8 };
+
+        self.size.clamp(1, 32) as usize * object_size
+    }
+}
+
+/// Interrupts signaled on the INT pin (CiINT enable bits). Pending interrupts are read by
+/// [read_interrupts](crate::can::MCP2517::read_interrupts).
+///
+/// Receive and transmit interrupts are signaled if any FIFO interrupt enabled by [FifoSettings::interrupts]
+/// is pending. The transmit event interrupt is signaled if the TEF is not empty.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct InterruptConfiguration {
+    /// Transmit FIFO interrupts
+    pub transmit: bool,
+
+    /// Receive FIFO interrupts
+    pub receive: bool,
+
+    /// Time base counter overflow
+    pub time_base_overflow: bool,
+
+    /// Operation mode changed
+    pub mode_change: bool,
+
+    /// Transmit event FIFO is not empty
+    pub transmit_event: bool,
+
+    /// ECC error in message RAM
+    pub ecc: bool,
+
+    /// CRC mismatch or format error of SPI instruction
+    pub spi_crc: bool,
+
+    /// Retransmission attempts of a transmit FIFO exhausted
+    pub transmit_attempt: bool,
+
+    /// Receive FIFO overflow
+    pub receive_overflow: bool,
+
+    /// System error (e.g. message RAM access failure)
+    pub system_error: bool,
+
+    /// CAN bus error (error counter changed)
+    pub bus_error: bool,
+
+    /// Bus activity while in sleep mode
+    pub wake_up: bool,
+
+    /// Invalid message received or transmitted
+    pub invalid_message: bool,
+}
+
+impl InterruptConfiguration {
+    /// Encodes the configuration for the CiINT register. Interrupt flags are cleared.
+    pub(crate) fn as_int_register(&self) -> u32 {
+        let enables = [
+            self.transmit,
+            self.receive,
+            self.time_base_overflow,
+            self.mode_change,
+            self.transmit_event,
+            false,
+            false,
+            false,
+            self.ecc,
+            self.spi_crc,
+            self.transmit_attempt,
+            self.receive_overflow,
+            self.system_error,
+            self.bus_error,
+            self.wake_up,
+            self.invalid_message,
+        ];
+
+        enables.iter().enumerate().fold(0, |register, (bit, enabled)| {
+            register | ((*enabled as u32) << (bit + 16))
+        })
+    }
+}
+
+/// Configuration of the I/O pins (IOCON register). By default, INT0/GPIO0 and INT1/GPIO1 are GPIO inputs,
+/// which matches the reset state. GPIOs are accessed by [set_gpio](crate::can::MCP2517::set_gpio) and
+/// [read_gpio](crate::can::MCP2517::read_gpio).
+#[derive(Copy, Clone, Debug, Default)]
+pub struct IoConfiguration {
+    /// Mode of INT0/GPIO0 pin, interrupt pin signals transmit interrupts
+    pub pin0: PinMode,
+
+    /// Mode of INT1/GPIO1 pin, interrupt pin signals receive interrupts
+    pub pin1: PinMode,
+
+    /// Transceiver standby control (XSTBYEN), GPIO0 is driven high in sleep mode and low otherwise.
+    /// Overrides the mode of [Self::pin0].
+    pub transceiver_standby: bool,
+
+    /// Output driver of the INT, INT0 and INT1 pins
+    pub interrupt_output: OutputDriver,
+
+    /// Output driver of the TXCAN pin
+    pub tx_can_output: OutputDriver,
+
+    /// CLKO pin outputs start of frame signal instead of the clock
+    pub start_of_frame: bool,
+}
+
+impl IoConfiguration {
+    /// Encodes the configuration for the IOCON register
+    pub(crate) fn as_iocon_register(&self) -> u32 {
+        // Transceiver standby control requires GPIO0 to be an output
+        let pin0 = match self.transceiver_standby {
+            true => PinMode::Output(false),
+            false => self.pin0,
+        };
+
+        // bit 30 -> INTOD, bit 29 -> SOF, bit 28 -> TXCANOD, bit 6 -> XSTBYEN
+        let mut register = pin0.as_iocon_bits(GpioPin::Gpio0) | self.pin1.as_iocon_bits(GpioPin::Gpio1);
+        register |= (self.interrupt_output as u32) << 30;
+        register |= (self.start_of_frame as u32) << 29;
+        register |= (self.tx_can_output as u32) << 28;
+        register |= (self.transceiver_standby as u32) << 6;
+
+        register
+    }
+}
+
+/// Mode of a INTx/GPIOx pin
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PinMode {
+    /// Interrupt output
+    Interrupt,
+    /// GPIO input
+    #[default]
+    Input,
+    /// GPIO output with the given initial level (true = high)
+    Output(bool),
+}
+
+impl PinMode {
+    /// Encodes the PMx, TRISx and LATx bits of the given pin
+    fn as_iocon_bits(&self, pin: GpioPin) -> u32 {
+        // PMx -> bits 25-24, LATx -> bits 9-8, TRISx -> bits 1-0
+        let (gpio, input, level) = match self {
+            PinMode::Interrupt => (false, true, false),
+            PinMode::Input => (true, true, false),
+            PinMode::Output(level) => (true, false, *level),
+        };
+
+        ((gpio as u32) << 24 | (level as u32) << 8 | input as u32) << pin as u8
+    }
+}
+
+/// General purpose I/O pin
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GpioPin {
+    /// INT0/GPIO0 pin
+    Gpio0 = 0,
+    /// INT1/GPIO1 pin
+    Gpio1 = 1,
+}
+
+/// Output driver of a pin
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OutputDriver {
+    #[default]
+    PushPull = 0,
+    OpenDrain = 1,
+}
+
+/// Filter time of the wake-up filter (WFT), suppressing glitches on the bus while in sleep mode.
+/// Refer to the datasheet for the filter times of the given bit values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum WakeUpFilterTime {
+    /// T00FILTER
+    T00 = 0b00,
+    /// T01FILTER
+    T01 = 0b01,
+    /// T10FILTER
+    T10 = 0b10,
+    /// T11FILTER
+    #[default]
+    T11 = 0b11,
+}
+
+/// ECC configuration of the message RAM, which corrects single bit errors and detects double bit errors.
+/// Errors are read by [read_ecc_status](crate::can::MCP2517::read_ecc_status).
+///
+/// ECC interrupts are signaled on the INT pin if enabled by [InterruptConfiguration::ecc].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct EccConfiguration {
+    /// Single bit error (corrected) interrupt
+    pub single_error_interrupt: bool,
+
+    /// Double bit error (detected) interrupt
+    pub double_error_interrupt: bool,
+}
+
+impl EccConfiguration {
+    /// Encodes the configuration for the ECC control register
+    pub(crate) fn as_ecccon_register(&self) -> u32 {
+        // bit 2 -> DEDIE, bit 1 -> SECIE, bit 0 -> ECCEN
+        ((self.double_error_interrupt as u32) << 2) | ((self.single_error_interrupt as u32) << 1) | 1
+    }
+}
+
+/// Handling of bus-off state, which is detected while blocking transmit operations are waiting.
+/// In any case, the transmit operation returns [CanError::BusOff](crate::can::CanError::BusOff).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BusOffRecovery {
+    /// Controller recovers after 128 occurrences of 11 consecutive recessive bits.
+    /// Pending messages are transmitted after recovery.
+    #[default]
+    Automatic,
+    /// Controller is switched to configuration mode, pending messages are aborted.
+    /// Operation is restarted by [recover_bus_off](crate::can::MCP2517::recover_bus_off).
+    Manual,
+    /// Same as [BusOffRecovery::Manual], but [recover_bus_off](crate::can::MCP2517::recover_bus_off)
+    /// keeps the controller off the bus for the given delay before restarting.
+    Delayed(Milliseconds<u32>),
+}
+
+/// Time base counter (CiTBC) configuration. The counter is incremented every `prescaler` SYSCLK cycles
+/// and its value is stored in received messages and transmit events if timestamping is enabled.
+#[derive(Copy, Clone, Debug)]
+pub struct TimestampConfiguration {
+    /// Time base counter prescaler: 1 - 1024.
+    /// Value is limited to 1 - 1024 if a different value is given.
+    pub prescaler: u16,
+
+    /// Frame edge at which the timestamp is captured
+    pub edge: TimestampEdge,
+}
+
+impl Default for TimestampConfiguration {
+    fn default() -> Self {
+        Self {
+            prescaler: 1,
+            edge: TimestampEdge::default(),
+        }
+    }
+}
+
+impl TimestampConfiguration {
+    /// Encodes the configuration for the CiTSCON register
+    pub(crate) fn as_tscon_register(&self) -> u32 {
+        // bit 18 -> timestamp at res bit of CAN FD frames
+        // bit 17 -> timestamp at end of frame
+        // bit 16 -> time base counter enable
+        let edge = match self.edge {
+            TimestampEdge::StartOfFrame => 0,
+            TimestampEdge::EndOfFrame => 1 << 17,
+            TimestampEdge::FdResBit => 1 << 18,
+        };
+
+        edge | (1 << 16) | (self.prescaler.clamp(1, 1024) as u32 - 1)
+    }
+
+    /// Converts a timestamp (time base counter ticks) to the elapsed time since the counter was started.
+    /// `sys_clk` is the SYSCLK frequency in Hz the counter is derived from, see [Configuration::sys_clk].
+    pub fn to_duration(&self, sys_clk: u32, ticks: u32) -> Nanoseconds<u64> {
+        let cycles = ticks as u128 * self.prescaler.clamp(1, 1024) as u128;
+
+        Nanoseconds::new((cycles * 1_000_000_000 / sys_clk.max(1) as u128) as u64)
+    }
+}
+
+/// Frame edge at which the timestamp is captured
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TimestampEdge {
+    /// Timestamp is captured at the start of frame (SOF)
+    #[default]
+    StartOfFrame,
+    /// Timestamp is captured at the end of frame (EOF)
+    EndOfFrame,
+    /// Timestamp is captured at the sample point of the res bit of CAN FD frames, SOF for CAN 2.0 frames
+    FdResBit,
+}
+
+/// Returns the size of a message object in bytes
+pub(crate) const fn message_object_size(pl_size: PayloadSize, timestamp: bool) -> usize {
+    let header_size = if timestamp { 12 } else { 8 };
+
+    header_size + pl_size.bytes()
+}
+
+/// Number of retransmission attempts
+#[derive(Copy, Clone, Debug, Default)]
+pub enum RetransmissionAttempts {
+    Disabled = 0b00,
+    Three = 0b01,
+    #[default]
+    Unlimited = 0b10,
+}
+
+/// Request mode. This is basically a subset of operation mode, filtered to request modes
+#[derive(Copy, Clone, Debug, Default)]
 pub enum RequestMode {
     /// Normal CAN FD mode, supports mixing of CAN FDC can classic CAN 2.0 frames
+    #[default]
     NormalCANFD,
     /// Internal loop back mode
     InternalLoopback,
@@ -295,12 +1213,6 @@ pub enum RequestMode {
     NormalCAN2_0,
 }
 
-impl Default for RequestMode {
-    fn default() -> Self {
-        Self::NormalCANFD
-    }
-}
-
 impl RequestMode {
     pub(crate) fn to_operation_mode(self) -> OperationMode {
         match self {
@@ -314,14 +1226,25 @@ impl RequestMode {
 }
 
 /// MCP2517FD clock speed
-#[derive(Copy, Debug, Clone)]
+#[derive(Copy, Debug, Clone, Default)]
 pub enum SysClk {
     /// Chip SYSCLK is 20 Mhz
+    #[default]
     MHz20,
     /// Chip SYSCLK is 40 Mhz
     Mhz40,
 }
 
+impl SysClk {
+    /// Returns the SYSCLK frequency in Hz
+    pub const fn frequency(self) -> u32 {
+        match self {
+            SysClk::MHz20 => 20_000_000,
+            SysClk::Mhz40 => 40_000_000,
+        }
+    }
+}
+
 /// CAN bus baud rate
 #[derive(Copy, Debug, Clone)]
 pub enum CanBaudRate {
@@ -339,6 +1262,39 @@ pub enum CanBaudRate {
     Kbps10,
     /// 5 kilo bits per second
     Kbps5,
+    /// Bit timing calculated by [BitTiming::nominal]. SYSCLK setting of [BitRateConfig] is ignored,
+    /// the SYSCLK frequency the timing is calculated for is used instead.
+    Custom(BitTiming),
+}
+
+/// Reset value of the second CiCON register byte: WFT = T11, WAKFIL set
+const CON_REGISTER_1_RESET: u8 = 0b0000_0111;
+
+/// Predefined nominal bit timings for SYSCLK of 20 MHz and 40 MHz, evaluated at compile time
+const NOMINAL_TIMINGS: [[BitTiming; 7]; 2] = [
+    nominal_timings(SysClk::MHz20.frequency()),
+    nominal_timings(SysClk::Mhz40.frequency()),
+];
+
+/// Calculates the nominal bit timings of all predefined baud rates with a sample point of 80%
+const fn nominal_timings(sys_clk: u32) -> [BitTiming; 7] {
+    [
+        predefined_timing(BitTiming::nominal(sys_clk, 1_000_000, 800, 2)),
+        predefined_timing(BitTiming::nominal(sys_clk, 500_000, 800, 2)),
+        predefined_timing(BitTiming::nominal(sys_clk, 250_000, 800, 2)),
+        predefined_timing(BitTiming::nominal(sys_clk, 125_000, 800, 2)),
+        predefined_timing(BitTiming::nominal(sys_clk, 50_000, 800, 2)),
+        predefined_timing(BitTiming::nominal(sys_clk, 10_000, 800, 2)),
+        predefined_timing(BitTiming::nominal(sys_clk, 5_000, 800, 2)),
+    ]
+}
+
+/// Fails compilation in case a predefined bit timing is not reachable
+const fn predefined_timing(result: Result<BitTiming, BitTimingError>) -> BitTiming {
+    match result {
+        Ok(timing) => timing,
+        Err(_) => panic!("Predefined bit timing is not reachable"),
+    }
 }
 
 /// Bit rate config
@@ -351,23 +1307,27 @@ pub struct BitRateConfig {
 }
 
 impl BitRateConfig {
-    /// Calculate CiNBTCFG register values based on SYSCLK and desired baud rate
-    /// using this bit time calculations [Excel sheet](https://ww1.microchip.com/downloads/aemDocuments/documents/OTH/ProductDocuments/DesignChecklist/MCP2517FD+Bit+Time+Calculations+-+UG.xlsx)
-    pub fn calculate_values(&self) -> [u8; 4] {
-        match (self.sys_clk, self.can_speed) {
-            (SysClk::MHz20, CanBaudRate::Kbps1000) => [0, 13, 4, 1],
-            (SysClk::MHz20, CanBaudRate::Kpbs500) | (SysClk::Mhz40, CanBaudRate::Kbps1000) => [0, 30, 7, 1],
-            (SysClk::MHz20, CanBaudRate::Kbps250) | (SysClk::Mhz40, CanBaudRate::Kpbs500) => [0, 62, 15, 1],
-            (SysClk::MHz20, CanBaudRate::Kbps125) | (SysClk::Mhz40, CanBaudRate::Kbps250) => [0, 126, 31, 1],
-            (SysClk::MHz20, CanBaudRate::Kbps50)
-            | (SysClk::MHz20, CanBaudRate::Kbps10)
-            | (SysClk::MHz20, CanBaudRate::Kbps5)
-            | (SysClk::Mhz40, CanBaudRate::Kbps125)
-            | (SysClk::Mhz40, CanBaudRate::Kbps50)
-            | (SysClk::Mhz40, CanBaudRate::Kbps10)
-            | (SysClk::Mhz40, CanBaudRate::Kbps5) => [0, 255, 63, 1],
+    /// Returns the nominal bit timing based on SYSCLK and desired baud rate.
+    /// Predefined baud rates are using a sample point of 80% and a SJW of 2 TQ.
+    pub const fn timing(&self) -> BitTiming {
+        let timings = &NOMINAL_TIMINGS[self.sys_clk as usize];
+
+        match self.can_speed {
+            CanBaudRate::Kbps1000 => timings[0],
+            CanBaudRate::Kpbs500 => timings[1],
+            CanBaudRate::Kbps250 => timings[2],
+            CanBaudRate::Kbps125 => timings[3],
+            CanBaudRate::Kbps50 => timings[4],
+            CanBaudRate::Kbps10 => timings[5],
+            CanBaudRate::Kbps5 => timings[6],
+            CanBaudRate::Custom(timing) => timing,
         }
     }
+
+    /// Calculate CiNBTCFG register values based on SYSCLK and desired baud rate
+    pub const fn calculate_values(&self) -> [u8; 4] {
+        self.timing().register_values()
+    }
 }
 
 impl Default for BitRateConfig {
@@ -378,3 +1338,115 @@ impl Default for BitRateConfig {
         }
     }
 }
+
+/// CAN FD data phase baud rate
+#[derive(Copy, Debug, Clone, Default)]
+pub enum DataBaudRate {
+    /// 500 kilo bits per second
+    Kbps500,
+    /// 1 mega bits per second
+    #[default]
+    Mbps1,
+    /// 2 mega bits per second
+    Mbps2,
+    /// 4 mega bits per second
+    Mbps4,
+    /// 5 mega bits per second
+    Mbps5,
+    /// Bit timing calculated by [BitTiming::data] for the SYSCLK frequency of [Configuration::sys_clk]
+    Custom(BitTiming),
+}
+
+/// Predefined data bit timings for SYSCLK of 20 MHz and 40 MHz, evaluated at compile time
+const DATA_TIMINGS: [[BitTiming; 5]; 2] = [
+    data_timings(SysClk::MHz20.frequency()),
+    data_timings(SysClk::Mhz40.frequency()),
+];
+
+/// Calculates the data bit timings of all predefined baud rates with a sample point of 80%
+/// and a SJW matching phase segment 2
+const fn data_timings(sys_clk: u32) -> [BitTiming; 5] {
+    [
+        predefined_timing(BitTiming::data(sys_clk, 500_000, 800, 16)),
+        predefined_timing(BitTiming::data(sys_clk, 1_000_000, 800, 16)),
+        predefined_timing(BitTiming::data(sys_clk, 2_000_000, 800, 16)),
+        predefined_timing(BitTiming::data(sys_clk, 4_000_000, 800, 16)),
+        predefined_timing(BitTiming::data(sys_clk, 5_000_000, 800, 16)),
+    ]
+}
+
+/// Transmitter delay compensation mode
+#[derive(Copy, Debug, Clone, Default, PartialEq, Eq)]
+pub enum TdcMode {
+    /// Transmitter delay compensation is disabled
+    Disabled,
+    /// Fixed delay compensation
+    Manual {
+        /// Delay compensation value in SYSCLK cycles (0 to 63)
+        value: u8,
+        /// Delay compensation offset in SYSCLK cycles (-64 to 63)
+        offset: i8,
+    },
+    /// Delay is measured by the controller, offset is set to the data phase sample point
+    #[default]
+    Automatic,
+}
+
+/// Bit rate config of CAN FD data phase. The SYSCLK frequency is given by the nominal [BitRateConfig].
+#[derive(Clone, Debug, Default)]
+pub struct DataBitRateConfig {
+    /// CAN FD data phase baud rate
+    pub can_speed: DataBaudRate,
+    /// Transmitter delay compensation mode
+    pub tdc: TdcMode,
+}
+
+impl DataBitRateConfig {
+    /// Returns the data bit timing based on the given SYSCLK frequency (Hz) and desired data baud rate.
+    /// Predefined baud rates are using a sample point of 80% (75% if not reachable) and a SJW matching
+    /// phase segment 2. They are precalculated for SYSCLK of 20 MHz and 40 MHz, and calculated for any other
+    /// frequency. Custom timings must be calculated for the given SYSCLK frequency.
+    pub const fn timing(&self, sys_clk: u32) -> Result<BitTiming, BitTimingError> {
+        let (index, bit_rate) = match self.can_speed {
+            DataBaudRate::Kbps500 => (0, 500_000),
+            DataBaudRate::Mbps1 => (1, 1_000_000),
+            DataBaudRate::Mbps2 => (2, 2_000_000),
+            DataBaudRate::Mbps4 => (3, 4_000_000),
+            DataBaudRate::Mbps5 => (4, 5_000_000),
+            DataBaudRate::Custom(timing) if timing.sys_clk() == sys_clk => return Ok(timing),
+            DataBaudRate::Custom(_) => return Err(BitTimingError::SysClkMismatch),
+        };
+
+        match sys_clk {
+            20_000_000 => Ok(DATA_TIMINGS[SysClk::MHz20 as usize][index]),
+            40_000_000 => Ok(DATA_TIMINGS[SysClk::Mhz40 as usize][index]),
+            _ => BitTiming::data(sys_clk, bit_rate, 800, 16),
+        }
+    }
+
+    /// Calculate CiDBTCFG register values based on the given SYSCLK frequency (Hz) and desired data baud rate
+    pub const fn calculate_values(&self, sys_clk: u32) -> Result<[u8; 4], BitTimingError> {
+        match self.timing(sys_clk) {
+            Ok(timing) => Ok(timing.register_values()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns the CiTDC register value for the given data bit timing
+    pub(crate) fn as_tdc_register(&self, timing: &BitTiming) -> u32 {
+        let register = match self.tdc {
+            TdcMode::Disabled => C1TDC::new(),
+            TdcMode::Manual { value, offset } => C1TDC::new()
+                .with_tdcmod(0b01)
+                .with_tdcv(value.min(63))
+                .with_tdco((offset.clamp(-64, 63) as u8) & 0x7F),
+            TdcMode::Automatic => {
+                // Secondary sample point is set to the sample point of the data phase (in SYSCLK cycles)
+                let offset = timing.brp() * (timing.tseg1() + 1);
+                C1TDC::new().with_tdcmod(0b10).with_tdco(offset.min(63) as u8)
+            }
+        };
+
+        register.into()
+    }
+}
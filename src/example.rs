@@ -1,9 +1,7 @@
 //! # Mock dummy structure for doc examples
 //!
 //! This code can be removed by disabling the `example` feature
-use alloc::vec;
-use alloc::vec::Vec;
-use core::cell::RefCell;
+use core::cell::Cell;
 use core::convert::Infallible;
 use embedded_hal::spi::{ErrorType, Operation, SpiDevice};
 use embedded_time::clock::Error;
@@ -39,11 +37,15 @@ impl SpiDevice<u8> for ExampleSPIDevice {
             }
         }
 
-        // Read RX fifo (payload received)
-        if operations[0] != Operation::Write(&[0x38, 0x84]) {
+        // Read RX fifo (message object received)
+        if operations[0] != Operation::Write(&[0x38, 0x7C]) {
             return Ok(());
         }
-        if let Operation::Read(words) = &mut operations[1] {
+        if let Operation::Read(header) = &mut operations[1] {
+            // Standard ID 0x55, DLC 8
+            header.copy_from_slice(&[0x55, 0x0, 0x0, 0x0, 0x8, 0x0, 0x0, 0x0]);
+        }
+        if let Operation::Read(words) = &mut operations[2] {
             if words.len() != 8 {
                 return Ok(());
             }
@@ -86,22 +88,69 @@ impl SpiDevice<u8> for ExampleSPIDevice {
     }
 }
 
+#[cfg(feature = "async")]
+impl embedded_hal_async::spi::SpiDevice<u8> for ExampleSPIDevice {
+    async fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        if let [Operation::TransferInPlace(buf)] = operations {
+            return SpiDevice::transfer_in_place(self, buf);
+        }
+
+        SpiDevice::transaction(self, operations)
+    }
+}
+
+/// Interrupt pin, which is always asserted
+#[cfg(feature = "async")]
+#[derive(Default, Debug)]
+pub struct ExampleInterruptPin {}
+
+#[cfg(feature = "async")]
+impl embedded_hal::digital::ErrorType for ExampleInterruptPin {
+    type Error = Infallible;
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::digital::Wait for ExampleInterruptPin {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Clock returning the given instants in order, returns an error once all instants are consumed
 #[derive(Debug, PartialEq, Eq)]
-pub struct ExampleClock {
-    pub next_instants: RefCell<Vec<u64>>,
+pub struct ExampleClock<const N: usize = 4> {
+    pub next_instants: [u64; N],
+    pub next_index: Cell<usize>,
 }
 
-impl ExampleClock {
-    pub fn new(next_instants: Vec<u64>) -> Self {
+impl<const N: usize> ExampleClock<N> {
+    pub fn new(next_instants: [u64; N]) -> Self {
         Self {
-            next_instants: RefCell::new(next_instants),
+            next_instants,
+            next_index: Cell::new(0),
         }
     }
 }
 
 impl Default for ExampleClock {
     fn default() -> Self {
-        Self::new(vec![
+        Self::new([
             100,    // Config mode: Timer start,
             200,    // Config mode: First expiration check
             10_000, // Request mode: Timer start
@@ -110,19 +159,21 @@ impl Default for ExampleClock {
     }
 }
 
-impl Clock for ExampleClock {
+impl<const N: usize> Clock for ExampleClock<N> {
     type T = u64;
     const SCALING_FACTOR: Fraction = Fraction::new(1, 1_000_000);
 
     fn try_now(&self) -> Result<Instant<Self>, Error> {
-        if self.next_instants.borrow().len() == 0 {
+        let index = self.next_index.get();
+        let Some(instant) = self.next_instants.get(index) else {
             return Err(Error::Unspecified);
-        }
+        };
 
-        Ok(Instant::new(self.next_instants.borrow_mut().remove(0)))
+        self.next_index.set(index + 1);
+        Ok(Instant::new(*instant))
     }
 
-    fn new_timer<Dur: Duration + FixedPoint>(&self, duration: Dur) -> Timer<OneShot, Armed, Self, Dur> {
+    fn new_timer<Dur: Duration + FixedPoint>(&self, duration: Dur) -> Timer<'_, OneShot, Armed, Self, Dur> {
         Timer::new(self, duration)
     }
 }
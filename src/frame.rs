@@ -0,0 +1,180 @@
+//!# embedded-can integration
+//! [MCP2517] implements the [embedded_can::blocking::Can] and [embedded_can::nb::Can] traits,
+//! so that protocol stacks written against these traits can use the CAN controller directly.
+//!
+//! The traits are using [CanFrame] as frame type, which wraps either a CAN 2.0 [TxMessage]
+//! or a received [RxMessage]. As the `embedded-can` frame is limited to classic CAN frames,
+//! received CAN FD frames are truncated to the first 8 payload bytes.
+//!
+//!```
+//!# use mcp2517::can::MCP2517;
+//!# use mcp2517::config::Configuration;
+//!# use mcp2517::example::*;
+//!# use mcp2517::frame::CanFrame;
+//! use embedded_can::blocking::Can;
+//! use embedded_can::{Frame, Id, StandardId};
+//!
+//!# let sys_clk = ExampleClock::default();
+//!# let spi_dev = ExampleSPIDevice::default();
+//!# let mut controller = MCP2517::new(spi_dev);
+//!# controller.configure(&Configuration::default(), &sys_clk).unwrap();
+//! let id = StandardId::new(0x55).unwrap();
+//! let frame = CanFrame::new(id, &[0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]).unwrap();
+//!
+//! // Transmit frame in blocking mode
+//! Can::transmit(&mut controller, &frame).unwrap();
+//!
+//! // Receive frame in blocking mode
+//! let received = Can::receive(&mut controller).unwrap();
+//! assert_eq!(received.id(), Id::Standard(id));
+//! assert_eq!(received.data(), &[0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]);
+//!```
+use crate::can::{CanController, CanError, MCP2517};
+use crate::message::{Can20, RxMessage, TxMessage, DLC, MAX_PAYLOAD_CAN_2_0};
+use core::fmt::Debug;
+use embedded_can::{ErrorKind, Frame, Id};
+use embedded_hal::spi::SpiDevice;
+use embedded_time::Clock;
+
+/// CAN 2.0 frame used by the `embedded-can` trait implementations
+#[derive(Clone, Debug)]
+pub enum CanFrame {
+    /// Frame created for transmission
+    Tx(TxMessage<Can20<8>, 8>),
+    /// Frame received from RX FIFO
+    Rx(RxMessage<8>),
+}
+
+impl CanFrame {
+    /// Returns the transmit message object of this frame
+    fn to_tx_message(&self) -> Option<TxMessage<Can20<8>, 8>> {
+        match self {
+            CanFrame::Tx(message) => Some(message.clone()),
+            CanFrame::Rx(message) if message.header.remote_transmission_request() => {
+                Self::new_remote(message.id(), self.dlc())?.to_tx_message()
+            }
+            CanFrame::Rx(message) => Self::new(message.id(), message.get_payload())?.to_tx_message(),
+        }
+    }
+}
+
+impl Frame for CanFrame {
+    fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+        let message = TxMessage::new(Can20::<8> {}, data, id.into()).ok()?;
+
+        Some(CanFrame::Tx(message))
+    }
+
+    fn new_remote(id: impl Into<Id>, dlc: usize) -> Option<Self> {
+        if dlc > MAX_PAYLOAD_CAN_2_0 {
+            return None;
+        }
+
+        let dlc = DLC::from_length(dlc).ok()?;
+        let message = TxMessage::new_remote(Can20::<8> {}, dlc, id.into()).ok()?;
+
+        Some(CanFrame::Tx(message))
+    }
+
+    fn is_extended(&self) -> bool {
+        matches!(self.id(), Id::Extended(_))
+    }
+
+    fn is_remote_frame(&self) -> bool {
+        match self {
+            CanFrame::Tx(message) => message.header.remote_transmission_request(),
+            CanFrame::Rx(message) => message.header.remote_transmission_request(),
+        }
+    }
+
+    fn id(&self) -> Id {
+        match self {
+            CanFrame::Tx(message) => message.get_id(),
+            CanFrame::Rx(message) => message.id(),
+        }
+    }
+
+    fn dlc(&self) -> usize {
+        match self {
+            CanFrame::Tx(message) => message.header.data_length_code().to_length(),
+            CanFrame::Rx(message) => message.dlc().to_length().min(MAX_PAYLOAD_CAN_2_0),
+        }
+    }
+
+    fn data(&self) -> &[u8] {
+        if self.is_remote_frame() {
+            return &[];
+        }
+
+        match self {
+            CanFrame::Tx(message) => message.get_payload(),
+            CanFrame::Rx(message) => message.get_payload(),
+        }
+    }
+}
+
+impl<D: SpiDevice<u8> + Debug> embedded_can::Error for CanError<D> {
+    /// Bus-off errors are mapped to the kind of the recorded bus errors.
+    /// SPI, configuration and FIFO errors, as well as bus-off without recorded bus error, are mapped to [ErrorKind::Other].
+    fn kind(&self) -> ErrorKind {
+        match self {
+            CanError::BusOff(Some(kind)) => *kind,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl<D, CLK> embedded_can::blocking::Can for MCP2517<D, CLK>
+where
+    D: SpiDevice<u8> + Debug,
+    CLK: Clock,
+{
+    type Frame = CanFrame;
+    type Error = CanError<D>;
+
+    /// Transmits the frame and waits for its transmission. Returns [CanError::TransmissionFailed]
+    /// if the transmission was aborted or the retransmission attempts were exhausted.
+    fn transmit(&mut self, frame: &Self::Frame) -> Result<(), Self::Error> {
+        let message = frame.to_tx_message().ok_or(CanError::InvalidPayloadLength(frame.dlc()))?;
+
+        let outcome = CanController::transmit(self, &message, true)?;
+        if !outcome.is_transmitted() {
+            return Err(CanError::TransmissionFailed(outcome.status));
+        }
+
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Self::Frame, Self::Error> {
+        Ok(CanFrame::Rx(CanController::receive(self, true)?))
+    }
+}
+
+impl<D, CLK> embedded_can::nb::Can for MCP2517<D, CLK>
+where
+    D: SpiDevice<u8> + Debug,
+    CLK: Clock,
+{
+    type Frame = CanFrame;
+    type Error = CanError<D>;
+
+    /// Loads the frame in TX FIFO without waiting for transmission.
+    /// Pending frames are never replaced, so `Ok(None)` is returned on success.
+    fn transmit(&mut self, frame: &Self::Frame) -> nb::Result<Option<Self::Frame>, Self::Error> {
+        let message = frame.to_tx_message().ok_or(CanError::InvalidPayloadLength(frame.dlc()))?;
+
+        match CanController::transmit(self, &message, false) {
+            Ok(_) => Ok(None),
+            Err(CanError::TxFifoFullErr) => Err(nb::Error::WouldBlock),
+            Err(error) => Err(nb::Error::Other(error)),
+        }
+    }
+
+    fn receive(&mut self) -> nb::Result<Self::Frame, Self::Error> {
+        match CanController::receive(self, false) {
+            Ok(message) => Ok(CanFrame::Rx(message)),
+            Err(CanError::RxFifoEmptyErr) => Err(nb::Error::WouldBlock),
+            Err(error) => Err(nb::Error::Other(error)),
+        }
+    }
+}
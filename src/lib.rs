@@ -8,7 +8,22 @@
 //! Crate currently offers the following features:
 //! * CAN2.0 and CAN FD format support
 //! * Standard and extended ID formats for CAN frames
-//! * `no_std` support
+//! * Remote frames and automatic RTR responses of transmit FIFOs
+//! * `embedded-can` blocking and non-blocking traits
+//! * Async driver based on `embedded-hal-async` (`async` feature)
+//! * Bit timing calculation for arbitrary SYSCLK frequencies and bit rates
+//! * Transmit event FIFO (TEF) with sequence numbers of transmitted messages
+//! * Timestamping of received messages and transmit events
+//! * Optional CRC protected SPI transfers
+//! * ECC of the message RAM with error reporting
+//! * Sleep and low-power mode with wake-up by bus activity
+//! * GPIO and interrupt pin configuration, including transceiver standby control
+//! * MCP2518FD and MCP251863 support with runtime detection of the variant
+//! * Typestate API encoding the operation mode at compile time
+//! * Register-level simulator of the controller and virtual CAN bus for host tests (`simulator` feature)
+//! * Bridge to Linux SocketCAN interfaces (`socketcan` feature, requires `std`)
+//! * `no_std` support without heap allocation, payloads are stored in fixed-size buffers
+//!   (`alloc` is only required by the simulator)
 //!
 //!## Example
 //! For detailed example with rp-pico check [example](https://github.com/atlas-aero/rt-mcp2517/tree/main/example)
@@ -21,7 +36,6 @@
 //!use mcp2517::message::{Can20,TxMessage};
 //!use mcp2517::filter::Filter;
 //!use mcp2517::config::*;
-//!use bytes::Bytes;
 //!use embedded_can::{Id,StandardId};
 //!
 //!let spi_dev = ExampleSPIDevice::default();
@@ -37,6 +51,7 @@
 //!                clock_output: ClockOutputDivisor::DivideBy10,
 //!                system_clock: SystemClockDivisor::DivideBy1,
 //!                disable_clock: false,
+//!                low_power_mode: false,
 //!                pll: PLLSetting::TenTimesPLL,
 //!                 },
 //!            fifo: FifoConfiguration {
@@ -47,11 +62,21 @@
 //!                tx_size: 20,
 //!                tx_enable: true,
 //!                 },
+//!            fifo_table: FifoTable::default(),
+//!            txq: None,
+//!            tef: None,
+//!            timestamp: None,
+//!            interrupts: InterruptConfiguration::default(),
+//!            io: IoConfiguration::default(),
+//!            ecc: None,
+//!            wake_up_filter: None,
+//!            bus_off_recovery: BusOffRecovery::Automatic,
 //!            mode: RequestMode::NormalCANFD,
 //!            bit_rate: BitRateConfig{
 //!                sys_clk: SysClk::MHz20,
 //!                can_speed: CanBaudRate::Kpbs500
 //!                },
+//!            data_bit_rate: DataBitRateConfig::default(),
 //!             },
 //!        &clock,
 //!         ).unwrap();
@@ -65,8 +90,7 @@
 //!// you can only use Can20::<8> as the message type
 //!let message_type = Can20::<8> {};
 //!let payload = [0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8];
-//!let pl_bytes = Bytes::copy_from_slice(&payload);
-//!let can_message = TxMessage::new(message_type, pl_bytes, can_id).unwrap();
+//!let can_message = TxMessage::new(message_type, &payload, can_id).unwrap();
 //!
 //!// Create and set filter object
 //!let filter = Filter::new(can_id, 0).unwrap();
@@ -76,23 +100,34 @@
 //!controller.transmit(&can_message,true).unwrap();
 //!
 //!// Receive CAN message in blocking mode
-//!let mut buff = [0u8;8];
-//!let result = controller.receive(&mut buff,true);
-//!assert!(result.is_ok());
-//!assert_eq!(buff,[0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]);
+//!let received = controller.receive::<8>(true).unwrap();
+//!assert_eq!(received.id(), can_id);
+//!assert_eq!(received.get_payload(),[0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]);
 //!```
 
+#[cfg(any(test, feature = "alloc"))]
 extern crate alloc;
+#[cfg(feature = "socketcan")]
+extern crate std;
 
+pub mod bit_timing;
 pub mod can;
+#[cfg(feature = "async")]
+pub mod can_async;
 pub mod config;
 #[cfg(feature = "example")]
 pub mod example;
 pub mod filter;
+pub mod frame;
 pub mod message;
 #[cfg(test)]
 pub(crate) mod mocks;
 mod registers;
+#[cfg(any(test, feature = "simulator"))]
+pub mod simulator;
+#[cfg(all(feature = "socketcan", target_os = "linux"))]
+pub mod socketcan;
 pub mod status;
 #[cfg(test)]
 mod tests;
+pub mod typestate;
@@ -5,7 +5,6 @@
 //!
 //! ## CAN 2.0 message construction example
 //! ```
-//!# use bytes::Bytes;
 //!# use mcp2517::message::{Can20,TxMessage};
 //!# use embedded_can::{Id,StandardId};
 //!#
@@ -15,14 +14,11 @@
 //! let message_type = Can20::<4>{};
 //! // Create payload buffer of 3 data bytes. DLC determined by length of payload buffer.
 //! let payload = [0x1, 0x2, 0x3];
-//! // Create Bytes object
-//! let bytes = Bytes::copy_from_slice(&payload);
-//! // Create message object
-//! let tx_message = TxMessage::new(message_type,bytes,message_id).unwrap();
+//! // Create message object, payload is copied into a buffer of 4 bytes
+//! let tx_message = TxMessage::new(message_type,&payload,message_id).unwrap();
 //!```
 //! ## CAN FD message construction example
 //! ```
-//!# use bytes::Bytes;
 //!# use mcp2517::message::{CanFd,TxMessage};
 //!# use embedded_can::{Id,StandardId};
 //!#
@@ -32,13 +28,11 @@
 //! let message_type = CanFd::<24>{bitrate_switch: true};
 //! // Create payload buffer with 22 data bytes (here DLC will be 24 because 22 is not a supported DLC code)
 //! let payload = [0u8;22];
-//! // Create Bytes object
-//! let bytes = Bytes::copy_from_slice(&payload);
-//! // Create message object
-//! let tx_message = TxMessage::new(message_type,bytes,message_id).unwrap();
+//! // Create message object, payload is copied into a buffer of 24 bytes
+//! let tx_message = TxMessage::new(message_type,&payload,message_id).unwrap();
 //! ```
 
-use bytes::Bytes;
+use core::fmt::{self, Debug, Formatter};
 use embedded_can::{ExtendedId, Id, StandardId};
 use log::debug;
 use modular_bitfield_msb::prelude::*;
@@ -84,7 +78,7 @@ pub enum MessageError {
 }
 
 impl DLC {
-    fn from_length(value: usize) -> Result<Self, MessageError> {
+    pub(crate) fn from_length(value: usize) -> Result<Self, MessageError> {
         match value {
             0 => Ok(Self::Zero),
             1 => Ok(Self::One),
@@ -105,11 +99,33 @@ impl DLC {
             val => Err(MessageError::InvalidLength(val)),
         }
     }
+
+    /// Returns the number of payload bytes encoded by the DLC
+    pub fn to_length(self) -> usize {
+        match self {
+            Self::Zero => 0,
+            Self::One => 1,
+            Self::Two => 2,
+            Self::Three => 3,
+            Self::Four => 4,
+            Self::Five => 5,
+            Self::Six => 6,
+            Self::Seven => 7,
+            Self::Eight => 8,
+            Self::Twelve => 12,
+            Self::Sixteen => 16,
+            Self::Twenty => 20,
+            Self::TwentyFour => 24,
+            Self::ThirtyTwo => 32,
+            Self::FortyEight => 48,
+            Self::SixtyFour => 64,
+        }
+    }
 }
 
 /// Transmit message object header
 #[bitfield(bits = 64)]
-#[derive(BitfieldSpecifier, Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Default)]
+#[derive(BitfieldSpecifier, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Default)]
 pub struct TxHeader {
     // T0
     #[skip]
@@ -201,26 +217,36 @@ impl<const L: usize> MessageType<L> for CanFd<L> {
     }
 }
 
-/// Transmit Message Object
+/// Transmit Message Object, where `L` is the size of the payload buffer
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct TxMessage<T: MessageType<L>, const L: usize> {
     /// first 8 bytes of Transmit Message Object representing header
     pub(crate) header: TxHeader,
     /// Payload bytes of Message Object
-    pub(crate) buff: Bytes,
+    pub(crate) buff: [u8; L],
+    /// Number of payload bytes in buffer
+    pub(crate) length: usize,
     /// CAN message type (CAN 2.0 or CAN FD)
     pub(crate) message_type: T,
 }
 
 impl<T: MessageType<L>, const L: usize> TxMessage<T, L> {
-    /// Create new CAN message
-    pub fn new(message_type: T, data: Bytes, identifier: Id) -> Result<Self, MessageError> {
+    /// Create new CAN message, the payload is copied into the buffer of size `L`
+    pub fn new(message_type: T, data: &[u8], identifier: Id) -> Result<Self, MessageError> {
         let mut header = TxHeader::new();
 
         let mut payload_length = data.len();
 
         message_type.setup_header(&mut header, payload_length)?;
 
+        if payload_length > L {
+            debug!("Payload length {payload_length} exceeds buffer size {L}");
+            return Err(MessageError::InvalidLength(payload_length));
+        }
+
+        let mut buff = [0u8; L];
+        buff[..payload_length].copy_from_slice(data);
+
         // length used to choose the next supported DLC
         while let Err(MessageError::InvalidLength(_)) = DLC::from_length(payload_length) {
             payload_length += 1;
@@ -239,58 +265,173 @@ impl<T: MessageType<L>, const L: usize> TxMessage<T, L> {
 
         Ok(TxMessage {
             header,
-            buff: data,
+            buff,
+            length: data.len(),
             message_type,
         })
     }
 
     /// Returns payload as a `&[u8]`
     pub fn get_payload(&self) -> &[u8] {
-        self.buff.as_ref()
+        &self.buff[..self.length]
     }
 
     /// Returns Header register of Transmit Message Object
     pub fn get_header(&self) -> &TxHeader {
         &self.header
     }
+
+    /// Returns the standard or extended identifier of the message
+    pub fn get_id(&self) -> Id {
+        self.header.get_id()
+    }
+
+    /// Returns true if the message is a remote transmission request
+    pub fn is_remote_frame(&self) -> bool {
+        self.header.remote_transmission_request()
+    }
+}
+
+impl<const L: usize> TxMessage<Can20<L>, L> {
+    /// Create new remote frame requesting data of the given length (DLC).
+    /// Remote frames are only supported in CAN 2.0 format and carry no payload.
+    pub fn new_remote(message_type: Can20<L>, dlc: DLC, identifier: Id) -> Result<Self, MessageError> {
+        let length = dlc.to_length();
+
+        if length > MAX_PAYLOAD_CAN_2_0 {
+            debug!("DLC of remote frame must not exceed {MAX_PAYLOAD_CAN_2_0} bytes");
+            return Err(MessageError::InvalidLength(length));
+        }
+
+        // Payload is not transmitted, but determines the DLC
+        let payload = [0u8; MAX_PAYLOAD_CAN_2_0];
+        let mut message = Self::new(message_type, &payload[..length], identifier)?;
+        message.header.set_remote_transmission_request(true);
+
+        Ok(message)
+    }
+}
+
+impl Debug for TxHeader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TxHeader")
+            .field("sid11", &self.sid11())
+            .field("extended_identifier", &self.extended_identifier())
+            .field("standard_identifier", &self.standard_identifier())
+            .field("sequence", &self.sequence())
+            .field("error_status_indicator", &self.error_status_indicator())
+            .field("fd_frame", &self.fd_frame())
+            .field("bit_rate_switch", &self.bit_rate_switch())
+            .field("remote_transmission_request", &self.remote_transmission_request())
+            .field("identifier_extension_flag", &self.identifier_extension_flag())
+            .field("data_length_code", &self.data_length_code())
+            .finish()
+    }
+}
+
+impl TxHeader {
+    /// Returns the standard or extended identifier of the message
+    pub fn get_id(&self) -> Id {
+        if self.identifier_extension_flag() {
+            let id = ((self.standard_identifier() as u32) << 18) | self.extended_identifier();
+            Id::Extended(ExtendedId::new(id).unwrap())
+        } else {
+            Id::Standard(StandardId::new(self.standard_identifier()).unwrap())
+        }
+    }
+}
+
+/// Transmit event read from the Transmit Event FIFO (TEF), confirming the transmission of a message
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TransmitEvent {
+    /// First 8 bytes of the TEF object, matching the header of the transmitted message
+    pub(crate) header: TxHeader,
+    /// Timestamp of the transmission, if enabled by configuration
+    pub(crate) timestamp: Option<u32>,
+}
+
+impl TransmitEvent {
+    /// Returns the sequence number assigned to the message on transmission
+    pub fn sequence(&self) -> u8 {
+        self.header.sequence()
+    }
+
+    /// Returns the standard or extended identifier of the transmitted message
+    pub fn id(&self) -> Id {
+        self.header.get_id()
+    }
+
+    /// Returns the data length code of the transmitted message
+    pub fn dlc(&self) -> DLC {
+        self.header.data_length_code()
+    }
+
+    /// Returns the timestamp of the transmission (start of frame) in time base counter ticks,
+    /// or None if TEF timestamps are disabled
+    pub fn timestamp(&self) -> Option<u32> {
+        self.timestamp
+    }
+
+    /// Returns Header of the TEF object
+    pub fn get_header(&self) -> &TxHeader {
+        &self.header
+    }
 }
 
 /// Receive message object header
 #[bitfield(bits = 64)]
-#[derive(Default, PartialEq, Eq, Debug)]
+#[derive(Default, PartialEq, Eq, Copy, Clone)]
 #[repr(u64)]
 pub struct RxHeader {
     // R0
     #[skip]
     __: B2,
     /// In FD mode the standard ID can be extended to 12 bit using r1
-    sid11: bool,
+    pub(crate) sid11: bool,
     /// Extended Identifier
-    extended_identifier: B18,
+    pub(crate) extended_identifier: B18,
     /// Standard Identifier
-    standard_identifier: B11,
+    pub(crate) standard_identifier: B11,
     #[skip]
     __: B16,
     /// Filter Hit, number of filter that matched
-    filter_hit: B5,
+    pub(crate) filhit: B5,
     #[skip]
     __: B2,
     /// Error Status Indicator
-    error_status_indicator: bool,
+    pub(crate) error_status_indicator: bool,
     /// FD Frame; distinguishes between CAN and CAN FD formats
-    fd_frame: bool,
+    pub(crate) fd_frame: bool,
     /// Bit Rate Switch; indicates if data bit rate was switched
-    bit_rate_switch: bool,
+    pub(crate) bit_rate_switch: bool,
     /// Remote Transmission Request; not used in CAN FD
-    remote_transmission_request: bool,
+    pub(crate) remote_transmission_request: bool,
     /// Identifier Extension Flag; distinguishes between base and extended format
-    identifier_extension_flag: bool,
+    pub(crate) identifier_extension_flag: bool,
     /// Data Length Code
-    data_length_code: DLC,
+    pub(crate) data_length_code: DLC,
+}
+
+impl Debug for RxHeader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RxHeader")
+            .field("sid11", &self.sid11())
+            .field("extended_identifier", &self.extended_identifier())
+            .field("standard_identifier", &self.standard_identifier())
+            .field("filter_hit", &self.filhit())
+            .field("error_status_indicator", &self.error_status_indicator())
+            .field("fd_frame", &self.fd_frame())
+            .field("bit_rate_switch", &self.bit_rate_switch())
+            .field("remote_transmission_request", &self.remote_transmission_request())
+            .field("identifier_extension_flag", &self.identifier_extension_flag())
+            .field("data_length_code", &self.data_length_code())
+            .finish()
+    }
 }
 
 impl RxHeader {
-    fn get_id(&self) -> Id {
+    /// Returns the standard or extended identifier of the received frame
+    pub fn get_id(&self) -> Id {
         if self.identifier_extension_flag() {
             let id = ((self.standard_identifier() as u32) << 18) | (self.extended_identifier());
             let extended_id = ExtendedId::new(id);
@@ -301,6 +442,36 @@ impl RxHeader {
         }
     }
 
+    /// Returns the data length code of the received frame
+    pub fn dlc(&self) -> DLC {
+        self.data_length_code()
+    }
+
+    /// Returns the index of the filter that matched the frame
+    pub fn filter_hit(&self) -> u8 {
+        self.filhit()
+    }
+
+    /// Returns true if the frame is a remote transmission request
+    pub fn is_remote_frame(&self) -> bool {
+        self.remote_transmission_request()
+    }
+
+    /// Returns true if the frame was received in CAN FD format
+    pub fn is_fd_frame(&self) -> bool {
+        self.fd_frame()
+    }
+
+    /// Returns true if the data bit rate was switched
+    pub fn is_bit_rate_switched(&self) -> bool {
+        self.bit_rate_switch()
+    }
+
+    /// Returns true if the transmitting node was error passive
+    pub fn is_error_passive(&self) -> bool {
+        self.error_status_indicator()
+    }
+
     #[cfg(test)]
     pub fn new_test_cfg(identifier: Id) -> Self {
         match identifier {
@@ -315,3 +486,78 @@ impl RxHeader {
         }
     }
 }
+
+/// Receive Message Object, where `L` is the size of the payload buffer
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct RxMessage<const L: usize> {
+    /// first 8 bytes of Receive Message Object representing header
+    pub(crate) header: RxHeader,
+    /// Timestamp of the reception, if enabled for the receive FIFO
+    pub(crate) timestamp: Option<u32>,
+    /// Payload bytes of Message Object
+    pub(crate) buff: [u8; L],
+}
+
+impl<const L: usize> RxMessage<L> {
+    /// Returns the standard or extended identifier of the received frame
+    pub fn id(&self) -> Id {
+        self.header.get_id()
+    }
+
+    /// Returns the data length code of the received frame
+    pub fn dlc(&self) -> DLC {
+        self.header.dlc()
+    }
+
+    /// Returns the payload as `&[u8]`, length is given by the DLC but limited to the buffer size `L`.
+    /// Classic CAN frames carry at most 8 bytes, even if the DLC is between 9 and 15.
+    /// Remote frames carry no payload, so an empty slice is returned.
+    pub fn get_payload(&self) -> &[u8] {
+        if self.is_remote_frame() {
+            return &[];
+        }
+
+        let length = match self.is_fd_frame() {
+            true => self.dlc().to_length(),
+            false => self.dlc().to_length().min(MAX_PAYLOAD_CAN_2_0),
+        };
+
+        &self.buff[..length.min(L)]
+    }
+
+    /// Returns true if the frame is a remote transmission request
+    pub fn is_remote_frame(&self) -> bool {
+        self.header.is_remote_frame()
+    }
+
+    /// Returns true if the frame was received in CAN FD format
+    pub fn is_fd_frame(&self) -> bool {
+        self.header.is_fd_frame()
+    }
+
+    /// Returns true if the data bit rate was switched
+    pub fn is_bit_rate_switched(&self) -> bool {
+        self.header.is_bit_rate_switched()
+    }
+
+    /// Returns true if the transmitting node was error passive
+    pub fn is_error_passive(&self) -> bool {
+        self.header.is_error_passive()
+    }
+
+    /// Returns the index of the filter that matched the frame
+    pub fn filter_hit(&self) -> u8 {
+        self.header.filter_hit()
+    }
+
+    /// Returns the timestamp of the reception in time base counter ticks,
+    /// or None if timestamps are disabled for the receive FIFO
+    pub fn timestamp(&self) -> Option<u32> {
+        self.timestamp
+    }
+
+    /// Returns Header register of Receive Message Object
+    pub fn get_header(&self) -> &RxHeader {
+        &self.header
+    }
+}
@@ -29,14 +29,14 @@ impl Clock for TestClock {
     const SCALING_FACTOR: Fraction = Fraction::new(1, 1_000_000);
 
     fn try_now(&self) -> Result<Instant<Self>, ClockError> {
-        if self.next_instants.borrow().len() == 0 {
+        if self.next_instants.borrow().is_empty() {
             return Err(ClockError::Unspecified);
         }
 
         Ok(Instant::new(self.next_instants.borrow_mut().remove(0)))
     }
 
-    fn new_timer<Dur>(&self, duration: Dur) -> Timer<OneShot, Armed, Self, Dur>
+    fn new_timer<Dur>(&self, duration: Dur) -> Timer<'_, OneShot, Armed, Self, Dur>
     where
         Dur: Duration + FixedPoint,
     {
@@ -84,3 +84,107 @@ mock! {
         }
     }
 }
+
+/// Scripted async SPI device, expecting transactions in the given order
+#[cfg(feature = "async")]
+#[derive(Default, Debug)]
+pub struct AsyncSPIDevice {
+    /// Expected bytes written and bytes returned per transaction
+    expectations: alloc::collections::VecDeque<(Vec<u8>, Vec<u8>)>,
+}
+
+#[cfg(feature = "async")]
+impl AsyncSPIDevice {
+    /// Expects a transaction writing `sent` bytes. Read operations are filled with `received` bytes.
+    /// In case of an in-place transfer, `received` replaces the entire buffer.
+    pub fn expect(&mut self, sent: &[u8], received: &[u8]) {
+        self.expectations.push_back((sent.to_vec(), received.to_vec()));
+    }
+
+    /// Expects a single register byte write
+    pub fn expect_register_write(&mut self, expected_write: [u8; 3]) {
+        self.expect(&expected_write, &expected_write);
+    }
+
+    /// Expects a single register byte read
+    pub fn expect_register_read(&mut self, command: [u8; 2], value: u8) {
+        self.expect(&[command[0], command[1], 0x0], &[0x0, 0x0, value]);
+    }
+}
+
+#[cfg(feature = "async")]
+impl Drop for AsyncSPIDevice {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            assert!(self.expectations.is_empty(), "expected SPI transactions not executed");
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl ErrorType for AsyncSPIDevice {
+    type Error = SPIError;
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::spi::SpiDevice<u8> for AsyncSPIDevice {
+    async fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), SPIError> {
+        let (sent, received) = self.expectations.pop_front().expect("unexpected SPI transaction");
+
+        if let [Operation::TransferInPlace(buffer)] = operations {
+            assert_eq!(sent, *buffer);
+            buffer.copy_from_slice(&received);
+            return Ok(());
+        }
+
+        let mut written = Vec::new();
+        let mut received = received.into_iter();
+
+        for operation in operations {
+            match operation {
+                Operation::Write(buffer) => written.extend_from_slice(buffer),
+                Operation::Read(buffer) => buffer.iter_mut().for_each(|byte| *byte = received.next().unwrap()),
+                _ => panic!("unexpected operation {operation:?}"),
+            }
+        }
+
+        assert_eq!(sent, written);
+        Ok(())
+    }
+}
+
+/// Interrupt pin counting the number of waits
+#[cfg(feature = "async")]
+#[derive(Default, Debug)]
+pub struct TestInterruptPin {
+    pub waits: alloc::rc::Rc<core::cell::Cell<usize>>,
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal::digital::ErrorType for TestInterruptPin {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::digital::Wait for TestInterruptPin {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        unimplemented!()
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.waits.set(self.waits.get() + 1);
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        unimplemented!()
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        unimplemented!()
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        unimplemented!()
+    }
+}
@@ -1,6 +1,191 @@
 #![allow(unused_braces)]
+use core::fmt::{self, Debug, Formatter};
 use modular_bitfield_msb::prelude::*;
 
+/// CAN control register
+pub(crate) const REGISTER_C1CON: u16 = 0x000;
+
+/// Nominal bit time configuration register
+pub(crate) const REGISTER_C1NBTCFG: u16 = 0x004;
+
+/// Data bit time configuration register
+pub(crate) const REGISTER_C1DBTCFG: u16 = 0x008;
+
+/// Transmitter delay compensation register
+pub(crate) const REGISTER_C1TDC: u16 = 0x00C;
+
+/// Time base counter register
+pub(crate) const REGISTER_C1TBC: u16 = 0x010;
+
+/// Time stamp control register
+pub(crate) const REGISTER_C1TSCON: u16 = 0x014;
+
+/// Interrupt code register
+pub(crate) const REGISTER_C1VEC: u16 = 0x018;
+
+/// Interrupt register
+pub(crate) const REGISTER_C1INT: u16 = 0x01C;
+
+/// Receive overflow interrupt status register
+pub(crate) const REGISTER_C1RXOVIF: u16 = 0x028;
+
+/// Transmit attempt interrupt status register
+pub(crate) const REGISTER_C1TXATIF: u16 = 0x02C;
+
+/// Transmit request register
+pub(crate) const REGISTER_C1TXREQ: u16 = 0x030;
+
+/// Transmit/receive error count register
+pub(crate) const REGISTER_C1TREC: u16 = 0x034;
+
+/// Bus diagnostic register 0
+pub(crate) const REGISTER_C1BDIAG0: u16 = 0x038;
+
+/// Transmit event FIFO control register
+pub(crate) const REGISTER_C1TEFCON: u16 = 0x040;
+
+/// Transmit event FIFO status register
+pub(crate) const REGISTER_C1TEFSTA: u16 = 0x044;
+
+/// Transmit event FIFO user address register
+pub(crate) const REGISTER_C1TEFUA: u16 = 0x048;
+
+/// Transmit queue control register
+pub(crate) const REGISTER_C1TXQCON: u16 = 0x050;
+
+/// Transmit queue status register
+pub(crate) const REGISTER_C1TXQSTA: u16 = 0x054;
+
+/// Transmit queue user address register
+pub(crate) const REGISTER_C1TXQUA: u16 = 0x058;
+
+/// Oscillator control register
+pub(crate) const REGISTER_OSC: u16 = 0xE00;
+
+/// Input/output control register
+pub(crate) const REGISTER_IOCON: u16 = 0xE04;
+
+/// CRC register
+pub(crate) const REGISTER_CRC: u16 = 0xE08;
+
+/// ECC control register
+pub(crate) const REGISTER_ECCCON: u16 = 0xE0C;
+
+/// ECC status register
+pub(crate) const REGISTER_ECCSTAT: u16 = 0xE10;
+
+/// Device ID register
+pub(crate) const REGISTER_DEVID: u16 = 0xE14;
+
+/// FIFO index for receiving CAN messages
+pub(crate) const FIFO_RX_INDEX: u8 = 1;
+
+/// FIFO index for transmitting CAN messages
+pub(crate) const FIFO_TX_INDEX: u8 = 2;
+
+/// Highest FIFO index
+pub(crate) const MAX_FIFO_INDEX: u8 = 31;
+
+/// Size of message RAM in bytes
+pub(crate) const RAM_SIZE: usize = 2048;
+
+/// Register operation type
+#[derive(Copy, Clone)]
+pub(crate) enum Operation {
+    Reset = 0b0000,
+    Write = 0b0010,
+    Read = 0b0011,
+    /// Write with CRC appended by the host
+    WriteCrc = 0b1010,
+    /// Read with CRC appended by the device
+    ReadCrc = 0b1011,
+    /// Single byte write with CRC, discarded by the device on mismatch
+    WriteSafe = 0b1100,
+}
+
+impl Operation {
+    /// Encodes the two byte command (instruction + address) for the given register
+    pub(crate) fn command(self, register: u16) -> [u8; 2] {
+        let command = (register & 0x0FFF) | ((self as u16) << 12);
+
+        [(command >> 8) as u8, (command & 0xFF) as u8]
+    }
+
+    /// Encodes the three byte command (instruction + address + length) of READ_CRC/WRITE_CRC.
+    /// Length is given in bytes for SFRs and in 4-byte words for the message RAM.
+    pub(crate) fn crc_command(self, register: u16, length: usize) -> [u8; 3] {
+        let command = self.command(register);
+        let length = if (0x400..0xC00).contains(&register) {
+            length / 4
+        } else {
+            length
+        };
+
+        [command[0], command[1], length as u8]
+    }
+}
+
+/// Initial value of the SPI CRC
+pub(crate) const CRC16_INIT: u16 = 0xFFFF;
+
+/// Updates the SPI CRC (polynomial 0x8005, MSB first) with the given data
+pub(crate) fn crc16(mut crc: u16, data: &[u8]) -> u16 {
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+
+    crc
+}
+
+/// Register write, used for sharing the configuration sequence between drivers
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum RegisterWrite {
+    /// Single register byte write
+    Byte(u16, u8),
+    /// 4-byte SFR write
+    Word(u16, u32),
+    /// Zeroes the entire message RAM
+    InitializeRam,
+}
+
+/// Returns the configuration register address for the given FIFO index
+pub(crate) fn fifo_control_register(fifo_index: u8) -> u16 {
+    0x05C + 12 * (fifo_index as u16 - 1)
+}
+
+/// Returns the status register address for the given FIFO index
+pub(crate) fn fifo_status_register(fifo_index: u8) -> u16 {
+    0x60 + 12 * (fifo_index as u16 - 1)
+}
+
+/// Returns the address of fifo user address register for the given index
+pub(crate) fn fifo_user_address_register(fifo_index: u8) -> u16 {
+    0x64 + 12 * (fifo_index as u16 - 1)
+}
+
+/// returns the filter control register address byte of the corresponding filter
+pub(crate) fn filter_control_register_byte(filter_index: u8) -> u16 {
+    0x1D0 + filter_index as u16
+}
+
+/// returns the filter object register address of corresponding filter
+pub(crate) fn filter_object_register(filter_index: u8) -> u16 {
+    0x1F0 + 8 * (filter_index as u16)
+}
+
+/// returns the filter mask register address of corresponding filter
+pub(crate) fn filter_mask_register(filter_index: u8) -> u16 {
+    0x1F4 + 8 * (filter_index as u16)
+}
+
 #[bitfield]
 #[derive(Default)]
 #[repr(u8)]
@@ -111,7 +296,7 @@ pub struct FifoStatusReg0 {
 
 /// Filter mask register
 #[bitfield]
-#[derive(Default, Debug, Eq, PartialEq)]
+#[derive(Default, Eq, PartialEq)]
 #[repr(u32)]
 pub struct FilterMaskReg {
     #[skip]
@@ -128,9 +313,20 @@ pub struct FilterMaskReg {
     pub msid: B11,
 }
 
+impl Debug for FilterMaskReg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilterMaskReg")
+            .field("mide", &self.mide())
+            .field("msid11", &self.msid11())
+            .field("meid", &self.meid())
+            .field("msid", &self.msid())
+            .finish()
+    }
+}
+
 /// Filter object register
 #[bitfield]
-#[derive(Default, Debug, Eq, PartialEq)]
+#[derive(Default, Eq, PartialEq)]
 #[repr(u32)]
 pub struct FilterObjectReg {
     #[skip]
@@ -147,9 +343,20 @@ pub struct FilterObjectReg {
     pub sid: B11,
 }
 
+impl Debug for FilterObjectReg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilterObjectReg")
+            .field("exide", &self.exide())
+            .field("sid11", &self.sid11())
+            .field("eid", &self.eid())
+            .field("sid", &self.sid())
+            .finish()
+    }
+}
+
 /// Nominal bit time configuration register
 #[bitfield]
-#[derive(Default, Debug, Eq, PartialEq)]
+#[derive(Default, Eq, PartialEq)]
 #[repr(u32)]
 pub struct C1NBTCFG {
     /// Baud rate prescalar bits
@@ -165,3 +372,49 @@ pub struct C1NBTCFG {
     /// Synchronization Jump Width bits
     pub sjw: B7,
 }
+
+/// Data bit time configuration register
+#[bitfield]
+#[derive(Default, Eq, PartialEq)]
+#[repr(u32)]
+pub struct C1DBTCFG {
+    /// Baud rate prescalar bits
+    pub brp: B8,
+    #[skip]
+    __: B3,
+    /// Time Segment 1 bits (Propagation Segment + Phase Segment 1)
+    pub tseg1: B5,
+    #[skip]
+    __: B4,
+    /// Time Segment 2 bits (Phase Segment 2)
+    pub tseg2: B4,
+    #[skip]
+    __: B4,
+    /// Synchronization Jump Width bits
+    pub sjw: B4,
+}
+
+/// Transmitter delay compensation register
+#[bitfield]
+#[derive(Default, Eq, PartialEq)]
+#[repr(u32)]
+pub struct C1TDC {
+    #[skip]
+    __: B6,
+    /// Enable edge filtering during bus integration state bit
+    pub edgflten: bool,
+    /// Enable 12-bit SID in CAN FD base format messages bit
+    pub sid11en: bool,
+    #[skip]
+    __: B6,
+    /// Transmitter delay compensation mode bits
+    pub tdcmod: B2,
+    #[skip]
+    __: B1,
+    /// Transmitter delay compensation offset bits (two's complement)
+    pub tdco: B7,
+    #[skip]
+    __: B2,
+    /// Transmitter delay compensation value bits
+    pub tdcv: B6,
+}
@@ -0,0 +1,1358 @@
+//! # Register-level MCP2517FD simulator
+//!
+//! [Simulator] is a software model of the controller for host tests, which is accessed by [MCP2517](crate::can::MCP2517)
+//! through [SimulatedDevice]. The simulator decodes the SPI instruction set (including CRC instructions), maintains
+//! the SFRs and the 2 KB message RAM and implements:
+//! * FIFO, TXQ and TEF head/tail handling (UINC, TXREQ, FRESET), transmit aborts and RAM allocation on leaving configuration mode
+//! * Acceptance filters and masks
+//! * Operation mode transitions
+//! * Internal and external loopback
+//! * Status and interrupt flags
+//! * Sleep and low-power mode, wake-up by the oscillator or bus activity (wake-up filter is not modeled)
+//! * GPIO pins and transceiver standby control, levels of inputs are applied by [Simulator::set_gpio_input]
+//! * Device ID and low-power mode support of the selected variant ([Simulator::set_variant])
+//!
+//! Frames transmitted in normal mode are taken by [Simulator::take_transmitted], while frames received from the
+//! bus are injected by [Simulator::receive]. Bit timing and error handling are not modeled, so all transmissions
+//! succeed immediately. The TXQ transmits messages in the order they are loaded.
+//!
+//! Multiple simulators may be connected by a [VirtualBus](bus::VirtualBus) instead, which adds arbitration,
+//! acknowledgement, error handling and bit rates.
+//!
+//!```
+//!# use mcp2517::can::{CanController, MCP2517};
+//!# use mcp2517::config::{Configuration, RequestMode};
+//!# use mcp2517::message::{Can20, TxMessage};
+//!# use mcp2517::simulator::{SimFrame, SimulatedClock, Simulator};
+//!# use embedded_can::{Id, StandardId};
+//!#
+//! let simulator = Simulator::new();
+//! let clock = SimulatedClock::default();
+//! let mut controller = MCP2517::new(simulator.device());
+//!
+//! let config = Configuration {
+//!     mode: RequestMode::InternalLoopback,
+//!     ..Default::default()
+//! };
+//! controller.configure(&config, &clock).unwrap();
+//!
+//! // Message is received by the controller itself
+//! let id = Id::Standard(StandardId::new(0x55).unwrap());
+//! let message = TxMessage::new(Can20::<8> {}, &[1, 2, 3, 4], id).unwrap();
+//! controller.transmit(&message, true).unwrap();
+//!
+//! let received = controller.receive::<8>(false).unwrap();
+//! assert_eq!(id, received.id());
+//! assert_eq!([1, 2, 3, 4], received.get_payload());
+//!
+//! // Frame received from the bus
+//! simulator.receive(&SimFrame::new(id, &[5, 6]));
+//! assert_eq!([5, 6], controller.receive::<8>(false).unwrap().get_payload());
+//!```
+use crate::config::GpioPin;
+use crate::registers::{crc16, CRC16_INIT, MAX_FIFO_INDEX, RAM_SIZE};
+use crate::status::{ChipVariant, OperationMode};
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::convert::Infallible;
+use core::fmt::{self, Debug, Formatter};
+use embedded_can::{ExtendedId, Id, StandardId};
+use embedded_hal::spi::{ErrorType, Operation, SpiDevice};
+use embedded_time::clock::Error;
+use embedded_time::duration::{Duration, Fraction};
+use embedded_time::fixed_point::FixedPoint;
+use embedded_time::timer::param::{Armed, OneShot};
+use embedded_time::{Clock, Instant, Timer};
+
+pub mod bus;
+
+const C1CON: u16 = 0x000;
+const C1TBC: u16 = 0x010;
+const C1TSCON: u16 = 0x014;
+const C1VEC: u16 = 0x018;
+const C1INT: u16 = 0x01C;
+const C1RXIF: u16 = 0x020;
+const C1TXIF: u16 = 0x024;
+const C1RXOVIF: u16 = 0x028;
+const C1TXATIF: u16 = 0x02C;
+const C1TXREQ: u16 = 0x030;
+const C1TREC: u16 = 0x034;
+const C1TEFCON: u16 = 0x040;
+const C1TEFSTA: u16 = 0x044;
+const C1TEFUA: u16 = 0x048;
+const C1FLTCON: u16 = 0x1D0;
+const C1FLTOBJ: u16 = 0x1F0;
+const OSC: u16 = 0xE00;
+const IOCON: u16 = 0xE04;
+const CRC: u16 = 0xE08;
+const ECCSTAT: u16 = 0xE10;
+const DEVID: u16 = 0xE14;
+const RAM_START: u16 = 0x400;
+
+/// Payload sizes selected by PLSIZE
+const PAYLOAD_SIZES: [u16; 8] = [8, 12, 16, 20, 24, 32, 48, 64];
+
+/// CAN frame transmitted or received by the simulator
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimFrame {
+    /// Standard or extended identifier
+    pub id: Id,
+
+    /// Payload, padded to the length encoded by the DLC
+    pub data: Vec<u8>,
+
+    /// CAN FD format
+    pub fd: bool,
+
+    /// Bit rate switch of CAN FD frames
+    pub bit_rate_switch: bool,
+
+    /// Remote transmission request, data of remote frames is zeroed
+    pub remote: bool,
+}
+
+impl SimFrame {
+    /// Creates a data frame, which uses the CAN FD format if the payload exceeds 8 bytes
+    pub fn new(id: Id, data: &[u8]) -> Self {
+        let mut data = data.to_vec();
+        data.resize(dlc_to_length(length_to_dlc(data.len()), true), 0);
+
+        Self {
+            id,
+            fd: data.len() > 8,
+            data,
+            bit_rate_switch: false,
+            remote: false,
+        }
+    }
+
+    /// Creates a remote frame requesting the given number of data bytes
+    pub fn new_remote(id: Id, length: usize) -> Self {
+        Self {
+            id,
+            data: vec![0; length],
+            fd: false,
+            bit_rate_switch: false,
+            remote: true,
+        }
+    }
+
+    /// Encodes the first two words of the message object
+    fn to_header(&self) -> (u32, u32) {
+        let (id_bits, extended) = match self.id {
+            Id::Standard(id) => (id.as_raw() as u32, false),
+            Id::Extended(id) => {
+                let raw = id.as_raw();
+                ((raw >> 18) | ((raw & 0x3FFFF) << 11), true)
+            }
+        };
+
+        let flags = length_to_dlc(self.data.len()) as u32
+            | (extended as u32) << 4
+            | (self.remote as u32) << 5
+            | (self.bit_rate_switch as u32) << 6
+            | (self.fd as u32) << 7;
+
+        (id_bits, flags)
+    }
+
+    /// Decodes the frame of the given message object header and payload
+    fn from_header(word0: u32, word1: u32, payload: &[u8]) -> Self {
+        let extended = word1 & (1 << 4) != 0;
+        let fd = word1 & (1 << 7) != 0;
+        let standard_id = word0 & 0x7FF;
+
+        let id = if extended {
+            Id::Extended(ExtendedId::new((standard_id << 18) | ((word0 >> 11) & 0x3FFFF)).unwrap())
+        } else {
+            Id::Standard(StandardId::new(standard_id as u16).unwrap())
+        };
+
+        let length = dlc_to_length((word1 & 0xF) as u8, fd).min(payload.len());
+        let remote = word1 & (1 << 5) != 0;
+
+        Self {
+            id,
+            data: if remote {
+                vec![0; length]
+            } else {
+                payload[..length].to_vec()
+            },
+            fd,
+            bit_rate_switch: word1 & (1 << 6) != 0,
+            remote,
+        }
+    }
+
+    /// Returns the raw identifier bits, lower bits are SID and upper bits are EID
+    fn filter_bits(&self) -> u32 {
+        self.to_header().0
+    }
+}
+
+/// Converts the payload length to the DLC, lengths between valid CAN FD lengths are rounded up
+fn length_to_dlc(length: usize) -> u8 {
+    match length {
+        0..=8 => length as u8,
+        9..=12 => 9,
+        13..=16 => 10,
+        17..=20 => 11,
+        21..=24 => 12,
+        25..=32 => 13,
+        33..=48 => 14,
+        _ => 15,
+    }
+}
+
+/// Converts the DLC to the payload length, classic frames are limited to 8 bytes
+fn dlc_to_length(dlc: u8, fd: bool) -> usize {
+    match dlc {
+        0..=8 => dlc as usize,
+        _ if !fd => 8,
+        9 => 12,
+        10 => 16,
+        11 => 20,
+        12 => 24,
+        13 => 32,
+        14 => 48,
+        _ => 64,
+    }
+}
+
+/// Head/tail state of a FIFO
+#[derive(Copy, Clone, Debug, Default)]
+struct FifoState {
+    /// Index of the next message object to be written
+    head: u8,
+    /// Index of the next message object to be read
+    tail: u8,
+    /// Number of occupied message objects
+    count: u8,
+    /// Number of failed transmission attempts of the next message
+    failed_attempts: u8,
+}
+
+/// RAM region allocated to a FIFO when leaving configuration mode
+#[derive(Copy, Clone, Debug, Default)]
+struct Region {
+    /// Offset of first message object relative to RAM start
+    base: u16,
+    /// Message object size in bytes
+    object_size: u16,
+    /// Number of message objects, zero if not allocated
+    depth: u8,
+}
+
+impl Region {
+    /// RAM address of the message object with the given index
+    fn address(&self, index: u8) -> u16 {
+        RAM_START + self.base + index as u16 * self.object_size
+    }
+}
+
+/// Internal state of the simulated controller
+struct State {
+    /// SFRs and message RAM, indexed by address
+    memory: [u8; 4096],
+
+    /// State of TXQ (index 0) and FIFO 1 - 31
+    fifos: [FifoState; 32],
+
+    /// RAM regions of TXQ (index 0) and FIFO 1 - 31
+    regions: [Region; 32],
+
+    /// State of transmit event FIFO
+    tef: FifoState,
+
+    /// RAM region of transmit event FIFO
+    tef_region: Region,
+
+    /// Frames transmitted to the bus
+    transmitted: Vec<SimFrame>,
+
+    /// Transmissions in normal mode are processed by a virtual bus
+    bus_node: bool,
+
+    /// External levels applied to GPIO0 (bit 0) and GPIO1 (bit 1)
+    gpio_inputs: u8,
+
+    /// Simulated controller variant
+    variant: ChipVariant,
+}
+
+impl State {
+    fn new() -> Self {
+        let mut state = Self {
+            memory: [0u8; 4096],
+            fifos: [FifoState::default(); 32],
+            regions: [Region::default(); 32],
+            tef: FifoState::default(),
+            tef_region: Region::default(),
+            transmitted: Vec::new(),
+            bus_node: false,
+            gpio_inputs: 0,
+            variant: ChipVariant::default(),
+        };
+        state.reset();
+
+        state
+    }
+
+    /// Restores the reset values of all registers, controller enters configuration mode
+    fn reset(&mut self) {
+        self.memory = [0u8; 4096];
+        self.fifos = [FifoState::default(); 32];
+        self.regions = [Region::default(); 32];
+        self.tef = FifoState::default();
+        self.tef_region = Region::default();
+
+        self.set_word(C1CON, 0x0498_0760);
+        self.set_word(0x004, 0x003E_0F0F);
+        self.set_word(0x008, 0x000E_0303);
+        self.set_word(0x00C, 0x0002_1000);
+        self.set_word(C1VEC, 0x4040_0040);
+        self.set_word(OSC, 0x0000_0460);
+        self.set_word(IOCON, 0x0300_0003);
+
+        // DEV bits, revision 0
+        let device_id = match self.variant {
+            ChipVariant::MCP2517FD => 0x00,
+            ChipVariant::MCP2518FD | ChipVariant::MCP251863 => 0x10,
+        };
+        self.set_byte(DEVID, device_id);
+
+        for index in 0..=MAX_FIFO_INDEX {
+            self.set_byte(control_register(index) + 2, 0x60);
+        }
+
+        self.update();
+    }
+
+    fn byte(&self, address: u16) -> u8 {
+        self.memory[(address & 0xFFF) as usize]
+    }
+
+    fn set_byte(&mut self, address: u16, value: u8) {
+        self.memory[(address & 0xFFF) as usize] = value;
+    }
+
+    fn word(&self, address: u16) -> u32 {
+        u32::from_le_bytes([
+            self.byte(address),
+            self.byte(address + 1),
+            self.byte(address + 2),
+            self.byte(address + 3),
+        ])
+    }
+
+    fn set_word(&mut self, address: u16, value: u32) {
+        for (offset, byte) in value.to_le_bytes().into_iter().enumerate() {
+            self.set_byte(address + offset as u16, byte);
+        }
+    }
+
+    /// Current operation mode (OPMOD)
+    fn mode(&self) -> OperationMode {
+        OperationMode::from_register(self.byte(C1CON + 2))
+    }
+
+    /// Applies a byte written by SPI, including the side effects of the register
+    fn write(&mut self, address: u16, value: u8) {
+        let address = address & 0xFFF;
+
+        match address {
+            // OPMOD is read-only
+            0x002 => self.set_byte(address, (self.byte(address) & 0xE0) | (value & 0x1F)),
+            0x003 => self.write_request_mode(value),
+            OSC => self.write_oscillator(value),
+            // Flags are cleared by writing zero, other flags are read-only
+            0x01C => self.clear_flags(address, value, 0b0000_1100),
+            0x01D => self.clear_flags(address, value, 0b1111_0000),
+            0x041 => self.write_tef_control(value),
+            C1TEFSTA => self.clear_flags(address, value, 0b0000_1000),
+            0x050..=0x1CF => self.write_fifo_register(address, value),
+            // CRCERRIF and FERRIF
+            0xE0A => self.clear_flags(address, value, 0b0000_0011),
+            // SECIF and DEDIF
+            ECCSTAT => self.clear_flags(address, value, 0b0000_0110),
+            // Read-only status registers
+            C1VEC..=0x01B | C1RXIF..=0x037 | 0x045..=0x04F | 0xE01 | 0xE06 | DEVID..=0xE17 => {}
+            _ => self.set_byte(address, value),
+        }
+    }
+
+    /// Sets the bits of the given mask
+    fn set_flags(&mut self, address: u16, mask: u8) {
+        self.set_byte(address, self.byte(address) | mask);
+    }
+
+    /// Clears the flags of the given mask, which are written as zero
+    fn clear_flags(&mut self, address: u16, value: u8, mask: u8) {
+        self.set_byte(address, self.byte(address) & (value | !mask));
+    }
+
+    /// Handles the write of CiCON byte 3 (REQOP and ABAT)
+    fn write_request_mode(&mut self, value: u8) {
+        // ABAT aborts all pending transmissions
+        if value & (1 << 3) != 0 {
+            for index in 0..=MAX_FIFO_INDEX {
+                let control = control_register(index) + 1;
+                if self.byte(control) & 0b10 != 0 {
+                    self.set_byte(control, self.byte(control) & !0b10);
+                    // TXABT
+                    let status = control_register(index) + 4;
+                    self.set_byte(status, self.byte(status) | (1 << 7));
+                }
+            }
+        }
+
+        self.set_byte(C1CON + 3, value & !(1 << 3));
+
+        // Error counters are reset on restart from bus-off state (TXBO)
+        let requested = value & 0b111;
+        if self.byte(C1TREC + 2) & (1 << 5) != 0 || requested == OperationMode::Configuration as u8 {
+            self.set_word(C1TREC, 0);
+        }
+
+        let previous = self.mode();
+        if previous as u8 == requested {
+            return;
+        }
+
+        self.set_byte(C1CON + 2, (self.byte(C1CON + 2) & 0x1F) | (requested << 5));
+
+        // MODIF
+        self.set_byte(C1INT, self.byte(C1INT) | (1 << 3));
+
+        if requested == OperationMode::Configuration as u8 {
+            self.reset_fifos();
+        } else if requested == OperationMode::Sleep as u8 {
+            // Oscillator is disabled in sleep mode (OSCDIS)
+            self.set_flags(OSC, 1 << 2);
+        } else if previous == OperationMode::Configuration {
+            self.allocate_ram();
+        }
+    }
+
+    /// Handles the write of the OSC register, clearing OSCDIS wakes the controller
+    fn write_oscillator(&mut self, value: u8) {
+        // LPMEN is not implemented by the MCP2517FD
+        let value = match self.variant.supports_low_power_mode() {
+            true => value,
+            false => value & !(1 << 3),
+        };
+
+        let sleeping = self.mode() == OperationMode::Sleep;
+        let low_power_mode = self.byte(OSC) & (1 << 3) != 0;
+
+        if sleeping && value & (1 << 2) == 0 && low_power_mode {
+            // LPM: all registers are reset
+            self.reset();
+        } else if sleeping && value & (1 << 2) == 0 {
+            self.wake_up();
+        }
+
+        self.set_byte(OSC, value);
+    }
+
+    /// Leaves sleep mode by entering configuration mode, oscillator is enabled
+    fn wake_up(&mut self) {
+        let configuration = OperationMode::Configuration as u8;
+
+        self.set_byte(OSC, self.byte(OSC) & !(1 << 2));
+        self.set_byte(C1CON + 3, (self.byte(C1CON + 3) & !0b111) | configuration);
+        self.set_byte(C1CON + 2, (self.byte(C1CON + 2) & 0x1F) | (configuration << 5));
+
+        // MODIF
+        self.set_flags(C1INT, 1 << 3);
+        self.reset_fifos();
+    }
+
+    /// Bus activity wakes the controller from sleep mode (WAKIF)
+    fn detect_bus_activity(&mut self) {
+        if self.mode() == OperationMode::Sleep {
+            self.set_flags(C1INT + 1, 1 << 6);
+            self.wake_up();
+        }
+    }
+
+    /// Resets all FIFOs and pending transmissions
+    fn reset_fifos(&mut self) {
+        self.fifos = [FifoState::default(); 32];
+        self.tef = FifoState::default();
+
+        for index in 0..=MAX_FIFO_INDEX {
+            let control = control_register(index) + 1;
+            self.set_byte(control, self.byte(control) & !0b10);
+        }
+    }
+
+    /// Allocates message RAM in order TEF, TXQ, FIFO 1 - 31
+    fn allocate_ram(&mut self) {
+        let control = self.byte(C1CON + 2);
+        let mut offset = 0u16;
+
+        let mut allocate = |depth: u8, object_size: u16| {
+            let size = depth as u16 * object_size;
+            if offset + size > RAM_SIZE as u16 {
+                return Region::default();
+            }
+
+            let region = Region {
+                base: offset,
+                object_size,
+                depth,
+            };
+            offset += size;
+            region
+        };
+
+        // STEF
+        self.tef_region = if control & (1 << 3) != 0 {
+            let tef_control = self.word(C1TEFCON);
+            let object_size = if tef_control & (1 << 5) != 0 { 12 } else { 8 };
+            allocate(((tef_control >> 24) & 0x1F) as u8 + 1, object_size)
+        } else {
+            Region::default()
+        };
+
+        for index in 0..=MAX_FIFO_INDEX {
+            // TXQEN
+            if index == 0 && control & (1 << 4) == 0 {
+                self.regions[0] = Region::default();
+                continue;
+            }
+
+            let fifo_control = self.word(control_register(index));
+            let payload_size = PAYLOAD_SIZES[(fifo_control >> 29) as usize];
+            let timestamp = !self.is_transmit(index) && fifo_control & (1 << 5) != 0;
+            let object_size = 8 + payload_size + if timestamp { 4 } else { 0 };
+
+            self.regions[index as usize] = allocate(((fifo_control >> 24) & 0x1F) as u8 + 1, object_size);
+        }
+    }
+
+    /// Returns true if the FIFO is configured for transmission, TXQ is always a transmit FIFO
+    fn is_transmit(&self, index: u8) -> bool {
+        index == 0 || self.byte(control_register(index)) & (1 << 7) != 0
+    }
+
+    /// Handles writes to TXQ and FIFO registers
+    fn write_fifo_register(&mut self, address: u16, value: u8) {
+        let index = ((address - 0x050) / 12) as u8;
+        let offset = (address - 0x050) % 12;
+
+        match offset {
+            1 => self.write_fifo_control(index, value),
+            // TXABT, TXLARB, TXERR, TXATIF and RXOVIF are cleared by writing zero
+            4 => self.clear_flags(address, value, 0b1111_1000),
+            // Status and user address are read-only
+            5..=11 => {}
+            _ => self.set_byte(address, value),
+        }
+    }
+
+    /// Handles UINC, TXREQ and FRESET of a FIFO control register
+    fn write_fifo_control(&mut self, index: u8, value: u8) {
+        let address = control_register(index) + 1;
+        let depth = self.regions[index as usize].depth;
+        let transmit = self.is_transmit(index);
+        let fifo = &mut self.fifos[index as usize];
+
+        // FRESET
+        if value & (1 << 2) != 0 {
+            *fifo = FifoState::default();
+            self.set_byte(address, 0);
+            return;
+        }
+
+        // UINC: message loaded by transmit FIFO or read from receive FIFO
+        if value & 1 != 0 && depth > 0 {
+            if transmit && fifo.count < depth {
+                fifo.head = (fifo.head + 1) % depth;
+                fifo.count += 1;
+            } else if !transmit && fifo.count > 0 {
+                fifo.tail = (fifo.tail + 1) % depth;
+                fifo.count -= 1;
+            }
+        }
+
+        // TXREQ remains set until all messages are transmitted, clearing it aborts pending messages
+        let txreq = value & (1 << 1) != 0 && transmit;
+        if !txreq && self.byte(address) & 0b10 != 0 {
+            // TXABT
+            self.set_flags(control_register(index) + 4, 1 << 7);
+        }
+        self.set_byte(address, (txreq as u8) << 1);
+
+        // Requesting transmission restarts the attempt count, status flags remain set until cleared by SPI
+        if txreq {
+            self.fifos[index as usize].failed_attempts = 0;
+        }
+    }
+
+    /// Handles UINC and FRESET of the TEF control register
+    fn write_tef_control(&mut self, value: u8) {
+        if value & (1 << 2) != 0 {
+            self.tef = FifoState::default();
+        } else if value & 1 != 0 && self.tef.count > 0 {
+            self.tef.tail = (self.tef.tail + 1) % self.tef_region.depth;
+            self.tef.count -= 1;
+        }
+    }
+
+    /// Returns true if the controller takes part in bus communication in the current mode
+    fn transmission_enabled(&self) -> bool {
+        matches!(
+            self.mode(),
+            OperationMode::NormalCANFD
+                | OperationMode::NormalCAN2_0
+                | OperationMode::InternalLoopback
+                | OperationMode::ExternalLoopback
+        )
+    }
+
+    /// Returns the FIFO of the next pending transmission, selected by TXQ first and then by FIFO priority
+    fn next_transmission(&self) -> Option<u8> {
+        if !self.transmission_enabled() {
+            return None;
+        }
+
+        (0..=MAX_FIFO_INDEX)
+            .filter(|index| {
+                self.is_transmit(*index)
+                    && self.fifos[*index as usize].count > 0
+                    && self.byte(control_register(*index) + 1) & 0b10 != 0
+            })
+            .min_by_key(|index| {
+                let priority = self.byte(control_register(*index) + 2) & 0x1F;
+                (*index != 0, 31 - priority, *index)
+            })
+    }
+
+    /// Returns the header words and payload of the next message of the given transmit FIFO
+    fn pending_message(&self, index: u8) -> (u32, u32, Vec<u8>) {
+        let region = self.regions[index as usize];
+        let address = region.address(self.fifos[index as usize].tail);
+
+        let payload_size = region.object_size - 8;
+        let payload = (0..payload_size).map(|offset| self.byte(address + 8 + offset)).collect();
+
+        (self.word(address), self.word(address + 4), payload)
+    }
+
+    /// Returns the frame of the next message of the given transmit FIFO
+    fn pending_frame(&self, index: u8) -> SimFrame {
+        let (word0, word1, payload) = self.pending_message(index);
+
+        SimFrame::from_header(word0, word1, &payload)
+    }
+
+    /// Removes the next message of the given FIFO and returns its frame
+    fn complete_transmission(&mut self, index: u8) -> SimFrame {
+        let (word0, word1, payload) = self.pending_message(index);
+
+        let depth = self.regions[index as usize].depth;
+        let fifo = &mut self.fifos[index as usize];
+        fifo.tail = (fifo.tail + 1) % depth;
+        fifo.count -= 1;
+        fifo.failed_attempts = 0;
+
+        // TXREQ is cleared once all messages are transmitted
+        if fifo.count == 0 {
+            let control = control_register(index) + 1;
+            self.set_byte(control, self.byte(control) & !0b10);
+        }
+
+        self.store_transmit_event(word0, word1);
+
+        SimFrame::from_header(word0, word1, &payload)
+    }
+
+    /// Stores the event of a transmitted message in the TEF, if enabled
+    fn store_transmit_event(&mut self, word0: u32, word1: u32) {
+        let region = self.tef_region;
+        if region.depth == 0 {
+            return;
+        }
+
+        if self.tef.count == region.depth {
+            // TEFOVIF
+            self.set_byte(C1TEFSTA, self.byte(C1TEFSTA) | (1 << 3));
+            return;
+        }
+
+        let address = region.address(self.tef.head);
+        self.set_word(address, word0);
+        self.set_word(address + 4, word1);
+        if region.object_size == 12 {
+            self.set_word(address + 8, self.word(C1TBC));
+        }
+
+        self.tef.head = (self.tef.head + 1) % region.depth;
+        self.tef.count += 1;
+    }
+
+    /// Stores the frame in the receive FIFO of the first matching filter.
+    /// Returns true if the frame was accepted by a filter.
+    fn receive(&mut self, frame: &SimFrame) -> bool {
+        if matches!(self.mode(), OperationMode::Configuration | OperationMode::Sleep) {
+            return false;
+        }
+
+        let Some((filter, index)) = self.match_filter(frame) else {
+            return false;
+        };
+
+        let region = self.regions[index as usize];
+        if region.depth == 0 {
+            return false;
+        }
+
+        if self.is_transmit(index) {
+            // RTREN: remote frame requests transmission of the loaded response message
+            let control = control_register(index);
+            let auto_rtr = frame.remote && self.byte(control) & (1 << 6) != 0;
+
+            if auto_rtr && self.fifos[index as usize].count > 0 {
+                self.set_flags(control + 1, 1 << 1);
+            }
+
+            return auto_rtr;
+        }
+
+        if self.fifos[index as usize].count == region.depth {
+            // RXOVIF
+            let status = control_register(index) + 4;
+            self.set_byte(status, self.byte(status) | (1 << 3));
+            return true;
+        }
+
+        let address = region.address(self.fifos[index as usize].head);
+        let (word0, word1) = frame.to_header();
+        self.set_word(address, word0);
+        self.set_word(address + 4, word1 | ((filter as u32) << 11));
+
+        let mut payload_address = address + 8;
+        if self.byte(control_register(index)) & (1 << 5) != 0 {
+            self.set_word(payload_address, self.word(C1TBC));
+            payload_address += 4;
+        }
+
+        let payload_size = (region.object_size - (payload_address - address)) as usize;
+        for offset in 0..payload_size {
+            let byte = frame.data.get(offset).copied().unwrap_or(0);
+            self.set_byte(payload_address + offset as u16, byte);
+        }
+
+        let fifo = &mut self.fifos[index as usize];
+        fifo.head = (fifo.head + 1) % region.depth;
+        fifo.count += 1;
+
+        true
+    }
+
+    /// Returns the index of the first enabled filter matching the frame and its FIFO
+    fn match_filter(&self, frame: &SimFrame) -> Option<(u8, u8)> {
+        let extended = matches!(frame.id, Id::Extended(_));
+        let frame_bits = frame.filter_bits();
+
+        (0..32u16).find_map(|filter| {
+            let control = self.byte(C1FLTCON + filter);
+            if control & (1 << 7) == 0 {
+                return None;
+            }
+
+            let object = self.word(C1FLTOBJ + filter * 8);
+            let mut mask = self.word(C1FLTOBJ + filter * 8 + 4);
+
+            // MIDE: match only the frame format given by EXIDE
+            if mask & (1 << 30) != 0 && (object & (1 << 30) != 0) != extended {
+                return None;
+            }
+
+            // Standard frames are filtered by SID only
+            mask &= if extended { 0x1FFF_FFFF } else { 0x7FF };
+
+            ((frame_bits ^ object) & mask == 0).then_some((filter as u8, control & 0x1F))
+        })
+    }
+
+    /// Transmits all pending messages, as no bus is simulated.
+    /// Frames are received by the controller itself in loopback modes.
+    /// Transmissions of bus nodes in normal mode are left to the [VirtualBus](bus::VirtualBus).
+    fn process_transmissions(&mut self) {
+        while let Some(index) = self.next_transmission() {
+            let loopback = matches!(
+                self.mode(),
+                OperationMode::InternalLoopback | OperationMode::ExternalLoopback
+            );
+            if self.bus_node && !loopback {
+                break;
+            }
+
+            let frame = self.complete_transmission(index);
+
+            match self.mode() {
+                OperationMode::InternalLoopback => {
+                    self.receive(&frame);
+                }
+                OperationMode::ExternalLoopback => {
+                    self.receive(&frame);
+                    self.transmitted.push(frame);
+                }
+                _ => self.transmitted.push(frame),
+            }
+        }
+
+        self.update();
+    }
+
+    /// Updates all status registers derived from the FIFO state
+    fn update(&mut self) {
+        let mut rx_pending = 0u32;
+        let mut tx_pending = 0u32;
+        let mut rx_overflow = 0u32;
+        let mut tx_attempts = 0u32;
+        let mut tx_requests = 0u32;
+
+        for index in 0..=MAX_FIFO_INDEX {
+            let control_address = control_register(index);
+            let fifo = self.fifos[index as usize];
+            let region = self.regions[index as usize];
+            let transmit = self.is_transmit(index);
+
+            let (flags, current) = if transmit {
+                let not_full = fifo.count < region.depth;
+                let half_empty = fifo.count <= region.depth / 2;
+                (
+                    not_full as u8 | (half_empty as u8) << 1 | ((fifo.count == 0) as u8) << 2,
+                    fifo.head,
+                )
+            } else {
+                let half_full = region.depth > 0 && fifo.count >= region.depth.div_ceil(2);
+                let full = region.depth > 0 && fifo.count == region.depth;
+                (
+                    (fifo.count > 0) as u8 | (half_full as u8) << 1 | (full as u8) << 2,
+                    fifo.tail,
+                )
+            };
+
+            let status_address = control_address + 4;
+            let status = (self.byte(status_address) & 0xF8) | flags;
+            self.set_byte(status_address, status);
+            self.set_byte(status_address + 1, current);
+            self.set_word(
+                control_address + 8,
+                region.address(current).wrapping_sub(RAM_START) as u32,
+            );
+
+            let enables = self.byte(control_address);
+            let bit = 1 << index;
+
+            if status & enables & 0b111 != 0 {
+                if transmit {
+                    tx_pending |= bit;
+                } else {
+                    rx_pending |= bit;
+                }
+            }
+
+            if status & (1 << 3) != 0 {
+                rx_overflow |= bit;
+            }
+            if status & (1 << 4) != 0 {
+                tx_attempts |= bit;
+            }
+            if self.byte(control_address + 1) & 0b10 != 0 {
+                tx_requests |= bit;
+            }
+        }
+
+        self.set_word(C1RXIF, rx_pending);
+        self.set_word(C1TXIF, tx_pending);
+        self.set_word(C1RXOVIF, rx_overflow);
+        self.set_word(C1TXATIF, tx_attempts);
+        self.set_word(C1TXREQ, tx_requests);
+
+        self.update_tef();
+        self.update_interrupts(rx_pending, tx_pending, rx_overflow, tx_attempts);
+        self.update_oscillator();
+        self.update_gpio();
+    }
+
+    /// Updates TEF status and user address
+    fn update_tef(&mut self) {
+        let region = self.tef_region;
+        let count = self.tef.count;
+
+        let not_empty = count > 0;
+        let half_full = region.depth > 0 && count >= region.depth.div_ceil(2);
+        let full = region.depth > 0 && count == region.depth;
+
+        let status = (self.byte(C1TEFSTA) & (1 << 3)) | not_empty as u8 | (half_full as u8) << 1 | (full as u8) << 2;
+        self.set_byte(C1TEFSTA, status);
+        self.set_word(C1TEFUA, region.address(self.tef.tail).wrapping_sub(RAM_START) as u32);
+    }
+
+    /// Updates CiINT flags and CiVEC codes
+    fn update_interrupts(&mut self, rx_pending: u32, tx_pending: u32, rx_overflow: u32, tx_attempts: u32) {
+        let tef_pending = self.byte(C1TEFSTA) & self.byte(C1TEFCON) & 0x0F != 0;
+        let crc_error = self.byte(CRC + 2) & 0b11 != 0;
+        let ecc_error = self.byte(ECCSTAT) & 0b110 != 0;
+
+        // TBCIF, MODIF, SERRIF, CERRIF, WAKIF and IVMIF are kept
+        let mut flags = self.word(C1INT) & 0xF00C;
+        flags |= (tx_pending != 0) as u32
+            | ((rx_pending != 0) as u32) << 1
+            | (tef_pending as u32) << 4
+            | (ecc_error as u32) << 8
+            | (crc_error as u32) << 9
+            | ((tx_attempts != 0) as u32) << 10
+            | ((rx_overflow != 0) as u32) << 11;
+        self.set_word(C1INT, (self.word(C1INT) & 0xFFFF_0000) | flags);
+
+        let enabled = flags & (self.word(C1INT) >> 16);
+        let fifo_pending =
+            (if enabled & 1 != 0 { tx_pending } else { 0 }) | (if enabled & 2 != 0 { rx_pending } else { 0 });
+
+        // Interrupt codes of flags in order of CiINT bits
+        let flag_codes = [
+            (2, 0x46),
+            (3, 0x47),
+            (4, 0x49),
+            (10, 0x4A),
+            (11, 0x43),
+            (12, 0x44),
+            (13, 0x41),
+            (14, 0x42),
+            (15, 0x48),
+        ];
+
+        let interrupt_code = if fifo_pending != 0 {
+            fifo_pending.trailing_zeros()
+        } else {
+            flag_codes
+                .iter()
+                .find(|(bit, _)| enabled & (1 << bit) != 0)
+                .map_or(0x40, |(_, code)| *code)
+        };
+
+        let fifo_code = |pending: u32| {
+            if pending == 0 {
+                0x40
+            } else {
+                pending.trailing_zeros()
+            }
+        };
+
+        self.set_word(
+            C1VEC,
+            interrupt_code | fifo_code(tx_pending) << 16 | fifo_code(rx_pending) << 24,
+        );
+    }
+
+    /// Updates the ready flags of the oscillator register
+    fn update_oscillator(&mut self) {
+        let control = self.byte(OSC);
+
+        // PLLRDY, OSCRDY and SCLKRDY
+        let pll_ready = control & 1;
+        let clock_ready = ((control & (1 << 2) == 0) as u8) << 2;
+        let sclk_ready = control & (1 << 4);
+
+        self.set_byte(OSC + 1, pll_ready | clock_ready | sclk_ready);
+    }
+
+    /// Updates the pin levels of the IOCON register (GPIO0 and GPIO1)
+    fn update_gpio(&mut self) {
+        // PMx and not TRISx
+        let outputs = self.byte(IOCON + 3) & !self.byte(IOCON) & 0b11;
+        let mut levels = (self.byte(IOCON + 1) & outputs) | (self.gpio_inputs & !outputs);
+
+        // XSTBYEN: GPIO0 is driven high in sleep mode
+        if self.byte(IOCON) & (1 << 6) != 0 {
+            let standby = (self.mode() == OperationMode::Sleep) as u8;
+            levels = (levels & !1) | standby;
+        }
+
+        self.set_byte(IOCON + 2, levels);
+    }
+}
+
+/// Returns the address of the control register of TXQ (index 0) and FIFO 1 - 31
+fn control_register(index: u8) -> u16 {
+    0x050 + 12 * index as u16
+}
+
+/// Decoded SPI instruction
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Instruction {
+    Reset,
+    Write,
+    Read,
+    WriteCrc,
+    ReadCrc,
+    WriteSafe,
+    Invalid,
+}
+
+/// State of a single SPI transaction, which executes one instruction
+struct Transfer {
+    /// Command bytes (instruction, address and optional length)
+    command: [u8; 3],
+
+    /// Number of bytes exchanged
+    position: usize,
+
+    /// Decoded instruction
+    instruction: Instruction,
+
+    /// Start address
+    address: u16,
+
+    /// Number of data bytes of CRC instructions
+    length: usize,
+
+    /// CRC calculated over command and data
+    crc: u16,
+
+    /// CRC received by CRC write instructions
+    received_crc: u16,
+
+    /// Bytes written, applied at the end of the transaction
+    writes: Vec<(u16, u8)>,
+}
+
+impl Transfer {
+    fn new() -> Self {
+        Self {
+            command: [0u8; 3],
+            position: 0,
+            instruction: Instruction::Invalid,
+            address: 0,
+            length: 0,
+            crc: CRC16_INIT,
+            received_crc: 0,
+            writes: Vec::new(),
+        }
+    }
+
+    /// Exchanges a single byte, returns the byte sent by the device
+    fn exchange(&mut self, state: &State, input: u8) -> u8 {
+        let position = self.position;
+        self.position += 1;
+
+        if position < 2 {
+            self.command[position] = input;
+            if position == 1 {
+                self.decode();
+            }
+            return 0;
+        }
+
+        match self.instruction {
+            Instruction::Read => state.byte(self.address + (position - 2) as u16),
+            Instruction::Write => {
+                self.writes.push((self.address + (position - 2) as u16, input));
+                0
+            }
+            Instruction::ReadCrc | Instruction::WriteCrc if position == 2 => {
+                self.command[2] = input;
+                self.crc = crc16(CRC16_INIT, &self.command);
+
+                // Length is given in words for RAM access
+                self.length = match self.address {
+                    0x400..=0xBFF => input as usize * 4,
+                    _ => input as usize,
+                };
+                0
+            }
+            Instruction::ReadCrc => {
+                let index = position - 3;
+
+                if index < self.length {
+                    let data = state.byte(self.address + index as u16);
+                    self.crc = crc16(self.crc, &[data]);
+                    data
+                } else if index == self.length {
+                    (self.crc >> 8) as u8
+                } else if index == self.length + 1 {
+                    self.crc as u8
+                } else {
+                    0
+                }
+            }
+            Instruction::WriteCrc => {
+                self.receive_crc_data(position - 3, input);
+                0
+            }
+            Instruction::WriteSafe => {
+                if position == 2 {
+                    self.crc = crc16(CRC16_INIT, &self.command[..2]);
+                    self.length = 1;
+                }
+
+                self.receive_crc_data(position - 2, input);
+                0
+            }
+            Instruction::Reset | Instruction::Invalid => 0,
+        }
+    }
+
+    /// Receives a data or CRC byte of a CRC write instruction
+    fn receive_crc_data(&mut self, index: usize, input: u8) {
+        if index < self.length {
+            self.writes.push((self.address + index as u16, input));
+            self.crc = crc16(self.crc, &[input]);
+        } else if index < self.length + 2 {
+            self.received_crc = (self.received_crc << 8) | input as u16;
+        }
+    }
+
+    /// Decodes instruction and address
+    fn decode(&mut self) {
+        self.address = (((self.command[0] & 0x0F) as u16) << 8) | self.command[1] as u16;
+        self.instruction = match self.command[0] >> 4 {
+            0b0000 if self.address == 0 => Instruction::Reset,
+            0b0010 => Instruction::Write,
+            0b0011 => Instruction::Read,
+            0b1010 => Instruction::WriteCrc,
+            0b1011 => Instruction::ReadCrc,
+            0b1100 => Instruction::WriteSafe,
+            _ => Instruction::Invalid,
+        };
+    }
+
+    /// Completes the instruction once chip select is deasserted
+    fn finish(self, state: &mut State) {
+        let complete = match self.instruction {
+            Instruction::WriteCrc => self.position == 3 + self.length + 2,
+            Instruction::WriteSafe => self.position == 5,
+            _ => true,
+        };
+        let crc_valid = complete && self.crc == self.received_crc;
+
+        match self.instruction {
+            Instruction::Reset => state.reset(),
+            Instruction::Write => self.apply(state),
+            // Data is written as received, CRC is checked afterward
+            Instruction::WriteCrc => self.apply(state),
+            // Data is only written if CRC matches
+            Instruction::WriteSafe if crc_valid => self.apply(state),
+            _ => {}
+        }
+
+        if matches!(self.instruction, Instruction::WriteCrc | Instruction::WriteSafe) {
+            if !complete {
+                // FERRIF
+                state.set_byte(CRC + 2, state.byte(CRC + 2) | 0b10);
+            } else if !crc_valid {
+                // CRCERRIF and CRC value
+                state.set_byte(CRC + 2, state.byte(CRC + 2) | 0b01);
+                state.set_byte(CRC, self.received_crc as u8);
+                state.set_byte(CRC + 1, (self.received_crc >> 8) as u8);
+            }
+        }
+    }
+
+    /// Applies the received writes
+    fn apply(&self, state: &mut State) {
+        for (address, value) in &self.writes {
+            state.write(*address, *value);
+        }
+    }
+}
+
+/// Simulated MCP2517FD controller, shared by the SPI device and the test
+#[derive(Clone)]
+pub struct Simulator {
+    state: Rc<RefCell<State>>,
+
+    /// Virtual bus the controller is connected to
+    bus: Option<Rc<RefCell<bus::BusState>>>,
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Simulator {
+    /// Creates a controller in reset state (configuration mode)
+    pub fn new() -> Self {
+        Self {
+            state: Rc::new(RefCell::new(State::new())),
+            bus: None,
+        }
+    }
+
+    /// Returns a SPI device connected to this controller, which is passed to [MCP2517](crate::can::MCP2517)
+    pub fn device(&self) -> SimulatedDevice {
+        SimulatedDevice {
+            state: self.state.clone(),
+            bus: self.bus.clone(),
+        }
+    }
+
+    /// Receives the given frame from the bus.
+    /// Returns true if the frame was accepted by a filter, even if the target FIFO is full.
+    /// Remote frames accepted by a transmit FIFO with auto RTR enabled trigger the transmission of the loaded message.
+    /// A controller in sleep mode is woken up by the frame, which is lost.
+    pub fn receive(&self, frame: &SimFrame) -> bool {
+        let mut state = self.state.borrow_mut();
+        state.detect_bus_activity();
+
+        let accepted = state.receive(frame);
+
+        // Auto RTR responses
+        state.process_transmissions();
+
+        accepted
+    }
+
+    /// Returns and removes all frames transmitted to the bus
+    pub fn take_transmitted(&self) -> Vec<SimFrame> {
+        core::mem::take(&mut self.state.borrow_mut().transmitted)
+    }
+
+    /// Returns the current operation mode
+    pub fn operation_mode(&self) -> OperationMode {
+        self.state.borrow().mode()
+    }
+
+    /// Returns the 4-byte register at the given address
+    pub fn read_register(&self, address: u16) -> u32 {
+        self.state.borrow().word(address)
+    }
+
+    /// Sets the simulated controller variant, which defaults to MCP2517FD. The controller is reset.
+    pub fn set_variant(&self, variant: ChipVariant) {
+        let mut state = self.state.borrow_mut();
+        state.variant = variant;
+        state.reset();
+    }
+
+    /// Applies the given external level to a GPIO pin (true = high), which is read if the pin is not an output
+    pub fn set_gpio_input(&self, pin: GpioPin, level: bool) {
+        let mut state = self.state.borrow_mut();
+        let mask = 1 << pin as u8;
+
+        state.gpio_inputs = match level {
+            true => state.gpio_inputs | mask,
+            false => state.gpio_inputs & !mask,
+        };
+        state.update_gpio();
+    }
+
+    /// Increments the time base counter by the given number of ticks, if enabled (TBCEN)
+    pub fn advance_time_base(&self, ticks: u32) {
+        let mut state = self.state.borrow_mut();
+
+        if state.word(C1TSCON) & (1 << 16) != 0 {
+            let value = state.word(C1TBC).wrapping_add(ticks);
+            state.set_word(C1TBC, value);
+        }
+    }
+}
+
+/// SPI device of a [Simulator]
+pub struct SimulatedDevice {
+    state: Rc<RefCell<State>>,
+    bus: Option<Rc<RefCell<bus::BusState>>>,
+}
+
+impl Debug for SimulatedDevice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SimulatedDevice").finish_non_exhaustive()
+    }
+}
+
+/// Devices are equal if connected to the same controller
+impl PartialEq for SimulatedDevice {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+impl ErrorType for SimulatedDevice {
+    type Error = Infallible;
+}
+
+impl SpiDevice<u8> for SimulatedDevice {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        let mut state = self.state.borrow_mut();
+        let mut transfer = Transfer::new();
+
+        for operation in operations {
+            match operation {
+                Operation::Write(data) => {
+                    for byte in data.iter() {
+                        transfer.exchange(&state, *byte);
+                    }
+                }
+                Operation::Read(buffer) => {
+                    for byte in buffer.iter_mut() {
+                        *byte = transfer.exchange(&state, 0);
+                    }
+                }
+                Operation::Transfer(read, write) => {
+                    for index in 0..read.len().max(write.len()) {
+                        let output = transfer.exchange(&state, write.get(index).copied().unwrap_or(0));
+                        if let Some(byte) = read.get_mut(index) {
+                            *byte = output;
+                        }
+                    }
+                }
+                Operation::TransferInPlace(buffer) => {
+                    for byte in buffer.iter_mut() {
+                        *byte = transfer.exchange(&state, *byte);
+                    }
+                }
+                Operation::DelayNs(_) => {}
+            }
+        }
+
+        transfer.finish(&mut state);
+        state.update();
+        state.process_transmissions();
+        drop(state);
+
+        if let Some(bus) = &self.bus {
+            bus.borrow_mut().run();
+        }
+
+        Ok(())
+    }
+}
+
+/// Clock for simulated controllers, advancing by one microsecond on each read
+#[derive(Debug, Default)]
+pub struct SimulatedClock {
+    now: Cell<u64>,
+}
+
+impl SimulatedClock {
+    /// Advances the clock by the given number of microseconds
+    pub fn advance(&self, micros: u64) {
+        self.now.set(self.now.get() + micros);
+    }
+}
+
+impl Clock for SimulatedClock {
+    type T = u64;
+    const SCALING_FACTOR: Fraction = Fraction::new(1, 1_000_000);
+
+    fn try_now(&self) -> Result<Instant<Self>, Error> {
+        self.advance(1);
+
+        Ok(Instant::new(self.now.get()))
+    }
+
+    fn new_timer<Dur: Duration + FixedPoint>(&self, duration: Dur) -> Timer<'_, OneShot, Armed, Self, Dur> {
+        Timer::new(self, duration)
+    }
+}
@@ -0,0 +1,598 @@
+//! # Virtual CAN bus
+//!
+//! [VirtualBus] connects multiple simulated controllers, so that several [MCP2517](crate::can::MCP2517)
+//! instances communicate with each other. Pending transmissions are processed after each SPI transaction of any
+//! connected controller. The bus implements:
+//! * Arbitration by the arbitration field (identifier, RTR/SRR and IDE), losing FIFOs report TXLARB.
+//!   Collisions of identical arbitration fields are resolved in order of attachment.
+//! * Acknowledgement by at least one other node in normal mode
+//! * Error frames, either injected by [VirtualBus::inject_error] or caused by mismatching bit rates or frame formats
+//! * Error counters, fault confinement (CiTREC) and bus diagnostics (CiBDIAG0 and CiBDIAG1)
+//! * Retransmission as configured by RTXAT and the TXAT of the FIFO
+//!
+//! The bit rates of each node are derived from its SYSCLK and bit timing registers. Nodes deviating more than 1%
+//! from the bit rates of the bus are unable to communicate. Bit stuffing is not modeled. Bus-off nodes recover
+//! on the next bus activity, as the recovery sequence is not simulated in real time.
+//!
+//!```
+//!# use mcp2517::can::{CanController, MCP2517};
+//!# use mcp2517::config::{Configuration, SysClk};
+//!# use mcp2517::message::{Can20, TxMessage};
+//!# use mcp2517::simulator::bus::VirtualBus;
+//!# use mcp2517::simulator::SimulatedClock;
+//!# use embedded_can::{Id, StandardId};
+//!#
+//! // Default configuration uses 250 kbit/s nominal and 1 Mbit/s data bit rate at 20 MHz SYSCLK
+//! let bus = VirtualBus::new(250_000, 1_000_000);
+//! let clock = SimulatedClock::default();
+//!
+//! let mut node_a = MCP2517::new(bus.attach(SysClk::MHz20.frequency()).device());
+//! let mut node_b = MCP2517::new(bus.attach(SysClk::MHz20.frequency()).device());
+//! node_a.configure(&Configuration::default(), &clock).unwrap();
+//! node_b.configure(&Configuration::default(), &clock).unwrap();
+//!
+//! let id = Id::Standard(StandardId::new(0x55).unwrap());
+//! let message = TxMessage::new(Can20::<8> {}, &[1, 2, 3], id).unwrap();
+//! node_a.transmit(&message, true).unwrap();
+//!
+//! let received = node_b.receive::<8>(false).unwrap();
+//! assert_eq!(id, received.id());
+//! assert_eq!([1, 2, 3], received.get_payload());
+//!```
+use super::{control_register, SimFrame, Simulator, State, C1CON, C1INT, C1TREC};
+use crate::status::OperationMode;
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use embedded_can::Id;
+use embedded_time::duration::Nanoseconds;
+
+const C1NBTCFG: u16 = 0x004;
+const C1DBTCFG: u16 = 0x008;
+const C1BDIAG0: u16 = 0x038;
+const C1BDIAG1: u16 = 0x03C;
+
+/// Error frame on the virtual bus
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BusError {
+    /// Transmitted bit differs from the bus level, detected by the transmitter
+    Bit,
+    /// More than five consecutive bits of the same level, detected by the receivers
+    Stuff,
+    /// Fixed-form bit field contains an illegal bit, detected by the receivers
+    Form,
+    /// Received CRC does not match the calculated CRC, detected by the receivers
+    Crc,
+    /// Frame was not acknowledged, detected by the transmitter
+    Ack,
+}
+
+impl BusError {
+    /// Returns the bit of the error flag in CiBDIAG1
+    fn diagnostic_flag(self, data_phase: bool) -> u32 {
+        match (self, data_phase) {
+            (BusError::Bit, false) => 17,
+            (BusError::Ack, _) => 18,
+            (BusError::Form, false) => 19,
+            (BusError::Stuff, false) => 20,
+            (BusError::Crc, false) => 21,
+            (BusError::Bit, true) => 25,
+            (BusError::Form, true) => 27,
+            (BusError::Stuff, true) => 28,
+            (BusError::Crc, true) => 29,
+        }
+    }
+}
+
+/// Error of a single transmission attempt
+#[derive(Copy, Clone, Debug)]
+struct TransferError {
+    error: BusError,
+    /// Error occurred during the data phase of a bit rate switched frame
+    data_phase: bool,
+}
+
+impl TransferError {
+    fn nominal(error: BusError) -> Self {
+        Self {
+            error,
+            data_phase: false,
+        }
+    }
+
+    fn data(error: BusError) -> Self {
+        Self {
+            error,
+            data_phase: true,
+        }
+    }
+}
+
+/// Controller connected to the bus
+struct Node {
+    state: Rc<RefCell<State>>,
+
+    /// SYSCLK frequency in Hz
+    sys_clk: u32,
+
+    /// Node entered bus-off state and recovers on the next bus activity
+    recovering: bool,
+}
+
+impl Node {
+    /// Returns the nominal bit rate derived from CiNBTCFG
+    fn nominal_bit_rate(&self, state: &State) -> u32 {
+        let register = state.word(C1NBTCFG);
+        let brp = (register >> 24) + 1;
+        let time_quanta = ((register >> 16) & 0xFF) + ((register >> 8) & 0x7F) + 3;
+
+        self.sys_clk / (brp * time_quanta)
+    }
+
+    /// Returns the data bit rate derived from CiDBTCFG
+    fn data_bit_rate(&self, state: &State) -> u32 {
+        let register = state.word(C1DBTCFG);
+        let brp = (register >> 24) + 1;
+        let time_quanta = ((register >> 16) & 0x1F) + ((register >> 8) & 0x0F) + 3;
+
+        self.sys_clk / (brp * time_quanta)
+    }
+}
+
+/// Shared state of the virtual bus
+pub(super) struct BusState {
+    /// Nominal bit rate in bits per second
+    nominal_bit_rate: u32,
+
+    /// Data bit rate of bit rate switched CAN FD frames in bits per second
+    data_bit_rate: u32,
+
+    /// Connected controllers in order of attachment
+    nodes: Vec<Node>,
+
+    /// Errors destroying the next frames
+    injected: VecDeque<BusError>,
+
+    /// Frames transmitted successfully
+    frames: Vec<SimFrame>,
+
+    /// Bus time spent in nanoseconds
+    elapsed: u64,
+
+    /// Pending transmissions are not processed
+    suspended: bool,
+}
+
+impl BusState {
+    /// Processes all pending transmissions until the bus is idle.
+    /// FIFOs with unlimited retransmissions are retried once per run after a failed attempt.
+    pub(super) fn run(&mut self) {
+        if self.suspended {
+            return;
+        }
+
+        self.recover_bus_off();
+
+        let mut deferred = vec![false; self.nodes.len()];
+
+        loop {
+            let candidates: Vec<(usize, u8, SimFrame)> = self
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !deferred[*index])
+                .filter_map(|(index, node)| {
+                    let state = node.state.borrow();
+                    if !is_normal_mode(state.mode()) || is_bus_off(&state) {
+                        return None;
+                    }
+
+                    let fifo = state.next_transmission()?;
+                    Some((index, fifo, transmitted_frame(&state, fifo)))
+                })
+                .collect();
+
+            let Some((transmitter, fifo, frame)) = candidates
+                .iter()
+                .min_by_key(|(index, _, frame)| (arbitration_field(frame), *index))
+                .cloned()
+            else {
+                break;
+            };
+
+            // TXLARB
+            for (index, fifo, _) in candidates.iter().filter(|(index, ..)| *index != transmitter) {
+                let mut state = self.nodes[*index].state.borrow_mut();
+                let status = control_register(*fifo) + 4;
+                state.set_flags(status, 1 << 6);
+            }
+
+            self.elapsed += self.frame_time(&frame);
+
+            for node in &self.nodes {
+                node.state.borrow_mut().detect_bus_activity();
+            }
+
+            match self.transfer_error(transmitter, &frame) {
+                None => self.complete(transmitter, fifo, frame),
+                Some(error) => {
+                    if !self.fail(transmitter, fifo, error) {
+                        deferred[transmitter] = true;
+                    }
+                }
+            }
+        }
+
+        for node in &self.nodes {
+            node.state.borrow_mut().update();
+        }
+    }
+
+    /// Returns the error of the transmission attempt, None if the frame is transmitted successfully
+    fn transfer_error(&mut self, transmitter: usize, frame: &SimFrame) -> Option<TransferError> {
+        if let Some(error) = self.injected.pop_front() {
+            return Some(TransferError::nominal(error));
+        }
+
+        let node = &self.nodes[transmitter];
+        if !self.nominal_matches(node) {
+            return Some(TransferError::nominal(BusError::Bit));
+        }
+
+        if frame.bit_rate_switch && !self.data_matches(node) {
+            return Some(TransferError::data(BusError::Bit));
+        }
+
+        let mut acknowledged = false;
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            let state = node.state.borrow();
+            if index == transmitter || !is_receiving_mode(state.mode()) || is_bus_off(&state) {
+                continue;
+            }
+
+            // CAN 2.0 nodes are unable to decode CAN FD frames
+            let can20_node = state.mode() == OperationMode::NormalCAN2_0;
+
+            let error = if !self.nominal_matches(node) || (frame.fd && can20_node) {
+                Some(TransferError::nominal(BusError::Form))
+            } else if frame.bit_rate_switch && !self.data_matches(node) {
+                Some(TransferError::data(BusError::Crc))
+            } else {
+                None
+            };
+
+            let listen_only = state.mode() == OperationMode::ListenOnly;
+            match error {
+                // Error flags of error passive nodes do not destroy the frame
+                Some(error) if !listen_only && !is_error_passive(&state) => return Some(error),
+                Some(_) => {}
+                None => acknowledged |= !listen_only,
+            }
+        }
+
+        if !acknowledged {
+            return Some(TransferError::nominal(BusError::Ack));
+        }
+
+        None
+    }
+
+    /// Removes the transmitted message from the FIFO and stores the frame in all receiving nodes
+    fn complete(&mut self, transmitter: usize, fifo: u8, frame: SimFrame) {
+        for (index, node) in self.nodes.iter().enumerate() {
+            let compatible = self.nominal_matches(node) && (!frame.bit_rate_switch || self.data_matches(node));
+            let mut state = node.state.borrow_mut();
+
+            if index == transmitter {
+                state.complete_transmission(fifo);
+                let (tec, rec) = error_counters(&state);
+                set_error_counters(&mut state, tec.saturating_sub(1), rec);
+            } else if compatible && is_receiving_mode(state.mode()) && !is_bus_off(&state) {
+                state.receive(&frame);
+                let (tec, rec) = error_counters(&state);
+                set_error_counters(&mut state, tec, rec.saturating_sub(1));
+            } else {
+                continue;
+            }
+
+            // EFMSGCNT
+            let error_free = state.word(C1BDIAG1);
+            state.set_word(
+                C1BDIAG1,
+                (error_free & 0xFFFF_0000) | (error_free as u16).saturating_add(1) as u32,
+            );
+        }
+
+        self.frames.push(frame);
+    }
+
+    /// Updates the error state of all nodes after a failed transmission attempt.
+    /// Returns true if the transmitter is retried immediately.
+    fn fail(&mut self, transmitter: usize, fifo: u8, error: TransferError) -> bool {
+        for (index, node) in self.nodes.iter_mut().enumerate() {
+            let mut state = node.state.borrow_mut();
+            if index != transmitter && (!is_receiving_mode(state.mode()) || is_bus_off(&state)) {
+                continue;
+            }
+
+            let (tec, rec) = error_counters(&state);
+            let (tec, rec) = if index == transmitter {
+                // TEC is not incremented on ACK errors in error passive state
+                let passive_ack = error.error == BusError::Ack && tec >= 128;
+                (if passive_ack { tec } else { tec + 8 }, rec)
+            } else if state.mode() == OperationMode::ListenOnly {
+                (tec, rec)
+            } else {
+                (tec, (rec + 1).min(255))
+            };
+            set_error_counters(&mut state, tec, rec);
+            node.recovering |= is_bus_off(&state);
+
+            // Error counters of nominal and data phase in CiBDIAG0: NRERRCNT, NTERRCNT, DRERRCNT and DTERRCNT
+            let counter = match (index == transmitter, error.data_phase) {
+                (false, false) => 0,
+                (true, false) => 8,
+                (false, true) => 16,
+                (true, true) => 24,
+            };
+            let diagnostics = state.word(C1BDIAG0);
+            let count = ((diagnostics >> counter) as u8).saturating_add(1);
+            state.set_word(C1BDIAG0, (diagnostics & !(0xFF << counter)) | (count as u32) << counter);
+
+            // Error flag and reset of EFMSGCNT
+            let flags = state.word(C1BDIAG1) & 0xFFFF_0000;
+            state.set_word(C1BDIAG1, flags | 1 << error.error.diagnostic_flag(error.data_phase));
+
+            // CERRIF
+            state.set_flags(C1INT + 1, 1 << 5);
+        }
+
+        let mut state = self.nodes[transmitter].state.borrow_mut();
+
+        // TXERR
+        let status = control_register(fifo) + 4;
+        state.set_flags(status, 1 << 5);
+
+        let fifo_state = &mut state.fifos[fifo as usize];
+        fifo_state.failed_attempts = fifo_state.failed_attempts.saturating_add(1);
+        let failed_attempts = fifo_state.failed_attempts;
+
+        let Some(attempts) = attempts(&state, fifo) else {
+            return false;
+        };
+
+        if failed_attempts >= attempts {
+            // TXATIF, transmission request is cleared
+            state.set_flags(status, 1 << 4);
+            let control = control_register(fifo) + 1;
+            let value = state.byte(control) & !0b10;
+            state.set_byte(control, value);
+        }
+
+        true
+    }
+
+    /// Restores the error active state of nodes which entered bus-off state during previous activity
+    fn recover_bus_off(&mut self) {
+        for node in self.nodes.iter_mut().filter(|node| node.recovering) {
+            let mut state = node.state.borrow_mut();
+            set_error_counters(&mut state, 0, 0);
+            node.recovering = false;
+
+            // 128 occurrences of 11 consecutive recessive bits
+            self.elapsed += 128 * 11 * 1_000_000_000 / self.nominal_bit_rate as u64;
+        }
+    }
+
+    /// Returns true if the nominal bit rate of the node deviates at most 1% from the bus
+    fn nominal_matches(&self, node: &Node) -> bool {
+        rate_matches(node.nominal_bit_rate(&node.state.borrow()), self.nominal_bit_rate)
+    }
+
+    /// Returns true if the data bit rate of the node deviates at most 1% from the bus
+    fn data_matches(&self, node: &Node) -> bool {
+        rate_matches(node.data_bit_rate(&node.state.borrow()), self.data_bit_rate)
+    }
+
+    /// Returns the transmission time of the frame in nanoseconds, bit stuffing is not considered
+    fn frame_time(&self, frame: &SimFrame) -> u64 {
+        // SOF, identifier and RTR/RRS
+        let arbitration = match frame.id {
+            Id::Standard(_) => 13,
+            Id::Extended(_) => 33,
+        };
+
+        // IDE, FDF, res, BRS, ESI and DLC (IDE, r0 and DLC for CAN 2.0)
+        let control = if frame.fd { 9 } else { 6 };
+
+        // CRC with stuff count and delimiter
+        let crc = match (frame.fd, frame.data.len()) {
+            (false, _) => 16,
+            (true, 0..=16) => 22,
+            (true, _) => 26,
+        };
+
+        // ACK, EOF and intermission
+        let end = 12;
+
+        let data = if frame.remote { 0 } else { frame.data.len() as u64 * 8 };
+
+        let (nominal_bits, data_bits) = if frame.bit_rate_switch {
+            // Data phase starts after BRS and ends with the CRC delimiter
+            (arbitration + 4 + end, 5 + data + crc)
+        } else {
+            (arbitration + control + data + crc + end, 0)
+        };
+
+        nominal_bits * 1_000_000_000 / self.nominal_bit_rate as u64
+            + data_bits * 1_000_000_000 / self.data_bit_rate as u64
+    }
+}
+
+/// Returns the frame transmitted by the node, frames of CAN 2.0 mode are sent in CAN 2.0 format
+fn transmitted_frame(state: &State, fifo: u8) -> SimFrame {
+    let mut frame = state.pending_frame(fifo);
+
+    if state.mode() == OperationMode::NormalCAN2_0 {
+        frame.fd = false;
+        frame.bit_rate_switch = false;
+        frame.data.truncate(8);
+    }
+
+    frame
+}
+
+/// Returns the arbitration field of the frame, lower values win the arbitration
+fn arbitration_field(frame: &SimFrame) -> u64 {
+    // RTR is replaced by dominant RRS bit in CAN FD frames
+    let remote = (frame.remote && !frame.fd) as u64;
+
+    match frame.id {
+        // SID, RTR and dominant IDE
+        Id::Standard(id) => (id.as_raw() as u64) << 21 | remote << 20,
+        // SID, recessive SRR and IDE, EID and RTR
+        Id::Extended(id) => {
+            let raw = id.as_raw() as u64;
+            (raw >> 18) << 21 | 0b11 << 19 | (raw & 0x3FFFF) << 1 | remote
+        }
+    }
+}
+
+/// Returns the maximum number of transmission attempts of the FIFO, None if unlimited
+fn attempts(state: &State, fifo: u8) -> Option<u8> {
+    // RTXAT
+    if state.byte(C1CON + 2) & 1 == 0 {
+        return None;
+    }
+
+    // TXAT
+    match (state.byte(control_register(fifo) + 2) >> 5) & 0b11 {
+        0b00 => Some(1),
+        0b01 => Some(3),
+        _ => None,
+    }
+}
+
+/// Returns true if the bit rate deviates at most 1% from the expected bit rate
+fn rate_matches(rate: u32, expected: u32) -> bool {
+    rate.abs_diff(expected) as u64 * 100 <= expected as u64
+}
+
+fn is_normal_mode(mode: OperationMode) -> bool {
+    matches!(mode, OperationMode::NormalCANFD | OperationMode::NormalCAN2_0)
+}
+
+fn is_receiving_mode(mode: OperationMode) -> bool {
+    is_normal_mode(mode) || mode == OperationMode::ListenOnly
+}
+
+/// Returns TEC and REC of CiTREC
+fn error_counters(state: &State) -> (u16, u16) {
+    (state.byte(C1TREC + 1) as u16, state.byte(C1TREC) as u16)
+}
+
+/// Returns true if TXBO of CiTREC is set
+fn is_bus_off(state: &State) -> bool {
+    state.byte(C1TREC + 2) & (1 << 5) != 0
+}
+
+/// Returns true if TXBP or RXBP of CiTREC is set
+fn is_error_passive(state: &State) -> bool {
+    state.byte(C1TREC + 2) & 0b11000 != 0
+}
+
+/// Updates the error counters and the derived fault confinement flags of CiTREC
+fn set_error_counters(state: &mut State, tec: u16, rec: u16) {
+    let bus_off = tec > 255;
+    let tec = tec.min(255);
+
+    let flags = (tec >= 96 || rec >= 96) as u32
+        | ((rec >= 96) as u32) << 1
+        | ((tec >= 96) as u32) << 2
+        | ((rec >= 128) as u32) << 3
+        | ((tec >= 128) as u32) << 4
+        | (bus_off as u32) << 5;
+    state.set_word(C1TREC, flags << 16 | (tec as u32) << 8 | rec as u32);
+
+    // TXBOERR
+    if bus_off {
+        state.set_flags(C1BDIAG1 + 2, 1 << 7);
+    }
+}
+
+/// Virtual CAN bus connecting simulated controllers
+#[derive(Clone)]
+pub struct VirtualBus {
+    state: Rc<RefCell<BusState>>,
+}
+
+impl VirtualBus {
+    /// Creates a bus with the given nominal and data bit rates in bits per second
+    pub fn new(nominal_bit_rate: u32, data_bit_rate: u32) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(BusState {
+                nominal_bit_rate,
+                data_bit_rate,
+                nodes: Vec::new(),
+                injected: VecDeque::new(),
+                frames: Vec::new(),
+                elapsed: 0,
+                suspended: false,
+            })),
+        }
+    }
+
+    /// Connects a new controller in reset state, operating at the given SYSCLK frequency in Hz
+    pub fn attach(&self, sys_clk: u32) -> Simulator {
+        let mut state = State::new();
+        state.bus_node = true;
+        let state = Rc::new(RefCell::new(state));
+
+        self.state.borrow_mut().nodes.push(Node {
+            state: state.clone(),
+            sys_clk,
+            recovering: false,
+        });
+
+        Simulator {
+            state,
+            bus: Some(self.state.clone()),
+        }
+    }
+
+    /// Destroys the next transmission attempt by an error frame of the given type.
+    /// Multiple errors are applied to consecutive attempts.
+    pub fn inject_error(&self, error: BusError) {
+        self.state.borrow_mut().injected.push_back(error);
+    }
+
+    /// Stops processing pending transmissions, e.g. to let multiple nodes compete in arbitration
+    pub fn suspend(&self) {
+        self.state.borrow_mut().suspended = true;
+    }
+
+    /// Resumes processing of pending transmissions
+    pub fn resume(&self) {
+        let mut state = self.state.borrow_mut();
+        state.suspended = false;
+        state.run();
+    }
+
+    /// Processes pending transmissions, which is done automatically after each SPI transaction
+    pub fn run(&self) {
+        self.state.borrow_mut().run();
+    }
+
+    /// Returns and removes all frames transmitted successfully
+    pub fn take_frames(&self) -> Vec<SimFrame> {
+        core::mem::take(&mut self.state.borrow_mut().frames)
+    }
+
+    /// Returns the bus time spent for all transmission attempts
+    pub fn elapsed(&self) -> Nanoseconds<u64> {
+        Nanoseconds::new(self.state.borrow().elapsed)
+    }
+}
@@ -0,0 +1,364 @@
+//! # SocketCAN bridge
+//! Bridges a [MCP2517] to a Linux SocketCAN interface (`socketcan` feature, requires `std`), so that tools like
+//! `candump` and `cansend` can be used against the driver on a plain Linux host, e.g. by a virtual `vcan0`
+//! interface:
+//!```text
+//! sudo modprobe vcan
+//! sudo ip link add dev vcan0 type vcan
+//! sudo ip link set up vcan0
+//!```
+//!
+//! [SocketCan] is a non-blocking raw CAN socket with CAN FD frames enabled. [Bridge] forwards frames in both
+//! directions on each [poll](Bridge::poll):
+//! * Messages received by the controller are written to the socket
+//! * Frames read from the socket are transmitted by the controller, CAN FD frames are transmitted as [CanFd]
+//!   messages and classic frames as [Can20] messages
+//!
+//! The controller may be connected to real hardware or to a simulated device.
+//!
+//!```no_run
+//!# use mcp2517::can::MCP2517;
+//!# use mcp2517::config::Configuration;
+//!# use mcp2517::example::*;
+//! use mcp2517::socketcan::{Bridge, SocketCan};
+//!
+//!# let clock = ExampleClock::default();
+//!# let spi_dev = ExampleSPIDevice::default();
+//! let mut controller = MCP2517::new(spi_dev);
+//! controller.configure(&Configuration::default(), &clock).unwrap();
+//!
+//! // Payload buffer of received messages matches the RX FIFO payload size
+//! let socket = SocketCan::open("vcan0").unwrap();
+//! let mut bridge = Bridge::<_, _, 8>::new(controller, socket);
+//!
+//! loop {
+//!     bridge.poll().unwrap();
+//! }
+//!```
+use crate::can::{CanController, CanError, MCP2517};
+use crate::message::{Can20, CanFd, RxMessage, TxMessage, DLC, MAX_PAYLOAD_CAN_2_0};
+use core::mem;
+use embedded_can::{ExtendedId, Id, StandardId};
+use embedded_hal::spi::{ErrorType, SpiDevice};
+use embedded_time::Clock;
+use libc::{c_int, c_void, canfd_frame, sockaddr, sockaddr_can, socklen_t, CANFD_MTU, CAN_MTU};
+use std::ffi::CString;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+/// Frame read from the socket, converted to a transmit message
+#[derive(Clone, Debug)]
+pub enum SocketMessage {
+    /// Classic CAN frame, including remote frames
+    Can20(TxMessage<Can20<8>, 8>),
+    /// CAN FD frame
+    CanFd(TxMessage<CanFd<64>, 64>),
+}
+
+impl SocketMessage {
+    /// Converts the frame read from the socket, `fd` is true if a CAN FD frame was read
+    pub(crate) fn from_frame(frame: &canfd_frame, fd: bool) -> io::Result<Self> {
+        let id = if frame.can_id & libc::CAN_EFF_FLAG != 0 {
+            ExtendedId::new(frame.can_id & libc::CAN_EFF_MASK).map(Id::Extended)
+        } else {
+            StandardId::new((frame.can_id & libc::CAN_SFF_MASK) as u16).map(Id::Standard)
+        };
+        let id = id.ok_or_else(|| invalid_data("Invalid CAN identifier"))?;
+
+        let max_length = if fd { 64 } else { MAX_PAYLOAD_CAN_2_0 };
+        let length = (frame.len as usize).min(max_length);
+        let payload = &frame.data[..length];
+
+        if fd {
+            let message_type = CanFd::<64> {
+                bitrate_switch: frame.flags as c_int & libc::CANFD_BRS != 0,
+            };
+            let message = TxMessage::new(message_type, payload, id).map_err(|_| invalid_data("Invalid length"))?;
+
+            return Ok(SocketMessage::CanFd(message));
+        }
+
+        let message = if frame.can_id & libc::CAN_RTR_FLAG != 0 {
+            DLC::from_length(length).and_then(|dlc| TxMessage::new_remote(Can20::<8> {}, dlc, id))
+        } else {
+            TxMessage::new(Can20::<8> {}, payload, id)
+        };
+
+        Ok(SocketMessage::Can20(
+            message.map_err(|_| invalid_data("Invalid length"))?,
+        ))
+    }
+
+    /// Returns the identifier of the message
+    pub fn id(&self) -> Id {
+        match self {
+            SocketMessage::Can20(message) => message.get_id(),
+            SocketMessage::CanFd(message) => message.get_id(),
+        }
+    }
+
+    /// Returns the payload of the message
+    pub fn get_payload(&self) -> &[u8] {
+        match self {
+            SocketMessage::Can20(message) => message.get_payload(),
+            SocketMessage::CanFd(message) => message.get_payload(),
+        }
+    }
+}
+
+/// Converts the received message to a socket frame, returns the frame and its size (MTU)
+pub(crate) fn to_frame<const L: usize>(message: &RxMessage<L>) -> (canfd_frame, usize) {
+    // SAFETY: frame is a plain C struct, for which all zero bytes are valid
+    let mut frame: canfd_frame = unsafe { mem::zeroed() };
+    let fd = message.is_fd_frame();
+
+    frame.can_id = match message.id() {
+        Id::Standard(id) => id.as_raw() as u32,
+        Id::Extended(id) => id.as_raw() | libc::CAN_EFF_FLAG,
+    };
+
+    // Length is given by the DLC, payload exceeding the buffer size is padded with zeros
+    let length = match fd {
+        true => message.dlc().to_length(),
+        false => message.dlc().to_length().min(MAX_PAYLOAD_CAN_2_0),
+    };
+    frame.len = length as u8;
+
+    if fd {
+        frame.flags = libc::CANFD_FDF as u8;
+        if message.is_bit_rate_switched() {
+            frame.flags |= libc::CANFD_BRS as u8;
+        }
+        if message.is_error_passive() {
+            frame.flags |= libc::CANFD_ESI as u8;
+        }
+    } else if message.is_remote_frame() {
+        frame.can_id |= libc::CAN_RTR_FLAG;
+    }
+
+    let payload = message.get_payload();
+    let copied = payload.len().min(length);
+    frame.data[..copied].copy_from_slice(&payload[..copied]);
+
+    (frame, if fd { CANFD_MTU } else { CAN_MTU })
+}
+
+fn invalid_data(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Non-blocking raw CAN socket bound to a SocketCAN interface
+#[derive(Debug)]
+pub struct SocketCan {
+    socket: OwnedFd,
+}
+
+impl SocketCan {
+    /// Opens a raw CAN socket bound to the given interface (e.g. `vcan0`), CAN FD frames are enabled
+    pub fn open(interface: &str) -> io::Result<Self> {
+        let name = CString::new(interface).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+
+        // SAFETY: name is a valid null terminated string
+        let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if index == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: returned descriptor is checked and owned afterward
+        let descriptor = unsafe {
+            libc::socket(
+                libc::AF_CAN,
+                libc::SOCK_RAW | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+                libc::CAN_RAW,
+            )
+        };
+        if descriptor < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: descriptor is a valid socket, which is not owned elsewhere
+        let socket = unsafe { OwnedFd::from_raw_fd(descriptor) };
+
+        let enable: c_int = 1;
+        // SAFETY: option value points to a c_int of the given size
+        let result = unsafe {
+            libc::setsockopt(
+                descriptor,
+                libc::SOL_CAN_RAW,
+                libc::CAN_RAW_FD_FRAMES,
+                &enable as *const c_int as *const c_void,
+                mem::size_of::<c_int>() as socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: address is a plain C struct, for which all zero bytes are valid
+        let mut address: sockaddr_can = unsafe { mem::zeroed() };
+        address.can_family = libc::AF_CAN as libc::sa_family_t;
+        address.can_ifindex = index as c_int;
+
+        // SAFETY: address points to a sockaddr_can of the given size
+        let result = unsafe {
+            libc::bind(
+                descriptor,
+                &address as *const sockaddr_can as *const sockaddr,
+                mem::size_of::<sockaddr_can>() as socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { socket })
+    }
+
+    /// Reads the next frame of the interface, returns None if no frame is pending
+    pub fn receive(&self) -> io::Result<Option<SocketMessage>> {
+        // SAFETY: frame is a plain C struct, for which all zero bytes are valid
+        let mut frame: canfd_frame = unsafe { mem::zeroed() };
+
+        // SAFETY: buffer points to a canfd_frame of CANFD_MTU bytes
+        let size = unsafe {
+            libc::read(
+                self.socket.as_raw_fd(),
+                &mut frame as *mut canfd_frame as *mut c_void,
+                CANFD_MTU,
+            )
+        };
+
+        if size < 0 {
+            let error = io::Error::last_os_error();
+            if error.kind() == io::ErrorKind::WouldBlock {
+                return Ok(None);
+            }
+
+            return Err(error);
+        }
+
+        match size as usize {
+            CAN_MTU => SocketMessage::from_frame(&frame, false).map(Some),
+            CANFD_MTU => SocketMessage::from_frame(&frame, true).map(Some),
+            _ => Err(invalid_data("Incomplete CAN frame")),
+        }
+    }
+
+    /// Writes the message received by the controller to the interface
+    pub fn send<const L: usize>(&self, message: &RxMessage<L>) -> io::Result<()> {
+        let (frame, size) = to_frame(message);
+
+        // SAFETY: buffer points to a canfd_frame, of which the first size bytes are written
+        let written = unsafe {
+            libc::write(
+                self.socket.as_raw_fd(),
+                &frame as *const canfd_frame as *const c_void,
+                size,
+            )
+        };
+
+        if written < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        if written as usize != size {
+            return Err(io::Error::from(io::ErrorKind::WriteZero));
+        }
+
+        Ok(())
+    }
+}
+
+/// Possible errors while forwarding frames
+#[derive(Debug)]
+pub enum BridgeError<D: ErrorType> {
+    /// Error of the CAN controller
+    Can(CanError<D>),
+    /// Error of the SocketCAN interface
+    Io(io::Error),
+}
+
+impl<D: ErrorType> From<CanError<D>> for BridgeError<D> {
+    fn from(error: CanError<D>) -> Self {
+        BridgeError::Can(error)
+    }
+}
+
+impl<D: ErrorType> From<io::Error> for BridgeError<D> {
+    fn from(error: io::Error) -> Self {
+        BridgeError::Io(error)
+    }
+}
+
+/// Forwards frames between a [MCP2517] and a [SocketCan] interface,
+/// where `L` is the payload buffer size of received messages (must be a multiple of 4)
+pub struct Bridge<D: SpiDevice, CLK: Clock, const L: usize> {
+    controller: MCP2517<D, CLK>,
+    socket: SocketCan,
+
+    /// Frame read from the socket, which is transmitted once the TX FIFO is not full
+    pending: Option<SocketMessage>,
+}
+
+impl<D: SpiDevice, CLK: Clock, const L: usize> Bridge<D, CLK, L> {
+    /// Creates a new bridge of a configured controller
+    pub fn new(controller: MCP2517<D, CLK>, socket: SocketCan) -> Self {
+        Self {
+            controller,
+            socket,
+            pending: None,
+        }
+    }
+
+    /// Forwards all pending frames in both directions without blocking.
+    /// Returns the number of forwarded frames.
+    pub fn poll(&mut self) -> Result<usize, BridgeError<D>> {
+        let mut forwarded = 0;
+
+        loop {
+            match self.controller.receive::<L>(false) {
+                Ok(message) => self.socket.send(&message)?,
+                Err(CanError::RxFifoEmptyErr) => break,
+                Err(error) => return Err(error.into()),
+            }
+
+            forwarded += 1;
+        }
+
+        loop {
+            let message = match self.pending.take() {
+                Some(message) => message,
+                None => match self.socket.receive()? {
+                    Some(message) => message,
+                    None => break,
+                },
+            };
+
+            let result = match &message {
+                SocketMessage::Can20(message) => self.controller.transmit(message, false),
+                SocketMessage::CanFd(message) => self.controller.transmit(message, false),
+            };
+
+            match result {
+                Ok(_) => forwarded += 1,
+                Err(CanError::TxFifoFullErr) => {
+                    self.pending = Some(message);
+                    break;
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        Ok(forwarded)
+    }
+
+    /// Returns the controller, e.g. for reading the status
+    pub fn controller(&mut self) -> &mut MCP2517<D, CLK> {
+        &mut self.controller
+    }
+
+    /// Releases controller and socket
+    pub fn release(self) -> (MCP2517<D, CLK>, SocketCan) {
+        (self.controller, self.socket)
+    }
+}
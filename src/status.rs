@@ -1,4 +1,7 @@
 //! # Mapped status registers
+use crate::config::Fifo;
+use crate::registers::MAX_FIFO_INDEX;
+use embedded_can::ErrorKind;
 
 ///  Operation status read from C1CON register
 #[derive(Copy, Clone, Debug)]
@@ -100,3 +103,473 @@ impl OscillatorStatus {
         }
     }
 }
+
+/// Pin-compatible controller variant. The variants differ as follows:
+///
+/// * Low-power mode (LPMEN of the OSC register) is only supported by MCP2518FD and MCP251863.
+///   [Configuration](crate::config::Configuration) enabling it is rejected for the MCP2517FD.
+/// * Oscillator options (PLL, system clock and clock output divisors) are identical, so no variant
+///   specific clock handling is applied.
+/// * READ_CRC of the MCP2517FD may return a mismatching CRC if the data is updated by the controller
+///   during the read (errata), so reads failing the CRC check are repeated once. The SPI clock limit of the
+///   errata (at most 0.85 * SYSCLK / 2) applies to all variants and must be respected by the SPI configuration.
+/// * The MCP251863 integrates a transceiver, but is identical to the MCP2518FD on the SPI interface.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ChipVariant {
+    #[default]
+    MCP2517FD,
+    MCP2518FD,
+    /// MCP2518FD with integrated transceiver, which is identified as MCP2518FD by the DEVID register
+    MCP251863,
+}
+
+impl ChipVariant {
+    /// Returns true if low-power mode (LPM) is supported
+    pub fn supports_low_power_mode(self) -> bool {
+        self != Self::MCP2517FD
+    }
+
+    /// Returns the number of READ_CRC attempts until a CRC mismatch is reported
+    pub(crate) fn crc_read_attempts(self) -> usize {
+        match self {
+            Self::MCP2517FD => 2,
+            Self::MCP2518FD | Self::MCP251863 => 1,
+        }
+    }
+}
+
+/// Mapped DEVID register
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DeviceId {
+    /// Device variant, MCP251863 is reported as MCP2518FD
+    pub variant: ChipVariant,
+
+    /// Silicon revision
+    pub revision: u8,
+}
+
+impl DeviceId {
+    /// Maps register bits, returns None if the device ID is unknown
+    pub(crate) fn from_register(register: u8) -> Option<Self> {
+        let variant = match register >> 4 {
+            0b0000 => ChipVariant::MCP2517FD,
+            0b0001 => ChipVariant::MCP2518FD,
+            _ => return None,
+        };
+
+        Some(Self {
+            variant,
+            revision: register & 0x0F,
+        })
+    }
+}
+
+/// Cause of a pending interrupt
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Interrupt {
+    /// Interrupt of the given receive FIFO (e.g. FIFO not empty)
+    Receive(Fifo),
+    /// Interrupt of the given transmit FIFO (e.g. FIFO not full)
+    Transmit(Fifo),
+    /// Interrupt of the transmit queue (TXQ)
+    TransmitQueue,
+    /// Transmit event FIFO interrupt
+    TransmitEvent,
+    /// Time base counter overflow
+    TimeBaseOverflow,
+    /// Operation mode changed
+    ModeChange,
+    /// ECC error in message RAM
+    Ecc,
+    /// CRC mismatch or format error of SPI instruction
+    SpiCrc,
+    /// Retransmission attempts of a transmit FIFO exhausted
+    TransmitAttempt,
+    /// Receive FIFO overflow
+    ReceiveOverflow,
+    /// System error
+    SystemError,
+    /// CAN bus error
+    BusError,
+    /// Bus activity while in sleep mode
+    WakeUp,
+    /// Invalid message received or transmitted
+    InvalidMessage,
+}
+
+/// CiINT interrupt flags which are not related to a single FIFO, by bit position
+const INTERRUPT_FLAGS: [(u8, Interrupt); 11] = [
+    (2, Interrupt::TimeBaseOverflow),
+    (3, Interrupt::ModeChange),
+    (4, Interrupt::TransmitEvent),
+    (8, Interrupt::Ecc),
+    (9, Interrupt::SpiCrc),
+    (10, Interrupt::TransmitAttempt),
+    (11, Interrupt::ReceiveOverflow),
+    (12, Interrupt::SystemError),
+    (13, Interrupt::BusError),
+    (14, Interrupt::WakeUp),
+    (15, Interrupt::InvalidMessage),
+];
+
+/// Pending interrupts, mapped from CiVEC, CiINT, CiRXIF and CiTXIF registers
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Interrupts {
+    /// CiVEC register
+    vector: u32,
+    /// CiINT register
+    flags: u32,
+    /// CiRXIF register
+    rx_fifos: u32,
+    /// CiTXIF register
+    tx_fifos: u32,
+}
+
+impl Interrupts {
+    /// Maps the CiVEC, CiINT, CiRXIF and CiTXIF registers
+    pub(crate) fn from_registers(registers: [u32; 4]) -> Self {
+        Self {
+            vector: registers[0],
+            flags: registers[1],
+            rx_fifos: registers[2],
+            tx_fifos: registers[3],
+        }
+    }
+
+    /// Returns the pending interrupt with the highest priority (CiVEC interrupt code), or None if no interrupt is pending
+    pub fn highest(&self) -> Option<Interrupt> {
+        let code = (self.vector & 0x7F) as u8;
+
+        match code {
+            0 => Some(Interrupt::TransmitQueue),
+            1..=31 if self.tx_fifos & (1 << code) != 0 => Fifo::new(code).map(Interrupt::Transmit),
+            1..=31 => Fifo::new(code).map(Interrupt::Receive),
+            0x41 => Some(Interrupt::BusError),
+            0x42 => Some(Interrupt::WakeUp),
+            0x43 => Some(Interrupt::ReceiveOverflow),
+            0x44 | 0x45 => Some(Interrupt::SystemError),
+            0x46 => Some(Interrupt::TimeBaseOverflow),
+            0x47 => Some(Interrupt::ModeChange),
+            0x48 => Some(Interrupt::InvalidMessage),
+            0x49 => Some(Interrupt::TransmitEvent),
+            0x4A => Some(Interrupt::TransmitAttempt),
+            _ => None,
+        }
+    }
+
+    /// Iterates over all pending interrupts. FIFO interrupts are returned first.
+    pub fn iter(&self) -> impl Iterator<Item = Interrupt> + '_ {
+        let txq = (self.tx_fifos & 1 != 0).then_some(Interrupt::TransmitQueue);
+        let tx_fifos = Self::fifos(self.tx_fifos).map(Interrupt::Transmit);
+        let rx_fifos = Self::fifos(self.rx_fifos).map(Interrupt::Receive);
+        let flags = INTERRUPT_FLAGS
+            .iter()
+            .filter(|(bit, _)| self.flags & (1 << bit) != 0)
+            .map(|(_, interrupt)| *interrupt);
+
+        txq.into_iter().chain(tx_fifos).chain(rx_fifos).chain(flags)
+    }
+
+    /// Returns true if the given interrupt is pending
+    pub fn contains(&self, interrupt: Interrupt) -> bool {
+        self.iter().any(|pending| pending == interrupt)
+    }
+
+    /// Returns true if no interrupt is pending
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Returns the index of the filter that matched the most recently received message
+    pub fn filter_hit(&self) -> u8 {
+        ((self.vector >> 8) & 0x1F) as u8
+    }
+
+    /// Returns the FIFOs of the given FIFO interrupt register
+    fn fifos(register: u32) -> impl Iterator<Item = Fifo> {
+        (1..=MAX_FIFO_INDEX)
+            .filter(move |index| register & (1 << index) != 0)
+            .filter_map(Fifo::new)
+    }
+}
+
+/// Final status of a transmission
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TxStatus {
+    /// Message loaded, but transmission not awaited (non-blocking transmission)
+    Pending,
+    /// All messages of the FIFO transmitted successfully
+    Transmitted,
+    /// Transmission aborted (TXABT)
+    Aborted,
+    /// Retransmission attempts exhausted without successful transmission (TXATIF)
+    AttemptsExhausted,
+}
+
+/// Outcome of a transmission, mapped from the TX FIFO status flags observed while waiting for the transmission
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TxOutcome {
+    /// Final status of the transmission
+    pub status: TxStatus,
+
+    /// True if arbitration was lost at least once since the transmission was requested (TXLARB)
+    pub arbitration_lost: bool,
+
+    /// True if an error was detected during a transmission attempt (TXERR)
+    pub error_detected: bool,
+}
+
+impl TxOutcome {
+    /// Outcome of a message loaded without waiting for the transmission
+    pub(crate) const PENDING: TxOutcome = TxOutcome {
+        status: TxStatus::Pending,
+        arbitration_lost: false,
+        error_detected: false,
+    };
+
+    /// Returns true if all messages of the FIFO were transmitted successfully
+    pub fn is_transmitted(&self) -> bool {
+        self.status == TxStatus::Transmitted
+    }
+}
+
+/// Transmit FIFOs, whose pending messages were aborted, mapped from the TXABT flags
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AbortedTransmissions {
+    /// Bit mask of FIFO indices, bit 0 represents the TXQ
+    fifos: u32,
+}
+
+impl AbortedTransmissions {
+    /// Maps the bit mask of aborted FIFO indices
+    pub(crate) fn from_register(fifos: u32) -> Self {
+        Self { fifos }
+    }
+
+    /// Returns true if messages of the TXQ were aborted
+    pub fn txq(&self) -> bool {
+        self.fifos & 1 != 0
+    }
+
+    /// Returns true if messages of the given FIFO were aborted
+    pub fn contains(&self, fifo: Fifo) -> bool {
+        self.fifos & (1 << fifo.index()) != 0
+    }
+
+    /// Iterates over all FIFOs, whose messages were aborted. TXQ is not included.
+    pub fn fifos(&self) -> impl Iterator<Item = Fifo> {
+        Interrupts::fifos(self.fifos)
+    }
+
+    /// Returns true if no message was aborted
+    pub fn is_empty(&self) -> bool {
+        self.fifos == 0
+    }
+}
+
+/// Fault confinement state of the CAN controller
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BusState {
+    /// Error counters are below 128
+    ErrorActive,
+    /// Transmit or receive error counter is 128 or higher
+    ErrorPassive,
+    /// Transmit error counter exceeded 255, controller does not participate in bus communication
+    BusOff,
+}
+
+/// Error counters and states read from CiTREC register
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ErrorState {
+    /// Transmit error counter (TEC)
+    pub tec: u8,
+
+    /// Receive error counter (REC)
+    pub rec: u8,
+
+    /// Fault confinement state
+    pub bus_state: BusState,
+
+    /// True if transmit or receive error counter is 96 or higher
+    pub error_warning: bool,
+
+    /// True if receive error counter is 96 or higher
+    pub rx_warning: bool,
+
+    /// True if transmit error counter is 96 or higher
+    pub tx_warning: bool,
+
+    /// True if receive error counter is 128 or higher
+    pub rx_error_passive: bool,
+
+    /// True if transmit error counter is 128 or higher
+    pub tx_error_passive: bool,
+}
+
+impl ErrorState {
+    /// Maps register bits to error state
+    pub(crate) fn from_register(register: u32) -> Self {
+        let flag = |bit: u32| register & (1 << bit) != 0;
+
+        let bus_state = if flag(21) {
+            BusState::BusOff
+        } else if flag(19) || flag(20) {
+            BusState::ErrorPassive
+        } else {
+            BusState::ErrorActive
+        };
+
+        Self {
+            tec: (register >> 8) as u8,
+            rec: register as u8,
+            bus_state,
+            error_warning: flag(16),
+            rx_warning: flag(17),
+            tx_warning: flag(18),
+            rx_error_passive: flag(19),
+            tx_error_passive: flag(20),
+        }
+    }
+}
+
+/// Bus diagnostics read from CiBDIAG0 and CiBDIAG1 registers.
+/// Error flags are set by the controller and remain set until cleared.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BusDiagnostics {
+    /// Receive error counter of the nominal bit rate phase
+    pub nominal_rx_errors: u8,
+
+    /// Transmit error counter of the nominal bit rate phase
+    pub nominal_tx_errors: u8,
+
+    /// Receive error counter of the data bit rate phase
+    pub data_rx_errors: u8,
+
+    /// Transmit error counter of the data bit rate phase
+    pub data_tx_errors: u8,
+
+    /// Number of messages received or transmitted without error since the last error
+    pub error_free_messages: u16,
+
+    /// DLC mismatch during transmission
+    pub dlc_mismatch: bool,
+
+    /// ESI flag of a received CAN FD message was set
+    pub esi: bool,
+
+    /// CRC error in data phase
+    pub data_crc_error: bool,
+
+    /// Stuffing error in data phase
+    pub data_stuff_error: bool,
+
+    /// Format error in data phase
+    pub data_form_error: bool,
+
+    /// Recessive bit was sent but dominant bit received in data phase
+    pub data_bit1_error: bool,
+
+    /// Dominant bit was sent but recessive bit received in data phase
+    pub data_bit0_error: bool,
+
+    /// Controller entered bus-off state
+    pub bus_off: bool,
+
+    /// CRC error in nominal phase
+    pub nominal_crc_error: bool,
+
+    /// Stuffing error in nominal phase
+    pub nominal_stuff_error: bool,
+
+    /// Format error in nominal phase
+    pub nominal_form_error: bool,
+
+    /// Transmitted message was not acknowledged
+    pub nominal_ack_error: bool,
+
+    /// Recessive bit was sent but dominant bit received in nominal phase
+    pub nominal_bit1_error: bool,
+
+    /// Dominant bit was sent but recessive bit received in nominal phase
+    pub nominal_bit0_error: bool,
+}
+
+impl BusDiagnostics {
+    /// Maps CiBDIAG0 and CiBDIAG1 register values
+    pub(crate) fn from_registers(diag0: u32, diag1: u32) -> Self {
+        let flag = |bit: u32| diag1 & (1 << bit) != 0;
+
+        Self {
+            nominal_rx_errors: diag0 as u8,
+            nominal_tx_errors: (diag0 >> 8) as u8,
+            data_rx_errors: (diag0 >> 16) as u8,
+            data_tx_errors: (diag0 >> 24) as u8,
+            error_free_messages: diag1 as u16,
+            dlc_mismatch: flag(31),
+            esi: flag(30),
+            data_crc_error: flag(29),
+            data_stuff_error: flag(28),
+            data_form_error: flag(27),
+            data_bit1_error: flag(25),
+            data_bit0_error: flag(24),
+            bus_off: flag(23),
+            nominal_crc_error: flag(21),
+            nominal_stuff_error: flag(20),
+            nominal_form_error: flag(19),
+            nominal_ack_error: flag(18),
+            nominal_bit1_error: flag(17),
+            nominal_bit0_error: flag(16),
+        }
+    }
+
+    /// Returns the kind of the recorded bus errors, or None if no bus error flag is set.
+    /// If multiple flags are set, acknowledge errors take precedence over bit, stuffing, CRC and format errors.
+    pub fn error_kind(&self) -> Option<ErrorKind> {
+        if self.nominal_ack_error {
+            return Some(ErrorKind::Acknowledge);
+        }
+
+        if self.nominal_bit0_error || self.nominal_bit1_error || self.data_bit0_error || self.data_bit1_error {
+            return Some(ErrorKind::Bit);
+        }
+
+        if self.nominal_stuff_error || self.data_stuff_error {
+            return Some(ErrorKind::Stuff);
+        }
+
+        if self.nominal_crc_error || self.data_crc_error {
+            return Some(ErrorKind::Crc);
+        }
+
+        if self.nominal_form_error || self.data_form_error {
+            return Some(ErrorKind::Form);
+        }
+
+        None
+    }
+}
+
+/// ECC errors of the message RAM read from ECCSTAT register.
+/// Error flags remain set until cleared by [clear_interrupt](crate::can::MCP2517::clear_interrupt).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EccStatus {
+    /// Single bit error was detected and corrected
+    pub single_error: bool,
+
+    /// Double bit error was detected, the read data is corrupted
+    pub double_error: bool,
+
+    /// RAM address of the last ECC error
+    pub error_address: u16,
+}
+
+impl EccStatus {
+    /// Maps register bits to ECC status
+    pub(crate) fn from_register(register: u32) -> Self {
+        Self {
+            single_error: register & (1 << 1) != 0,
+            double_error: register & (1 << 2) != 0,
+            error_address: ((register >> 16) & 0x0FFF) as u16,
+        }
+    }
+}
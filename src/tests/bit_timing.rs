@@ -0,0 +1,129 @@
+use crate::bit_timing::{BitTiming, BitTimingError};
+
+#[test]
+fn test_nominal_exact_bit_rate() {
+    let timing = BitTiming::nominal(40_000_000, 500_000, 800, 2).unwrap();
+
+    assert_eq!(1, timing.brp());
+    assert_eq!(63, timing.tseg1());
+    assert_eq!(16, timing.tseg2());
+    assert_eq!(2, timing.sjw());
+    assert_eq!(80, timing.time_quanta());
+    assert_eq!(800, timing.sample_point());
+    assert_eq!(500_000, timing.bit_rate());
+    assert_eq!(0, timing.error_ppm());
+    assert_eq!([0, 62, 15, 1], timing.register_values());
+}
+
+#[test]
+fn test_nominal_prescaler_required() {
+    // 8000 TQ per bit without prescaler
+    let timing = BitTiming::nominal(40_000_000, 5_000, 800, 2).unwrap();
+
+    assert_eq!(25, timing.brp());
+    assert_eq!(255, timing.tseg1());
+    assert_eq!(64, timing.tseg2());
+    assert_eq!(5_000, timing.bit_rate());
+    assert_eq!(0, timing.error_ppm());
+    assert_eq!([24, 254, 63, 1], timing.register_values());
+}
+
+#[test]
+fn test_nominal_prescaler_for_sample_point() {
+    // Without prescaler (320 TQ), TSEG2 is limited to 128 TQ, resulting in a sample point of 60%
+    let timing = BitTiming::nominal(40_000_000, 125_000, 500, 2).unwrap();
+
+    assert_eq!(2, timing.brp());
+    assert_eq!(79, timing.tseg1());
+    assert_eq!(80, timing.tseg2());
+    assert_eq!(500, timing.sample_point());
+    assert_eq!(0, timing.error_ppm());
+}
+
+#[test]
+fn test_nominal_bit_rate_error() {
+    let timing = BitTiming::nominal(24_000_000, 33_333, 875, 2).unwrap();
+
+    // Prescaler of 2 would exceed TSEG1 limit at 87.5% sample point
+    assert_eq!(3, timing.brp());
+    assert_eq!(240, timing.time_quanta());
+    assert_eq!(33_333, timing.bit_rate());
+    assert_eq!(10, timing.error_ppm());
+    assert_eq!(875, timing.sample_point());
+}
+
+#[test]
+fn test_data_bit_rate_error() {
+    let timing = BitTiming::data(20_000_000, 3_000_000, 800, 2).unwrap();
+
+    assert_eq!(1, timing.brp());
+    assert_eq!(7, timing.time_quanta());
+    assert_eq!(2_857_143, timing.bit_rate());
+    assert_eq!(47_619, timing.error_ppm());
+}
+
+#[test]
+fn test_data_sjw_limited_by_tseg2() {
+    let timing = BitTiming::data(40_000_000, 8_000_000, 800, 16).unwrap();
+
+    assert_eq!(5, timing.time_quanta());
+    assert_eq!(3, timing.tseg1());
+    assert_eq!(1, timing.tseg2());
+    assert_eq!(1, timing.sjw());
+}
+
+#[test]
+fn test_data_limits_require_prescaler() {
+    // 80 TQ exceed the data phase limit of 49 TQ
+    let timing = BitTiming::data(40_000_000, 500_000, 800, 16).unwrap();
+
+    assert_eq!(2, timing.brp());
+    assert_eq!([1, 30, 7, 7], timing.register_values());
+}
+
+#[test]
+fn test_const_evaluation() {
+    const TIMING: Result<BitTiming, BitTimingError> = BitTiming::nominal(20_000_000, 1_000_000, 750, 1);
+
+    assert_eq!([0, 13, 4, 0], TIMING.unwrap().register_values());
+}
+
+#[test]
+fn test_invalid_arguments() {
+    assert_eq!(
+        Err(BitTimingError::InvalidFrequency),
+        BitTiming::nominal(0, 500_000, 800, 2)
+    );
+    assert_eq!(
+        Err(BitTimingError::InvalidFrequency),
+        BitTiming::nominal(20_000_000, 0, 800, 2)
+    );
+    assert_eq!(
+        Err(BitTimingError::InvalidSamplePoint),
+        BitTiming::nominal(20_000_000, 500_000, 1000, 2)
+    );
+    assert_eq!(
+        Err(BitTimingError::InvalidSamplePoint),
+        BitTiming::data(20_000_000, 500_000, 0, 2)
+    );
+    assert_eq!(
+        Err(BitTimingError::InvalidSjw),
+        BitTiming::nominal(20_000_000, 500_000, 800, 0)
+    );
+    assert_eq!(
+        Err(BitTimingError::InvalidSjw),
+        BitTiming::data(20_000_000, 500_000, 800, 17)
+    );
+}
+
+#[test]
+fn test_bit_rate_not_reachable() {
+    assert_eq!(
+        Err(BitTimingError::NoValidTiming),
+        BitTiming::nominal(20_000_000, 10_000_000, 800, 1)
+    );
+    assert_eq!(
+        Err(BitTimingError::NoValidTiming),
+        BitTiming::nominal(20_000_000, 100, 800, 1)
+    );
+}
@@ -0,0 +1,377 @@
+use crate::can::{CanController, CanError, MCP2517};
+use crate::config::{
+    BitRateConfig, BusOffRecovery, CanBaudRate, Configuration, Fifo, FifoConfiguration, FifoDirection, FifoSettings,
+    FifoTable, InterruptConfiguration, RequestMode, RetransmissionAttempts, SysClk, TxQueueConfiguration,
+    WakeUpFilterTime,
+};
+use crate::filter::Filter;
+use crate::message::{Can20, CanFd, TxMessage, DLC};
+use crate::simulator::bus::{BusError, VirtualBus};
+use crate::simulator::{SimFrame, SimulatedClock, SimulatedDevice};
+use crate::status::{BusState, Interrupt, TxStatus};
+use alloc::vec;
+use alloc::vec::Vec;
+use embedded_can::{ErrorKind, ExtendedId, Id, StandardId};
+use embedded_time::duration::Nanoseconds;
+
+fn standard_id(id: u16) -> Id {
+    Id::Standard(StandardId::new(id).unwrap())
+}
+
+fn message(id: Id, payload: &[u8]) -> TxMessage<Can20<8>, 8> {
+    TxMessage::new(Can20::<8> {}, payload, id).unwrap()
+}
+
+/// Returns a new node of the bus configured with the given settings
+fn node(bus: &VirtualBus, config: &Configuration) -> MCP2517<SimulatedDevice, SimulatedClock> {
+    let mut controller = MCP2517::new(bus.attach(SysClk::MHz20.frequency()).device());
+    controller.configure(config, &SimulatedClock::default()).unwrap();
+
+    controller
+}
+
+fn bus() -> VirtualBus {
+    VirtualBus::new(250_000, 1_000_000)
+}
+
+fn single_attempt() -> Configuration {
+    Configuration {
+        fifo: FifoConfiguration {
+            tx_attempts: RetransmissionAttempts::Disabled,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_transmit_between_nodes() {
+    let bus = bus();
+    let mut node_a = node(&bus, &Configuration::default());
+    let mut node_b = node(&bus, &Configuration::default());
+
+    let outcome = node_a.transmit(&message(standard_id(0x10), &[1, 2]), true).unwrap();
+    assert!(outcome.is_transmitted());
+    assert!(!outcome.error_detected);
+    node_b.transmit(&message(standard_id(0x20), &[3]), true).unwrap();
+
+    assert_eq!([1, 2], node_b.receive::<8>(false).unwrap().get_payload());
+    assert_eq!([3], node_a.receive::<8>(false).unwrap().get_payload());
+    assert_eq!(
+        vec![
+            SimFrame::new(standard_id(0x10), &[1, 2]),
+            SimFrame::new(standard_id(0x20), &[3])
+        ],
+        bus.take_frames()
+    );
+
+    // 63 and 55 bits at 250 kbit/s
+    assert_eq!(Nanoseconds::<u64>::new(472_000), bus.elapsed());
+}
+
+#[test]
+fn test_arbitration() {
+    let bus = bus();
+    let simulator_a = bus.attach(SysClk::MHz20.frequency());
+    let simulator_c = bus.attach(SysClk::MHz20.frequency());
+    let clock = SimulatedClock::default();
+
+    let mut node_a = MCP2517::new(simulator_a.device());
+    let mut node_b = node(&bus, &Configuration::default());
+    let mut node_c = MCP2517::new(simulator_c.device());
+    node_a.configure(&Configuration::default(), &clock).unwrap();
+    node_c.configure(&Configuration::default(), &clock).unwrap();
+
+    let extended_id = Id::Extended(ExtendedId::new(0x10 << 18).unwrap());
+
+    bus.suspend();
+    node_a.transmit(&message(extended_id, &[1]), false).unwrap();
+    node_b.transmit(&message(standard_id(0x10), &[2]), false).unwrap();
+    node_c.transmit(&message(standard_id(0x08), &[3]), false).unwrap();
+    bus.resume();
+
+    // Standard frame wins against extended frame of same base ID
+    let frames = bus.take_frames();
+    assert_eq!(3, frames.len());
+    assert_eq!(standard_id(0x08), frames[0].id);
+    assert_eq!(standard_id(0x10), frames[1].id);
+    assert_eq!(extended_id, frames[2].id);
+
+    // TXLARB of TX FIFO 2
+    assert_ne!(0, simulator_a.read_register(0x06C) & (1 << 6));
+    assert_eq!(0, simulator_c.read_register(0x06C) & (1 << 6));
+
+    // TXLARB of the previous transmission is not reported
+    let outcome = node_a.transmit(&message(extended_id, &[4]), true).unwrap();
+    assert!(outcome.is_transmitted());
+    assert!(!outcome.arbitration_lost);
+}
+
+#[test]
+fn test_missing_acknowledgement() {
+    let bus = bus();
+    let mut node_a = node(&bus, &single_attempt());
+    let _listener = node(
+        &bus,
+        &Configuration {
+            mode: RequestMode::ListenOnly,
+            ..Default::default()
+        },
+    );
+
+    let outcome = node_a.transmit(&message(standard_id(0x10), &[1]), true).unwrap();
+
+    assert_eq!(TxStatus::AttemptsExhausted, outcome.status);
+    assert!(outcome.error_detected);
+    assert!(bus.take_frames().is_empty());
+
+    let error_state = node_a.read_error_state().unwrap();
+    assert_eq!(8, error_state.tec);
+    assert!(node_a.read_bus_diagnostics().unwrap().nominal_ack_error);
+}
+
+#[test]
+fn test_stale_attempts_flag_is_cleared() {
+    let bus = bus();
+    let simulator = bus.attach(SysClk::MHz20.frequency());
+    let mut node_a = MCP2517::new(simulator.device());
+    node_a.configure(&single_attempt(), &SimulatedClock::default()).unwrap();
+
+    // Not acknowledged without receiver
+    node_a.transmit(&message(standard_id(0x10), &[1]), false).unwrap();
+
+    // TXATIF and TXERR of TX FIFO 2 remain set
+    assert_eq!(0b0011_0000, simulator.read_register(0x06C) & 0b1111_0000);
+
+    let mut node_b = node(&bus, &Configuration::default());
+    let outcome = node_a.transmit(&message(standard_id(0x10), &[2]), true).unwrap();
+
+    assert_eq!(TxStatus::Transmitted, outcome.status);
+    assert!(!outcome.error_detected);
+
+    // Message of the failed transmission remains in the FIFO and is transmitted first
+    assert_eq!([1], node_b.receive::<8>(false).unwrap().get_payload());
+    assert_eq!([2], node_b.receive::<8>(false).unwrap().get_payload());
+}
+
+#[test]
+fn test_injected_error_is_retransmitted() {
+    let bus = bus();
+    let mut node_a = node(&bus, &Configuration::default());
+    let mut node_b = node(&bus, &Configuration::default());
+
+    bus.inject_error(BusError::Crc);
+    node_a.transmit(&message(standard_id(0x10), &[1]), true).unwrap();
+
+    // Frame is received once after retransmission
+    assert_eq!([1], node_b.receive::<8>(false).unwrap().get_payload());
+    assert_eq!(CanError::RxFifoEmptyErr, node_b.receive::<8>(false).unwrap_err());
+    assert_eq!(1, bus.take_frames().len());
+
+    assert_eq!(7, node_a.read_error_state().unwrap().tec);
+    assert_eq!(0, node_b.read_error_state().unwrap().rec);
+
+    let diagnostics = node_b.read_bus_diagnostics().unwrap();
+    assert!(diagnostics.nominal_crc_error);
+    assert_eq!(1, diagnostics.nominal_rx_errors);
+    assert_eq!(0, diagnostics.nominal_tx_errors);
+    assert_eq!(1, diagnostics.error_free_messages);
+
+    let diagnostics = node_a.read_bus_diagnostics().unwrap();
+    assert_eq!(0, diagnostics.nominal_rx_errors);
+    assert_eq!(1, diagnostics.nominal_tx_errors);
+}
+
+#[test]
+fn test_bit_rate_mismatch() {
+    let bus = bus();
+    let mut node_a = node(&bus, &single_attempt());
+    let mut node_b = node(
+        &bus,
+        &Configuration {
+            bit_rate: BitRateConfig {
+                sys_clk: SysClk::MHz20,
+                can_speed: CanBaudRate::Kpbs500,
+            },
+            ..Default::default()
+        },
+    );
+
+    node_a.transmit(&message(standard_id(0x10), &[1]), true).unwrap();
+
+    assert!(bus.take_frames().is_empty());
+    assert_eq!(CanError::RxFifoEmptyErr, node_b.receive::<8>(false).unwrap_err());
+    assert_eq!(8, node_a.read_error_state().unwrap().tec);
+    assert_eq!(1, node_b.read_error_state().unwrap().rec);
+}
+
+#[test]
+fn test_fd_frame_rejected_by_can20_node() {
+    let bus = bus();
+    let mut node_a = node(&bus, &single_attempt());
+    let _node_b = node(
+        &bus,
+        &Configuration {
+            mode: RequestMode::NormalCAN2_0,
+            ..Default::default()
+        },
+    );
+
+    let fd_message = TxMessage::new(CanFd::<8> { bitrate_switch: true }, &[1, 2, 3], standard_id(0x10)).unwrap();
+    node_a.transmit(&fd_message, true).unwrap();
+
+    assert!(bus.take_frames().is_empty());
+    assert!(node_a.read_bus_diagnostics().unwrap().nominal_form_error);
+}
+
+#[test]
+fn test_bus_off() {
+    let bus = bus();
+    let mut node_a = node(
+        &bus,
+        &Configuration {
+            bus_off_recovery: BusOffRecovery::Manual,
+            ..Default::default()
+        },
+    );
+    let mut node_b = node(&bus, &Configuration::default());
+
+    for _ in 0..32 {
+        bus.inject_error(BusError::Bit);
+    }
+
+    let clock = SimulatedClock::default();
+    assert_eq!(
+        CanError::BusOff(Some(ErrorKind::Bit)),
+        node_a.transmit(&message(standard_id(0x10), &[1]), true).unwrap_err()
+    );
+    assert!(node_a.read_bus_diagnostics().unwrap().bus_off);
+
+    // Controller recovered on the next bus activity and transmitted the pending message
+    assert_eq!([1], node_b.receive::<8>(false).unwrap().get_payload());
+
+    node_a.recover_bus_off(&clock).unwrap();
+    assert_eq!(BusState::ErrorActive, node_a.read_error_state().unwrap().bus_state);
+
+    node_a.transmit(&message(standard_id(0x10), &[2]), true).unwrap();
+    assert_eq!([2], node_b.receive::<8>(false).unwrap().get_payload());
+}
+
+#[test]
+fn test_remote_frame_answered_by_auto_rtr() {
+    let bus = bus();
+    let response_fifo = Fifo::new(3).unwrap();
+    let mut requester = node(&bus, &Configuration::default());
+    let mut responder = node(
+        &bus,
+        &Configuration {
+            fifo_table: FifoTable::default().with(
+                response_fifo,
+                FifoSettings {
+                    direction: FifoDirection::Transmit,
+                    auto_rtr: true,
+                    ..Default::default()
+                },
+            ),
+            ..Default::default()
+        },
+    );
+
+    responder.set_filter_object(Filter::new(standard_id(0x70), 0).unwrap()).unwrap();
+    responder.enable_filter(3, 0).unwrap();
+    responder
+        .load_fifo(response_fifo, &message(standard_id(0x70), &[7, 7]))
+        .unwrap();
+
+    let remote = TxMessage::new_remote(Can20::<8> {}, DLC::Two, standard_id(0x70)).unwrap();
+    requester.transmit(&remote, true).unwrap();
+
+    assert_eq!(
+        vec![
+            SimFrame::new_remote(standard_id(0x70), 2),
+            SimFrame::new(standard_id(0x70), &[7, 7])
+        ],
+        bus.take_frames()
+    );
+    assert_eq!([7, 7], requester.receive::<8>(false).unwrap().get_payload());
+}
+
+#[test]
+fn test_abort_fifo() {
+    let bus = bus();
+    let mut node_a = node(&bus, &Configuration::default());
+    let mut node_b = node(&bus, &Configuration::default());
+
+    bus.suspend();
+    node_a.transmit(&message(standard_id(0x10), &[1]), false).unwrap();
+    assert!(node_a.abort_fifo(Fifo::TX, &SimulatedClock::default()).unwrap());
+    bus.resume();
+
+    assert!(bus.take_frames().is_empty());
+
+    // Aborted message is discarded
+    node_a.transmit(&message(standard_id(0x10), &[2]), true).unwrap();
+    assert_eq!([2], node_b.receive::<8>(false).unwrap().get_payload());
+    assert_eq!(CanError::RxFifoEmptyErr, node_b.receive::<8>(false).unwrap_err());
+
+    // Nothing pending after transmission
+    assert!(!node_a.abort_fifo(Fifo::TX, &SimulatedClock::default()).unwrap());
+}
+
+#[test]
+fn test_abort_all() {
+    let bus = bus();
+    let config = Configuration {
+        txq: Some(TxQueueConfiguration::default()),
+        ..Default::default()
+    };
+    let mut node_a = node(&bus, &config);
+    let mut node_b = node(&bus, &Configuration::default());
+
+    bus.suspend();
+    node_a.transmit(&message(standard_id(0x10), &[1]), false).unwrap();
+    node_a.transmit_txq(&message(standard_id(0x11), &[2]), false).unwrap();
+
+    let aborted = node_a.abort_all(&SimulatedClock::default()).unwrap();
+    bus.resume();
+
+    assert!(aborted.txq());
+    assert!(aborted.contains(Fifo::TX));
+    assert_eq!(vec![Fifo::TX], aborted.fifos().collect::<Vec<_>>());
+    assert!(bus.take_frames().is_empty());
+
+    // Transmissions are possible after the abort
+    node_a.transmit(&message(standard_id(0x12), &[3]), true).unwrap();
+    assert_eq!([3], node_b.receive::<8>(false).unwrap().get_payload());
+}
+
+#[test]
+fn test_wake_up_by_bus_activity() {
+    let bus = bus();
+    let clock = SimulatedClock::default();
+    let mut node_a = node(&bus, &Configuration::default());
+    let mut node_b = node(&bus, &Configuration::default());
+    let mut sleeping = node(
+        &bus,
+        &Configuration {
+            interrupts: InterruptConfiguration {
+                wake_up: true,
+                ..Default::default()
+            },
+            wake_up_filter: Some(WakeUpFilterTime::T00),
+            ..Default::default()
+        },
+    );
+
+    sleeping.enter_sleep(&clock).unwrap();
+    node_a.transmit(&message(standard_id(0x10), &[1]), true).unwrap();
+
+    assert_eq!([1], node_b.receive::<8>(false).unwrap().get_payload());
+    assert!(sleeping.read_interrupts().unwrap().contains(Interrupt::WakeUp));
+
+    assert!(sleeping.wake(&clock).unwrap());
+    assert_eq!(CanError::RxFifoEmptyErr, sleeping.receive::<8>(false).unwrap_err());
+
+    node_a.transmit(&message(standard_id(0x10), &[2]), true).unwrap();
+    assert_eq!([2], sleeping.receive::<8>(false).unwrap().get_payload());
+}
@@ -1,19 +1,22 @@
 use crate::can::CanController;
 use crate::can::{CanError, MCP2517};
 use crate::config::{
-    BitRateConfig, CanBaudRate, ClockConfiguration, ClockOutputDivisor, Configuration, FifoConfiguration, PLLSetting,
-    PayloadSize, RequestMode, RetransmissionAttempts, SysClk, SystemClockDivisor,
+    BitRateConfig, BusOffRecovery, CanBaudRate, ClockConfiguration, ClockOutputDivisor, Configuration,
+    DataBitRateConfig, EccConfiguration, Fifo, FifoConfiguration, FifoDirection, FifoInterrupts, FifoSettings,
+    FifoTable, GpioPin, InterruptConfiguration, IoConfiguration, PLLSetting, PayloadSize, RequestMode,
+    RetransmissionAttempts, SysClk, SystemClockDivisor,
 };
 use crate::example::{ExampleClock, ExampleSPIDevice};
 use crate::filter::Filter;
-use crate::message::{Can20, CanFd, TxMessage};
+use crate::message::{Can20, CanFd, TxMessage, DLC};
 use crate::mocks::{MockSPIDevice, SPIError, TestClock};
-use crate::status::OperationMode;
+use crate::registers::{Operation as RegisterOperation, RegisterWrite};
+use crate::status::{BusState, ChipVariant, Interrupt, OperationMode, TxStatus};
 use alloc::vec;
 use byteorder::{BigEndian, ByteOrder, LittleEndian};
-use bytes::Bytes;
-use embedded_can::{ExtendedId, Id, StandardId};
+use embedded_can::{ErrorKind, ExtendedId, Id, StandardId};
 use embedded_hal::spi::Operation;
+use embedded_time::duration::Milliseconds;
 use mockall::Sequence;
 
 /// CAN configuration mock
@@ -21,9 +24,27 @@ fn expect_config(spi_dev: &mut Mocks, seq: &mut Sequence) {
     // Writing clock configuration
     spi_dev.expect_register_write([0x2E, 0x0, 0b0110_0001], seq);
 
+    // Writing IO configuration (GPIO inputs)
+    spi_dev.mock_write32([0x2E, 0x04, 0x03, 0x00, 0x00, 0x03], seq);
+
     // Writing NBT configuration register
     spi_dev.mock_write32([0x20, 0x04, 1, 15, 62, 0], seq);
 
+    // Writing DBT configuration register
+    spi_dev.mock_write32([0x20, 0x08, 3, 3, 14, 0], seq);
+
+    // Writing TDC register (automatic mode, offset 16)
+    spi_dev.mock_write32([0x20, 0x0C, 0, 16, 2, 0], seq);
+
+    // Disable wake-up filter, WFT keeps its reset value
+    spi_dev.expect_register_write([0x20, 0x01, 0b0000_0110], seq);
+
+    // Disable TXQ and TEF, restrict retransmission attempts
+    spi_dev.expect_register_write([0x20, 0x02, 0b0000_0001], seq);
+
+    // Disable all interrupts
+    spi_dev.mock_write32([0x20, 0x1C, 0, 0, 0, 0], seq);
+
     // Writing RX FIFO configuration
     spi_dev.expect_register_write([0x20, 0x5F, 0b0000_1111], seq);
 
@@ -84,6 +105,7 @@ fn test_configure_correct() {
                     clock_output: ClockOutputDivisor::DivideBy10,
                     system_clock: SystemClockDivisor::DivideBy1,
                     disable_clock: false,
+                    low_power_mode: false,
                     pll: PLLSetting::TenTimesPLL,
                 },
                 fifo: FifoConfiguration {
@@ -94,14 +116,114 @@ fn test_configure_correct() {
                     tx_size: 20,
                     tx_enable: true,
                 },
+                fifo_table: FifoTable::default(),
+                txq: None,
+                tef: None,
+                timestamp: None,
+                interrupts: InterruptConfiguration::default(),
+                io: IoConfiguration::default(),
+                ecc: None,
+                wake_up_filter: None,
+                bus_off_recovery: BusOffRecovery::Automatic,
                 mode: RequestMode::NormalCAN2_0,
                 bit_rate: BitRateConfig::default(),
+                data_bit_rate: DataBitRateConfig::default(),
             },
             &clock,
         )
         .unwrap();
 }
 
+#[test]
+fn test_configure_fifo_table() {
+    let clock = TestClock::new(vec![100, 200, 10_000, 10_100]);
+
+    let mut mock = Mocks::new();
+    let mut seq = Sequence::new();
+
+    // Request configuration mode
+    mock.expect_register_write([0x20, 0x3, 0b0000_1100], &mut seq);
+    mock.mock_register_read::<0b1001_0100>([0x30, 0x2], &mut seq);
+
+    mock.expect_register_write([0x2E, 0x0, 0b0000_0000], &mut seq);
+    mock.mock_write32([0x2E, 0x04, 0x03, 0x00, 0x00, 0x03], &mut seq);
+    mock.mock_write32([0x20, 0x04, 1, 15, 62, 0], &mut seq);
+    mock.mock_write32([0x20, 0x08, 3, 3, 14, 0], &mut seq);
+    mock.mock_write32([0x20, 0x0C, 0, 16, 2, 0], &mut seq);
+    mock.expect_register_write([0x20, 0x01, 0b0000_0111], &mut seq);
+    mock.expect_register_write([0x20, 0x02, 0b0000_0001], &mut seq);
+    mock.mock_write32([0x20, 0x1C, 0, 0, 0, 0], &mut seq);
+
+    // RX FIFO configuration
+    mock.expect_register_write([0x20, 0x5F, 0b0001_1111], &mut seq);
+
+    // TX FIFO 2: TXEN + RTREN, three attempts, priority 7, 4 messages of 64 bytes
+    mock.mock_write32([0x20, 0x68, 0b1100_0000, 0x0, 0b0010_0111, 0b1110_0011], &mut seq);
+
+    // RX FIFO 5: RXTSEN, 8 messages of 8 bytes
+    mock.mock_write32([0x20, 0x8C, 0b0010_0000, 0x0, 0b0100_0000, 0b0000_0111], &mut seq);
+
+    // Filter 0
+    mock.expect_register_write([0x21, 0xD0, 0x00], &mut seq);
+    mock.expect_register_write([0x21, 0xD0, 0x01], &mut seq);
+    mock.expect_register_write([0x21, 0xD0, 0b1000_0001], &mut seq);
+
+    // Request normal CAN FD mode
+    mock.expect_register_write([0x20, 0x3, 0b0000_1000], &mut seq);
+    mock.mock_register_read::<0b0000_0000>([0x30, 0x2], &mut seq);
+
+    let fifo_table = FifoTable::default()
+        .with(
+            Fifo::TX,
+            FifoSettings {
+                direction: FifoDirection::Transmit,
+                size: 4,
+                pl_size: PayloadSize::SixtyFourBytes,
+                priority: 7,
+                attempts: RetransmissionAttempts::Three,
+                timestamp: false,
+                auto_rtr: true,
+                interrupts: FifoInterrupts::default(),
+            },
+        )
+        .with(
+            Fifo::new(5).unwrap(),
+            FifoSettings {
+                size: 8,
+                timestamp: true,
+                ..Default::default()
+            },
+        );
+
+    let config = Configuration {
+        fifo_table,
+        ..Default::default()
+    };
+
+    mock.into_controller().configure(&config, &clock).unwrap();
+}
+
+#[test]
+fn test_configure_ram_size_exceeded() {
+    let clock = TestClock::new(vec![]);
+    let fifo_table = FifoTable::default().with(
+        Fifo::new(3).unwrap(),
+        FifoSettings {
+            size: 32,
+            pl_size: PayloadSize::SixtyFourBytes,
+            ..Default::default()
+        },
+    );
+
+    let config = Configuration {
+        fifo_table,
+        ..Default::default()
+    };
+
+    let result = Mocks::default().into_controller().configure(&config, &clock);
+    assert_eq!(CanError::RamSizeExceeded(3776), result.unwrap_err());
+}
+
 #[test]
 fn test_configure_mode_timeout() {
     let clock = TestClock::new(vec![
@@ -133,7 +255,7 @@ fn test_transmit_can20() {
     let mut mocks = Mocks::default();
     let mut seq = Sequence::new();
     let payload: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
-    let payload_bytes = Bytes::copy_from_slice(&payload);
+    let payload_bytes = &payload;
 
     let msg_type = Can20::<8> {};
 
@@ -144,6 +266,9 @@ fn test_transmit_can20() {
     // mock fifo status register read byte 0 (1st attempt) -> TX fifo full
     mocks.mock_register_read::<0b0000_0000>([0x30, 0x6C], &mut seq);
 
+    // mock error state read -> not in bus-off state
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x36], &mut seq);
+
     // mock fifo status register read byte 0 (2nd attempt) -> TX fifo not full
     mocks.mock_register_read::<0b0000_0001>([0x30, 0x6C], &mut seq);
 
@@ -168,23 +293,366 @@ fn test_transmit_can20() {
 
     mocks.expect_fifo_write_transaction(cmd_and_header_buffer, payload, &mut seq);
 
+    mocks.expect_register_write([0x20, 0x6C, 0b0000_1111], &mut seq);
     mocks.expect_register_write([0x20, 0x69, 0x03], &mut seq);
 
     // mock reading of fifo control register
     // 1st attempt -> txreq still set ->not all messages inside tx fifo have been transmitted
     mocks.mock_register_read::<0x02>([0x30, 0x69], &mut seq);
+    // mock error state read -> not in bus-off state
+    mocks.mock_register_read::<0x00>([0x30, 0x36], &mut seq);
+    // mock status read -> no lost arbitration or error
+    mocks.mock_register_read::<0x00>([0x30, 0x6C], &mut seq);
     // 2nd attempt -> txreq cleared -> all messages inside tx fifo have been transmitted
     mocks.mock_register_read::<0x00>([0x30, 0x69], &mut seq);
+    mocks.mock_register_read::<0x01>([0x30, 0x6C], &mut seq);
 
     mocks.into_controller().transmit(&tx_message_copy, true).unwrap();
 }
 
+#[test]
+fn test_transmit_outcome() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+    let tx_message = standard_message();
+
+    // TX FIFO not full, normal CAN FD mode
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x6C], &mut seq);
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x2], &mut seq);
+    mocks.mock_read32::<0x00_00_04_A0>([0x30, 0x70], &mut seq);
+
+    let mut cmd_and_header_buffer = [0u8; 10];
+    cmd_and_header_buffer[0] = 0x28;
+    cmd_and_header_buffer[1] = 0xA0;
+    cmd_and_header_buffer[2..].copy_from_slice(&tx_message.header.into_bytes());
+
+    for chunk in cmd_and_header_buffer[2..].chunks_exact_mut(4) {
+        let num = BigEndian::read_u32(chunk);
+        LittleEndian::write_u32(chunk, num);
+    }
+
+    mocks.expect_fifo_write_transaction(cmd_and_header_buffer, [1, 2, 3, 4, 5, 6, 7, 8], &mut seq);
+    mocks.expect_register_write([0x20, 0x6C, 0b0000_1111], &mut seq);
+    mocks.expect_register_write([0x20, 0x69, 0x03], &mut seq);
+
+    // 1st attempt -> no lost arbitration yet
+    mocks.mock_register_read::<0x02>([0x30, 0x69], &mut seq);
+    mocks.mock_register_read::<0x00>([0x30, 0x36], &mut seq);
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x6C], &mut seq);
+
+    // 2nd attempt -> lost arbitration and error detected, flags remain set
+    mocks.mock_register_read::<0x02>([0x30, 0x69], &mut seq);
+    mocks.mock_register_read::<0x00>([0x30, 0x36], &mut seq);
+    mocks.mock_register_read::<0b0110_0001>([0x30, 0x6C], &mut seq);
+
+    // TXREQ cleared -> attempts exhausted
+    mocks.mock_register_read::<0x00>([0x30, 0x69], &mut seq);
+    mocks.mock_register_read::<0b0111_0001>([0x30, 0x6C], &mut seq);
+
+    let outcome = mocks.into_controller().transmit(&tx_message, true).unwrap();
+
+    assert_eq!(TxStatus::AttemptsExhausted, outcome.status);
+    assert!(outcome.arbitration_lost);
+    assert!(outcome.error_detected);
+    assert!(!outcome.is_transmitted());
+}
+
+#[test]
+fn test_transmit_outcome_aborted() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+    let tx_message = standard_message();
+
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x6C], &mut seq);
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x2], &mut seq);
+    mocks.mock_read32::<0x00_00_04_A0>([0x30, 0x70], &mut seq);
+
+    let mut cmd_and_header_buffer = [0u8; 10];
+    cmd_and_header_buffer[0] = 0x28;
+    cmd_and_header_buffer[1] = 0xA0;
+    cmd_and_header_buffer[2..].copy_from_slice(&tx_message.header.into_bytes());
+
+    for chunk in cmd_and_header_buffer[2..].chunks_exact_mut(4) {
+        let num = BigEndian::read_u32(chunk);
+        LittleEndian::write_u32(chunk, num);
+    }
+
+    mocks.expect_fifo_write_transaction(cmd_and_header_buffer, [1, 2, 3, 4, 5, 6, 7, 8], &mut seq);
+    mocks.expect_register_write([0x20, 0x6C, 0b0000_1111], &mut seq);
+    mocks.expect_register_write([0x20, 0x69, 0x03], &mut seq);
+
+    // TXREQ cleared -> message aborted
+    mocks.mock_register_read::<0x00>([0x30, 0x69], &mut seq);
+    mocks.mock_register_read::<0b1000_0001>([0x30, 0x6C], &mut seq);
+
+    let outcome = mocks.into_controller().transmit(&tx_message, true).unwrap();
+
+    assert_eq!(TxStatus::Aborted, outcome.status);
+    assert!(!outcome.arbitration_lost);
+    assert!(!outcome.error_detected);
+}
+
+#[test]
+fn test_transmit_outcome_attempts_exhausted_before_aborted() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+    let tx_message = standard_message();
+
+    // Stale TXATIF of a previous transmission
+    mocks.mock_register_read::<0b0001_0001>([0x30, 0x6C], &mut seq);
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x2], &mut seq);
+    mocks.mock_read32::<0x00_00_04_A0>([0x30, 0x70], &mut seq);
+
+    let mut cmd_and_header_buffer = [0u8; 10];
+    cmd_and_header_buffer[0] = 0x28;
+    cmd_and_header_buffer[1] = 0xA0;
+    cmd_and_header_buffer[2..].copy_from_slice(&tx_message.header.into_bytes());
+
+    for chunk in cmd_and_header_buffer[2..].chunks_exact_mut(4) {
+        let num = BigEndian::read_u32(chunk);
+        LittleEndian::write_u32(chunk, num);
+    }
+
+    mocks.expect_fifo_write_transaction(cmd_and_header_buffer, [1, 2, 3, 4, 5, 6, 7, 8], &mut seq);
+
+    // TXABT, TXLARB, TXERR and TXATIF are cleared before requesting transmission
+    mocks.expect_register_write([0x20, 0x6C, 0b0000_1111], &mut seq);
+    mocks.expect_register_write([0x20, 0x69, 0x03], &mut seq);
+
+    // TXREQ cleared -> attempts exhausted, reported along with TXABT
+    mocks.mock_register_read::<0x00>([0x30, 0x69], &mut seq);
+    mocks.mock_register_read::<0b1011_0001>([0x30, 0x6C], &mut seq);
+
+    let outcome = mocks.into_controller().transmit(&tx_message, true).unwrap();
+
+    assert_eq!(TxStatus::AttemptsExhausted, outcome.status);
+    assert!(outcome.error_detected);
+}
+
+#[test]
+fn test_transmit_fifo() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+    let payload: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    let identifier = StandardId::new(STANDARD_ID).unwrap();
+    let tx_message = TxMessage::new(Can20::<8> {}, &payload, Id::Standard(identifier)).unwrap();
+
+    // FIFO 4 status register -> not full
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x84], &mut seq);
+
+    // mock read operation status
+    mocks.mock_register_read::<0b1100_0000>([0x30, 0x2], &mut seq);
+
+    // FIFO 4 user address register
+    mocks.mock_read32::<0x00_00_05_10>([0x30, 0x88], &mut seq);
+
+    let mut cmd_and_header_buffer = [0u8; 10];
+    cmd_and_header_buffer[0] = 0x29;
+    cmd_and_header_buffer[1] = 0x10;
+    cmd_and_header_buffer[2..].copy_from_slice(&tx_message.header.into_bytes());
+
+    for chunk in cmd_and_header_buffer[2..].chunks_exact_mut(4) {
+        let num = BigEndian::read_u32(chunk);
+        LittleEndian::write_u32(chunk, num);
+    }
+
+    mocks.expect_fifo_write_transaction(cmd_and_header_buffer, payload, &mut seq);
+
+    // Request transmission of FIFO 4
+    mocks.expect_register_write([0x20, 0x81, 0x03], &mut seq);
+
+    let outcome = mocks
+        .into_controller()
+        .transmit_fifo(Fifo::new(4).unwrap(), &tx_message, false)
+        .unwrap();
+    assert_eq!(TxStatus::Pending, outcome.status);
+}
+
+#[test]
+fn test_load_fifo() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    let identifier = StandardId::new(STANDARD_ID).unwrap();
+    let tx_message = TxMessage::new(Can20::<8> {}, &[1, 2, 3, 4], Id::Standard(identifier)).unwrap();
+
+    // FIFO 3 status register -> not full
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x78], &mut seq);
+
+    // mock read operation status
+    mocks.mock_register_read::<0b1100_0000>([0x30, 0x2], &mut seq);
+
+    // FIFO 3 user address register
+    mocks.mock_read32::<0x00_00_04_A0>([0x30, 0x7C], &mut seq);
+
+    let mut cmd_and_header_buffer = [0u8; 10];
+    cmd_and_header_buffer[0] = 0x28;
+    cmd_and_header_buffer[1] = 0xA0;
+    cmd_and_header_buffer[2..].copy_from_slice(&tx_message.header.into_bytes());
+
+    for chunk in cmd_and_header_buffer[2..].chunks_exact_mut(4) {
+        let num = BigEndian::read_u32(chunk);
+        LittleEndian::write_u32(chunk, num);
+    }
+
+    mocks.expect_fifo_write_transaction(cmd_and_header_buffer, [1, 2, 3, 4], &mut seq);
+
+    // Increment FIFO 3 without requesting transmission
+    mocks.expect_register_write([0x20, 0x75, 0x01], &mut seq);
+
+    let mut controller = mocks.into_controller();
+    controller.load_fifo(Fifo::new(3).unwrap(), &tx_message).unwrap();
+
+    assert_eq!(1, controller.next_sequence());
+}
+
+#[test]
+fn test_load_fifo_full() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    let tx_message = TxMessage::new(Can20::<8> {}, &[], Id::Standard(StandardId::ZERO)).unwrap();
+
+    // FIFO 3 status register -> full
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x78], &mut seq);
+
+    let result = mocks.into_controller().load_fifo(Fifo::new(3).unwrap(), &tx_message);
+
+    assert_eq!(CanError::TxFifoFullErr, result.unwrap_err());
+}
+
+#[test]
+fn test_transmit_txq() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+    let payload: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    let identifier = StandardId::new(STANDARD_ID).unwrap();
+    let tx_message = TxMessage::new(Can20::<8> {}, &payload, Id::Standard(identifier)).unwrap();
+
+    // TXQ status register -> not full
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x54], &mut seq);
+
+    // mock read operation status
+    mocks.mock_register_read::<0b1101_0000>([0x30, 0x2], &mut seq);
+
+    // TXQ user address register
+    mocks.mock_read32::<0x00_00_04_00>([0x30, 0x58], &mut seq);
+
+    let mut cmd_and_header_buffer = [0u8; 10];
+    cmd_and_header_buffer[0] = 0x28;
+    cmd_and_header_buffer[1] = 0x00;
+    cmd_and_header_buffer[2..].copy_from_slice(&tx_message.header.into_bytes());
+
+    for chunk in cmd_and_header_buffer[2..].chunks_exact_mut(4) {
+        let num = BigEndian::read_u32(chunk);
+        LittleEndian::write_u32(chunk, num);
+    }
+
+    mocks.expect_fifo_write_transaction(cmd_and_header_buffer, payload, &mut seq);
+
+    // Request transmission of TXQ
+    mocks.expect_register_write([0x20, 0x54, 0b0000_1111], &mut seq);
+    mocks.expect_register_write([0x20, 0x51, 0x03], &mut seq);
+
+    // TXREQ cleared
+    mocks.mock_register_read::<0x00>([0x30, 0x51], &mut seq);
+    mocks.mock_register_read::<0x01>([0x30, 0x54], &mut seq);
+
+    let outcome = mocks.into_controller().transmit_txq(&tx_message, true).unwrap();
+    assert_eq!(TxStatus::Transmitted, outcome.status);
+}
+
+#[test]
+fn test_transmit_sequence_number() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+    let payload: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    let identifier = StandardId::new(STANDARD_ID).unwrap();
+    let tx_message = TxMessage::new(Can20::<8> {}, &payload, Id::Standard(identifier)).unwrap();
+
+    for sequence in 0..2 {
+        // TXQ status register -> not full
+        mocks.mock_register_read::<0b0000_0001>([0x30, 0x54], &mut seq);
+
+        // mock read operation status
+        mocks.mock_register_read::<0b1101_0000>([0x30, 0x2], &mut seq);
+
+        // TXQ user address register
+        mocks.mock_read32::<0x00_00_04_00>([0x30, 0x58], &mut seq);
+
+        let mut cmd_and_header_buffer = [0u8; 10];
+        cmd_and_header_buffer[0] = 0x28;
+        cmd_and_header_buffer[1] = 0x00;
+        cmd_and_header_buffer[2..].copy_from_slice(&tx_message.header.with_sequence(sequence).into_bytes());
+
+        for chunk in cmd_and_header_buffer[2..].chunks_exact_mut(4) {
+            let num = BigEndian::read_u32(chunk);
+            LittleEndian::write_u32(chunk, num);
+        }
+
+        mocks.expect_fifo_write_transaction(cmd_and_header_buffer, payload, &mut seq);
+
+        // Request transmission of TXQ
+        mocks.expect_register_write([0x20, 0x51, 0x03], &mut seq);
+    }
+
+    let mut controller = mocks.into_controller();
+    assert_eq!(0, controller.next_sequence());
+
+    controller.transmit_txq(&tx_message, false).unwrap();
+    controller.transmit_txq(&tx_message, false).unwrap();
+
+    assert_eq!(2, controller.next_sequence());
+}
+
+#[test]
+fn test_read_transmit_event() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // TEF status register -> not empty
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x44], &mut seq);
+
+    // TEF user address register
+    mocks.mock_read32::<0x00_00_00_40>([0x30, 0x48], &mut seq);
+
+    // Standard ID, DLC 8, sequence 5
+    let header = [0xA5, 0x06, 0x0, 0x0, 0x08, 0x0A, 0x0, 0x0];
+    mocks.expect_read_transaction([0x34, 0x40], header, &mut seq);
+
+    // Increment TEF tail
+    mocks.expect_register_write([0x20, 0x41, 0b0000_0001], &mut seq);
+
+    let event = mocks.into_controller().read_transmit_event().unwrap();
+
+    assert_eq!(5, event.sequence());
+    assert_eq!(Id::Standard(StandardId::new(STANDARD_ID).unwrap()), event.id());
+    assert_eq!(DLC::Eight, event.dlc());
+    assert_eq!(None, event.timestamp());
+}
+
+#[test]
+fn test_read_transmit_event_empty() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // TEF status register -> empty
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x44], &mut seq);
+
+    let result = mocks.into_controller().read_transmit_event();
+
+    assert_eq!(CanError::TefEmptyErr, result.unwrap_err());
+}
+
 #[test]
 fn test_transmit_can20_3_bytes() {
     let mut mocks = Mocks::default();
     let mut seq = Sequence::new();
     let payload: [u8; 3] = [1, 2, 3];
-    let payload_bytes = Bytes::copy_from_slice(&payload);
+    let payload_bytes = &payload;
 
     let msg_type = Can20::<4> {};
 
@@ -195,6 +663,9 @@ fn test_transmit_can20_3_bytes() {
     // mock fifo status register read byte 0 (1st attempt) -> tx fifo full
     mocks.mock_register_read::<0b0000_0000>([0x30, 0x6C], &mut seq);
 
+    // mock error state read -> not in bus-off state
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x36], &mut seq);
+
     // mock fifo status register read byte 0 (2nd attempt) -> tx fifo not full
     mocks.mock_register_read::<0b0000_0001>([0x30, 0x6C], &mut seq);
 
@@ -219,13 +690,19 @@ fn test_transmit_can20_3_bytes() {
 
     mocks.expect_fifo_write_transaction(cmd_and_header_buffer, payload, &mut seq);
 
+    mocks.expect_register_write([0x20, 0x6C, 0b0000_1111], &mut seq);
     mocks.expect_register_write([0x20, 0x69, 0x03], &mut seq);
 
     // mock reading of fifo control register
     // 1st attempt -> txreq still set ->not all messages inside tx fifo have been transmitted
     mocks.mock_register_read::<0x02>([0x30, 0x69], &mut seq);
+    // mock error state read -> not in bus-off state
+    mocks.mock_register_read::<0x00>([0x30, 0x36], &mut seq);
+    // mock status read -> no lost arbitration or error
+    mocks.mock_register_read::<0x00>([0x30, 0x6C], &mut seq);
     // 2nd attempt -> txreq cleared -> all messages inside tx fifo have been transmitted
     mocks.mock_register_read::<0x00>([0x30, 0x69], &mut seq);
+    mocks.mock_register_read::<0x01>([0x30, 0x6C], &mut seq);
 
     mocks.into_controller().transmit(&tx_message_copy, true).unwrap();
 }
@@ -235,7 +712,7 @@ fn test_transmit_can_fd() {
     let mut mocks = Mocks::default();
     let mut seq = Sequence::new();
     let payload = [1u8; 64];
-    let payload_bytes = Bytes::copy_from_slice(&payload);
+    let payload_bytes = &payload;
 
     let msg_type = CanFd::<64> { bitrate_switch: false };
 
@@ -246,6 +723,9 @@ fn test_transmit_can_fd() {
     // mock fifo status register read byte 0 (1st attempt) -> tx fifo full
     mocks.mock_register_read::<0b0000_0000>([0x30, 0x6C], &mut seq);
 
+    // mock error state read -> not in bus-off state
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x36], &mut seq);
+
     // mock fifo status register read byte 0 (2nd attempt) -> tx fifo not full
     mocks.mock_register_read::<0b0000_0001>([0x30, 0x6C], &mut seq);
 
@@ -262,99 +742,641 @@ fn test_transmit_can_fd() {
     cmd_and_header_buffer[0] = 0x28;
     cmd_and_header_buffer[1] = 0xA2;
 
-    cmd_and_header_buffer[2..].copy_from_slice(&tx_message.header.into_bytes());
+    cmd_and_header_buffer[2..].copy_from_slice(&tx_message.header.into_bytes());
+
+    for chunk in cmd_and_header_buffer[2..].chunks_exact_mut(4) {
+        let num = BigEndian::read_u32(chunk);
+        LittleEndian::write_u32(chunk, num);
+    }
+
+    mocks.expect_fifo_write_transaction(cmd_and_header_buffer, payload, &mut seq);
+
+    mocks.expect_register_write([0x20, 0x6C, 0b0000_1111], &mut seq);
+    mocks.expect_register_write([0x20, 0x69, 0x03], &mut seq);
+
+    // mock reading of fifo control register
+    // 1st attempt -> txreq still set ->not all messages inside tx fifo have been transmitted
+    mocks.mock_register_read::<0x02>([0x30, 0x69], &mut seq);
+    // mock error state read -> not in bus-off state
+    mocks.mock_register_read::<0x00>([0x30, 0x36], &mut seq);
+    // mock status read -> no lost arbitration or error
+    mocks.mock_register_read::<0x00>([0x30, 0x6C], &mut seq);
+    // 2nd attempt -> txreq cleared -> all messages inside tx fifo have been transmitted
+    mocks.mock_register_read::<0x00>([0x30, 0x69], &mut seq);
+    mocks.mock_register_read::<0x01>([0x30, 0x6C], &mut seq);
+
+    mocks.into_controller().transmit(&tx_message_copy, true).unwrap();
+}
+
+#[test]
+fn test_read_fifo_invalid_payload_buffer_size() {
+    let mocks = Mocks::default();
+
+    let result = mocks.into_controller().read_fifo::<3>(0x123, false);
+    assert_eq!(result.unwrap_err(), CanError::InvalidBufferSize(3));
+}
+
+#[test]
+fn test_read_fifo_timestamp() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    let header = [0xA5, 0x06, 0x0, 0x0, 0x04, 0x0, 0x0, 0x0];
+    mocks.expect_fifo_read_transaction_with_timestamp(
+        [0x34, 0x7C],
+        header,
+        [0x78, 0x56, 0x34, 0x12],
+        [1, 2, 3, 4],
+        &mut seq,
+    );
+
+    let message = mocks.into_controller().read_fifo::<4>(0x47C, true).unwrap();
+
+    assert_eq!(message.id(), Id::Standard(StandardId::new(STANDARD_ID).unwrap()));
+    assert_eq!(message.timestamp(), Some(0x1234_5678));
+    assert_eq!(message.get_payload(), [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_read_fifo_crc() {
+    let mut mocks = Mocks::default();
+    let header = [0xA5, 0x06, 0x0, 0x0, 0x08, 0x0, 0x0, 0x0];
+
+    mocks.device.expect_transaction().times(1).returning(move |operation| {
+        assert_eq!(operation.len(), 4);
+        match operation[0] {
+            // length in words for RAM access
+            Operation::Write(write) => assert_eq!([0xB4, 0x7C, 0x04], write),
+            _ => panic!("Unexpected operation received {:?}", operation[0]),
+        }
+
+        for (index, received) in [&header[..], &[1, 2, 3, 4, 5, 6, 7, 8], &[0x3A, 0xB7]].into_iter().enumerate() {
+            match &mut operation[index + 1] {
+                Operation::Read(read) => read.copy_from_slice(received),
+                _ => panic!("Unexpected operation received {:?}", operation[index + 1]),
+            }
+        }
+        Ok(())
+    });
+
+    let mut controller = mocks.into_controller();
+    controller.set_spi_crc(true);
+    let message = controller.read_fifo::<8>(0x47C, false).unwrap();
+
+    assert_eq!(message.id(), Id::Standard(StandardId::new(STANDARD_ID).unwrap()));
+    assert_eq!(message.get_payload(), [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn test_read_crc() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    mocks.expect_crc_read_transaction([0xB0, 0x10, 0x04], [0x40, 0xE2, 0x01, 0x00], [0xE9, 0x44], &mut seq);
+
+    let mut controller = mocks.into_controller();
+    controller.set_spi_crc(true);
+
+    assert_eq!(123_456, controller.read_time_base().unwrap());
+}
+
+#[test]
+fn test_read_crc_mismatch() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    mocks.expect_crc_read_transaction([0xB0, 0x10, 0x04], [0x41, 0xE2, 0x01, 0x00], [0xE9, 0x44], &mut seq);
+
+    let mut controller = mocks.into_controller();
+    controller.set_variant(ChipVariant::MCP2518FD);
+    controller.set_spi_crc(true);
+
+    assert_eq!(CanError::CrcMismatch, controller.read_time_base().unwrap_err());
+}
+
+#[test]
+fn test_read_crc_mismatch_repeated_mcp2517fd() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // Counter updated during the first read
+    mocks.expect_crc_read_transaction([0xB0, 0x10, 0x04], [0x41, 0xE2, 0x01, 0x00], [0xE9, 0x44], &mut seq);
+    mocks.expect_crc_read_transaction([0xB0, 0x10, 0x04], [0x40, 0xE2, 0x01, 0x00], [0xE9, 0x44], &mut seq);
+
+    let mut controller = mocks.into_controller();
+    controller.set_spi_crc(true);
+
+    assert_eq!(123_456, controller.read_time_base().unwrap());
+}
+
+#[test]
+fn test_read_crc_mismatch_mcp2517fd() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    for _ in 0..2 {
+        mocks.expect_crc_read_transaction([0xB0, 0x10, 0x04], [0x41, 0xE2, 0x01, 0x00], [0xE9, 0x44], &mut seq);
+    }
+
+    let mut controller = mocks.into_controller();
+    controller.set_spi_crc(true);
+
+    assert_eq!(CanError::CrcMismatch, controller.read_time_base().unwrap_err());
+}
+
+#[test]
+fn test_write_crc() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // WRITE_SAFE of filter control byte
+    mocks.expect_write([0xC1, 0xD0, 0x00, 0xE1, 0x18], &mut seq);
+
+    // WRITE_CRC of bus diagnostic registers
+    mocks.expect_write([0xA0, 0x38, 0x04, 0x00, 0x00, 0x00, 0x00, 0xB3, 0xD9], &mut seq);
+    mocks.expect_write([0xA0, 0x3C, 0x04, 0x00, 0x00, 0x00, 0x00, 0x53, 0xDF], &mut seq);
+
+    let mut controller = mocks.into_controller();
+    controller.set_spi_crc(true);
+
+    controller.disable_filter(0).unwrap();
+    controller.clear_bus_diagnostics().unwrap();
+}
+
+#[test]
+fn test_read_interrupts() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    mocks.expect_read_transaction(
+        [0x30, 0x18],
+        [
+            0x01, 0x00, 0x00, 0x00, 0x02, 0x40, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ],
+        &mut seq,
+    );
+
+    let interrupts = mocks.into_controller().read_interrupts().unwrap();
+
+    assert_eq!(Some(Interrupt::Receive(Fifo::RX)), interrupts.highest());
+    assert_eq!(
+        vec![Interrupt::Receive(Fifo::RX), Interrupt::WakeUp],
+        interrupts.iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_clear_interrupt() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    mocks.expect_register_write([0x20, 0x1D, 0b1011_1111], &mut seq);
+    mocks.expect_register_write([0x20, 0x1C, 0b1111_0111], &mut seq);
+    mocks.expect_register_write([0x2E, 0x0A, 0x00], &mut seq);
+
+    let mut controller = mocks.into_controller();
+    controller.clear_interrupt(Interrupt::WakeUp).unwrap();
+    controller.clear_interrupt(Interrupt::ModeChange).unwrap();
+    controller.clear_interrupt(Interrupt::SpiCrc).unwrap();
+    controller.clear_interrupt(Interrupt::Receive(Fifo::RX)).unwrap();
+}
+
+#[test]
+fn test_clear_interrupt_receive_overflow() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // FIFO 1 and 4 overflowed
+    mocks.mock_read32::<0b1_0010>([0x30, 0x28], &mut seq);
+
+    mocks.expect_register_write([0x20, 0x60, 0b1111_0111], &mut seq);
+    mocks.expect_register_write([0x20, 0x84, 0b1111_0111], &mut seq);
+
+    mocks.into_controller().clear_interrupt(Interrupt::ReceiveOverflow).unwrap();
+}
+
+#[test]
+fn test_read_time_base() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    mocks.mock_read32::<0x0001_E240>([0x30, 0x10], &mut seq);
+
+    assert_eq!(123_456, mocks.into_controller().read_time_base().unwrap());
+}
+
+#[test]
+fn test_transmit_timeout() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // TX FIFO remains full, not in bus-off state
+    for _ in 0..2 {
+        mocks.mock_register_read::<0b0000_0000>([0x30, 0x6C], &mut seq);
+        mocks.mock_register_read::<0b0000_0000>([0x30, 0x36], &mut seq);
+    }
+
+    // Timeout of 5 ms is exceeded on second check
+    let clock = TestClock::new(vec![1_000, 2_000, 6_001]);
+    let mut controller = MCP2517::with_timeout(mocks.device, clock, Milliseconds::new(5));
+
+    let result = controller.transmit(&standard_message(), true);
+    assert_eq!(CanError::TransmitTimeout, result.unwrap_err());
+}
+
+#[test]
+fn test_receive_timeout() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // RX FIFO remains empty
+    for _ in 0..2 {
+        mocks.mock_register_read::<0b0000_0000>([0x30, 0x60], &mut seq);
+    }
+
+    // Timeout of 5 ms is exceeded on second check
+    let clock = TestClock::new(vec![1_000, 2_000, 6_001]);
+    let mut controller = MCP2517::with_timeout(mocks.device, clock, Milliseconds::new(5));
+
+    let result = controller.receive::<8>(true);
+    assert_eq!(CanError::ReceiveTimeout, result.unwrap_err());
+}
+
+#[test]
+fn test_receive() {
+    let mut mocks = Mocks::default();
+
+    let mut seq = Sequence::new();
+
+    // status register read (wait till fifo not empty flag is set)
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x60], &mut seq);
+
+    // status register read (fifo not empty flag is set)
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x60], &mut seq);
+
+    // user address register read
+    mocks.mock_read32::<0x00_00_04_7C>([0x30, 0x64], &mut seq);
+
+    // Message read from RAM address (0x47C) to read received message object (header + payload)
+    // Header: extended ID, DLC 6, FD frame with bit rate switch, filter hit 3
+    let header = [0x32, 0x5D, 0x51, 0x09, 0xD6, 0x18, 0x0, 0x0];
+    mocks.expect_fifo_read_transaction([0x38, 0x7C], header, [1, 2, 3, 4, 5, 6, 7, 8], &mut seq);
+
+    mocks.expect_register_write([0x20, 0x5D, 0b0000_0001], &mut seq);
+
+    let message = mocks.into_controller().receive::<8>(true).unwrap();
+
+    assert_eq!(message.id(), Id::Extended(ExtendedId::new(EXTENDED_ID).unwrap()));
+    assert_eq!(message.dlc(), DLC::Six);
+    assert!(message.is_fd_frame());
+    assert!(message.is_bit_rate_switched());
+    assert!(!message.is_error_passive());
+    assert_eq!(message.filter_hit(), 3);
+    assert_eq!(message.get_payload(), [1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn test_receive_standard_id() {
+    let mut mocks = Mocks::default();
+
+    let mut seq = Sequence::new();
+
+    // status register read (fifo not empty flag is set)
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x60], &mut seq);
+
+    // user address register read
+    mocks.mock_read32::<0x00_00_04_7C>([0x30, 0x64], &mut seq);
+
+    // Header: standard ID, DLC 8, CAN 2.0 frame, filter hit 0
+    let header = [0xA5, 0x06, 0x0, 0x0, 0x08, 0x0, 0x0, 0x0];
+    mocks.expect_fifo_read_transaction([0x38, 0x7C], header, [1, 2, 3, 4, 5, 6, 7, 8], &mut seq);
+
+    mocks.expect_register_write([0x20, 0x5D, 0b0000_0001], &mut seq);
+
+    let message = mocks.into_controller().receive::<8>(true).unwrap();
+
+    assert_eq!(message.id(), Id::Standard(StandardId::new(STANDARD_ID).unwrap()));
+    assert_eq!(message.dlc(), DLC::Eight);
+    assert!(!message.is_fd_frame());
+    assert_eq!(message.filter_hit(), 0);
+    assert_eq!(message.get_payload(), [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn test_receive_fifo() {
+    let mut mocks = Mocks::default();
+
+    let mut seq = Sequence::new();
+
+    // FIFO 5 status register read (fifo not empty flag is set)
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x90], &mut seq);
+
+    // FIFO 5 user address register read
+    mocks.mock_read32::<0x00_00_06_00>([0x30, 0x94], &mut seq);
+
+    let header = [0xA5, 0x06, 0x0, 0x0, 0x04, 0x0, 0x0, 0x0];
+    mocks.expect_fifo_read_transaction([0x3A, 0x00], header, [1, 2, 3, 4, 0, 0, 0, 0], &mut seq);
+
+    // Increment FIFO 5 head
+    mocks.expect_register_write([0x20, 0x8D, 0b0000_0001], &mut seq);
+
+    let message = mocks.into_controller().receive_fifo::<8>(Fifo::new(5).unwrap(), true).unwrap();
+
+    assert_eq!(message.id(), Id::Standard(StandardId::new(STANDARD_ID).unwrap()));
+    assert_eq!(message.get_payload(), [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_receive_fifo_empty() {
+    let mut mocks = Mocks::default();
+
+    let mut seq = Sequence::new();
+
+    // status register read (fifo not empty flag is not set)
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x60], &mut seq);
+
+    let result = mocks.into_controller().receive::<8>(false);
+
+    assert_eq!(result.unwrap_err(), CanError::RxFifoEmptyErr);
+}
+
+#[test]
+fn test_transmit_fifo_full() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+    let payload: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+    let payload_bytes = &payload;
+
+    let msg_type = Can20::<8> {};
+
+    let identifier = ExtendedId::new(EXTENDED_ID).unwrap();
+    let tx_message = TxMessage::new(msg_type, payload_bytes, Id::Extended(identifier)).unwrap();
+
+    // mock fifo status register read byte 0 (1st attempt) -> tx fifo full
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x6C], &mut seq);
+
+    let res = mocks.into_controller().transmit(&tx_message, false);
+
+    assert_eq!(res.unwrap_err(), CanError::TxFifoFullErr);
+}
+
+#[test]
+fn test_abort_fifo() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // Clear TXREQ of FIFO 2
+    mocks.expect_register_write([0x20, 0x69, 0x00], &mut seq);
+    mocks.mock_register_read::<0b0000_0010>([0x30, 0x69], &mut seq);
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x69], &mut seq);
+
+    // FIFO 2 status register -> TXABT
+    mocks.mock_register_read::<0b1000_0000>([0x30, 0x6C], &mut seq);
+
+    // Reset FIFO 2
+    mocks.expect_register_write([0x20, 0x69, 0x04], &mut seq);
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x69], &mut seq);
+
+    let clock = TestClock::new(vec![1_000, 2_000, 3_000]);
+    assert!(mocks.into_controller().abort_fifo(Fifo::TX, &clock).unwrap());
+}
+
+#[test]
+fn test_abort_fifo_not_pending() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    mocks.expect_register_write([0x20, 0x69, 0x00], &mut seq);
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x69], &mut seq);
+
+    // FIFO 2 status register -> no TXABT
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x6C], &mut seq);
+
+    let clock = TestClock::new(vec![1_000]);
+    assert!(!mocks.into_controller().abort_fifo(Fifo::TX, &clock).unwrap());
+}
+
+#[test]
+fn test_abort_fifo_timeout() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // TXREQ of FIFO 2 remains set
+    mocks.expect_register_write([0x20, 0x69, 0x00], &mut seq);
+    for _ in 0..2 {
+        mocks.mock_register_read::<0b0000_0010>([0x30, 0x69], &mut seq);
+    }
+
+    // Timeout of 100 ms is exceeded on second check, even if the driver has no timeout configured
+    let clock = TestClock::new(vec![1_000, 2_000, 101_001]);
+
+    assert_eq!(
+        CanError::AbortTimeout,
+        mocks.into_controller().abort_fifo(Fifo::TX, &clock).unwrap_err()
+    );
+}
+
+#[test]
+fn test_abort_fifo_reset_timeout() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    mocks.expect_register_write([0x20, 0x69, 0x00], &mut seq);
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x69], &mut seq);
+    mocks.mock_register_read::<0b1000_0000>([0x30, 0x6C], &mut seq);
+
+    // FRESET of FIFO 2 remains set
+    mocks.expect_register_write([0x20, 0x69, 0x04], &mut seq);
+    mocks.mock_register_read::<0b0000_0100>([0x30, 0x69], &mut seq);
+
+    let clock = TestClock::new(vec![1_000, 101_001]);
+
+    assert_eq!(
+        CanError::AbortTimeout,
+        mocks.into_controller().abort_fifo(Fifo::TX, &clock).unwrap_err()
+    );
+}
+
+#[test]
+fn test_abort_all() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // TXREQ register -> TXQ and FIFO 2 pending
+    mocks.mock_read32::<0b0000_0101>([0x30, 0x30], &mut seq);
+
+    // C1CON byte 3 -> TXBWS and normal CAN 2.0 mode
+    mocks.mock_register_read::<0x16>([0x30, 0x03], &mut seq);
+
+    // Set ABAT, which is cleared by the controller once the abort is completed
+    mocks.expect_register_write([0x20, 0x03, 0x1E], &mut seq);
+    mocks.mock_register_read::<0x1E>([0x30, 0x03], &mut seq);
+    mocks.mock_register_read::<0x16>([0x30, 0x03], &mut seq);
+
+    // TXQ status register -> TXABT
+    mocks.mock_register_read::<0b1000_0000>([0x30, 0x54], &mut seq);
+    mocks.expect_register_write([0x20, 0x51, 0x04], &mut seq);
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x51], &mut seq);
+
+    // FIFO 2 status register -> transmitted before abort
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x6C], &mut seq);
+
+    let clock = TestClock::new(vec![1_000, 2_000, 3_000, 4_000]);
+    let aborted = mocks.into_controller().abort_all(&clock).unwrap();
 
-    for chunk in cmd_and_header_buffer[2..].chunks_exact_mut(4) {
-        let num = BigEndian::read_u32(chunk);
-        LittleEndian::write_u32(chunk, num);
-    }
+    assert!(aborted.txq());
+    assert!(!aborted.contains(Fifo::TX));
+    assert_eq!(0, aborted.fifos().count());
+    assert!(!aborted.is_empty());
+}
 
-    mocks.expect_fifo_write_transaction(cmd_and_header_buffer, payload, &mut seq);
+#[test]
+fn test_abort_all_timeout() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
 
-    mocks.expect_register_write([0x20, 0x69, 0x03], &mut seq);
+    mocks.mock_read32::<0b0000_0100>([0x30, 0x30], &mut seq);
+    mocks.mock_register_read::<0x16>([0x30, 0x03], &mut seq);
 
-    // mock reading of fifo control register
-    // 1st attempt -> txreq still set ->not all messages inside tx fifo have been transmitted
-    mocks.mock_register_read::<0x02>([0x30, 0x69], &mut seq);
-    // 2nd attempt -> txreq cleared -> all messages inside tx fifo have been transmitted
-    mocks.mock_register_read::<0x00>([0x30, 0x69], &mut seq);
+    // ABAT remains set
+    mocks.expect_register_write([0x20, 0x03, 0x1E], &mut seq);
+    for _ in 0..2 {
+        mocks.mock_register_read::<0x1E>([0x30, 0x03], &mut seq);
+    }
 
-    mocks.into_controller().transmit(&tx_message_copy, true).unwrap();
+    let clock = TestClock::new(vec![1_000, 2_000, 101_001]);
+
+    assert_eq!(
+        CanError::AbortTimeout,
+        mocks.into_controller().abort_all(&clock).unwrap_err()
+    );
 }
 
 #[test]
-fn test_read_fifo_invalid_payload_buffer_size() {
-    let mocks = Mocks::default();
-    let mut buff = [0u8; 3];
+fn test_transmit_bus_off_automatic_recovery() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
 
-    let result = mocks.into_controller().read_fifo(0x123, &mut buff);
-    assert_eq!(result.unwrap_err(), CanError::InvalidBufferSize(3));
+    // TX FIFO full
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x6C], &mut seq);
+
+    // Bus-off state
+    mocks.mock_register_read::<0b0010_0000>([0x30, 0x36], &mut seq);
+
+    // CiBDIAG1 -> NACKERR
+    mocks.mock_read32::<0x00_04_00_00>([0x30, 0x3C], &mut seq);
+
+    let res = mocks.into_controller().transmit(&standard_message(), true);
+
+    assert_eq!(res.unwrap_err(), CanError::BusOff(Some(ErrorKind::Acknowledge)));
 }
 
 #[test]
-fn test_receive() {
+fn test_transmit_bus_off_manual_recovery() {
+    let clock = TestClock::new(vec![100, 200, 10_000, 10_100, 20_000, 20_100]);
     let mut mocks = Mocks::default();
-
     let mut seq = Sequence::new();
 
-    let mut message_buff = [0u8; 8];
+    let config = Configuration {
+        bus_off_recovery: BusOffRecovery::Manual,
+        ..Default::default()
+    };
+    expect_configure(&mut mocks, &config, &mut seq);
 
-    // status register read (wait till fifo not empty flag is set)
-    mocks.mock_register_read::<0b0000_0000>([0x30, 0x60], &mut seq);
+    // TX FIFO full
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x6C], &mut seq);
+    mocks.mock_register_read::<0b0010_0000>([0x30, 0x36], &mut seq);
+    mocks.mock_read32::<0x00_00_00_00>([0x30, 0x3C], &mut seq);
 
-    // status register read (fifo not empty flag is set)
-    mocks.mock_register_read::<0b0000_0001>([0x30, 0x60], &mut seq);
+    // Request configuration mode
+    mocks.expect_register_write([0x20, 0x3, 0b0000_1100], &mut seq);
 
-    // user address register read
-    mocks.mock_read32::<0x00_00_04_7C>([0x30, 0x64], &mut seq);
+    // Recovery: request normal CAN FD mode
+    mocks.expect_register_write([0x20, 0x3, 0b0000_1000], &mut seq);
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x2], &mut seq);
 
-    // Message read from RAM address (0x47C+8) to start reading received message object payload
-    // transfer cmd+address
-    mocks.expect_fifo_read_transaction([0x38, 0x84], [1, 2, 3, 4, 5, 6, 7, 8], &mut seq);
+    let mut controller = mocks.into_controller();
+    controller.configure(&config, &clock).unwrap();
 
-    mocks.expect_register_write([0x20, 0x5D, 0b0000_0001], &mut seq);
+    let res = controller.transmit(&standard_message(), true);
+    assert_eq!(res.unwrap_err(), CanError::BusOff(None));
 
-    let result = mocks.into_controller().receive(&mut message_buff, true);
+    controller.recover_bus_off(&clock).unwrap();
+}
 
-    assert!(result.is_ok());
+#[test]
+fn test_configure_ecc() {
+    let clock = TestClock::new(vec![100, 200, 300, 400]);
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    let config = Configuration {
+        ecc: Some(EccConfiguration {
+            single_error_interrupt: false,
+            double_error_interrupt: true,
+        }),
+        ..Default::default()
+    };
+    expect_configure(&mut mocks, &config, &mut seq);
 
-    assert_eq!(message_buff, [1, 2, 3, 4, 5, 6, 7, 8]);
+    mocks.into_controller().configure(&config, &clock).unwrap();
 }
 
 #[test]
-fn test_receive_fifo_empty() {
+fn test_read_ecc_status() {
     let mut mocks = Mocks::default();
-
     let mut seq = Sequence::new();
 
-    let mut message_buff = [0u8; 8];
-
-    // status register read (fifo not empty flag is not set)
-    mocks.mock_register_read::<0b0000_0000>([0x30, 0x60], &mut seq);
+    // double bit error at address 0x524
+    mocks.mock_read32::<0x0524_0004>([0x3E, 0x10], &mut seq);
 
-    let result = mocks.into_controller().receive(&mut message_buff, false);
+    let status = mocks.into_controller().read_ecc_status().unwrap();
 
-    assert_eq!(result.unwrap_err(), CanError::RxFifoEmptyErr);
+    assert!(!status.single_error);
+    assert!(status.double_error);
+    assert_eq!(0x524, status.error_address);
 }
 
 #[test]
-fn test_transmit_fifo_full() {
+fn test_inject_ecc_error() {
     let mut mocks = Mocks::default();
     let mut seq = Sequence::new();
-    let payload: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
-    let payload_bytes = Bytes::copy_from_slice(&payload);
 
-    let msg_type = Can20::<8> {};
+    // ECC enabled with SEC and DED interrupts
+    mocks.mock_read32::<0x0000_0007>([0x3E, 0x0C], &mut seq);
 
-    let identifier = ExtendedId::new(EXTENDED_ID).unwrap();
-    let tx_message = TxMessage::new(msg_type, payload_bytes, Id::Extended(identifier)).unwrap();
+    // ECC disabled, parity bits set
+    mocks.mock_write32([0x2E, 0x0C, 0x06, 0x15, 0x00, 0x00], &mut seq);
+    mocks.mock_write32([0x24, 0x00, 0x78, 0x56, 0x34, 0x12], &mut seq);
 
-    // mock fifo status register read byte 0 (1st attempt) -> tx fifo full
-    mocks.mock_register_read::<0b0000_0000>([0x30, 0x6C], &mut seq);
+    // ECC configuration restored
+    mocks.mock_write32([0x2E, 0x0C, 0x07, 0x00, 0x00, 0x00], &mut seq);
 
-    let res = mocks.into_controller().transmit(&tx_message, false);
+    mocks.into_controller().inject_ecc_error(0x400, 0x1234_5678, 0x15).unwrap();
+}
 
-    assert_eq!(res.unwrap_err(), CanError::TxFifoFullErr);
+#[test]
+fn test_inject_ecc_error_invalid_address() {
+    let result = Mocks::default().into_controller().inject_ecc_error(0x3FC, 0x0, 0x0);
+
+    assert_eq!(CanError::InvalidRamAddress(0x3FC), result.unwrap_err());
+}
+
+#[test]
+fn test_read_error_state() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // TEC 128, REC 5, error warning, TX warning, TX error passive
+    mocks.mock_read32::<0x0015_8005>([0x30, 0x34], &mut seq);
+
+    let state = mocks.into_controller().read_error_state().unwrap();
+
+    assert_eq!(128, state.tec);
+    assert_eq!(5, state.rec);
+    assert_eq!(BusState::ErrorPassive, state.bus_state);
+    assert!(state.tx_warning);
+    assert!(!state.rx_warning);
 }
 
 #[test]
@@ -407,6 +1429,7 @@ fn test_request_mode_timeout() {
                     clock_output: ClockOutputDivisor::DivideBy10,
                     system_clock: SystemClockDivisor::DivideBy1,
                     disable_clock: false,
+                    low_power_mode: false,
                     pll: PLLSetting::TenTimesPLL,
                 },
                 fifo: FifoConfiguration {
@@ -417,8 +1440,18 @@ fn test_request_mode_timeout() {
                     tx_size: 20,
                     tx_enable: true,
                 },
+                fifo_table: FifoTable::default(),
+                txq: None,
+                tef: None,
+                timestamp: None,
+                interrupts: InterruptConfiguration::default(),
+                io: IoConfiguration::default(),
+                ecc: None,
+                wake_up_filter: None,
+                bus_off_recovery: BusOffRecovery::Automatic,
                 mode: RequestMode::NormalCANFD,
                 bit_rate: BitRateConfig::default(),
+                data_bit_rate: DataBitRateConfig::default(),
             },
             &clock,
         )
@@ -494,6 +1527,186 @@ fn test_read_oscillator_transfer_error() {
     }
 }
 
+#[test]
+fn test_enter_sleep() {
+    let clock = TestClock::new(vec![100, 200]);
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // Request sleep mode
+    mocks.expect_register_write([0x20, 0x3, 0b0000_1001], &mut seq);
+    mocks.mock_register_read::<0b0010_0000>([0x30, 0x2], &mut seq);
+
+    mocks.into_controller().enter_sleep(&clock).unwrap();
+}
+
+#[test]
+fn test_enter_low_power_mode() {
+    let clock = TestClock::new(vec![100, 200, 10_000, 10_100]);
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    let config = Configuration {
+        clock: ClockConfiguration {
+            low_power_mode: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    expect_configure(&mut mocks, &config, &mut seq);
+
+    // Mode is not verified, as SPI is not available in LPM
+    mocks.expect_register_write([0x20, 0x3, 0b0000_1001], &mut seq);
+
+    let mut controller = mocks.into_controller();
+    controller.set_variant(ChipVariant::MCP2518FD);
+    controller.configure(&config, &clock).unwrap();
+    controller.enter_sleep(&clock).unwrap();
+}
+
+#[test]
+fn test_configure_low_power_mode_unsupported() {
+    let clock = TestClock::new(vec![]);
+    let config = Configuration {
+        clock: ClockConfiguration {
+            low_power_mode: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let mut controller = Mocks::default().into_controller();
+    assert_eq!(ChipVariant::MCP2517FD, controller.variant());
+    assert_eq!(
+        CanError::LowPowerModeUnsupported,
+        controller.configure(&config, &clock).unwrap_err()
+    );
+}
+
+#[test]
+fn test_detect_variant() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // LPMEN is retained -> MCP2518FD
+    mocks.mock_register_read::<0x60>([0x3E, 0x00], &mut seq);
+    mocks.expect_register_write([0x2E, 0x00, 0x68], &mut seq);
+    mocks.mock_register_read::<0x68>([0x3E, 0x00], &mut seq);
+    mocks.expect_register_write([0x2E, 0x00, 0x60], &mut seq);
+
+    // LPMEN is read as zero -> MCP2517FD
+    mocks.mock_register_read::<0x60>([0x3E, 0x00], &mut seq);
+    mocks.expect_register_write([0x2E, 0x00, 0x68], &mut seq);
+    mocks.mock_register_read::<0x60>([0x3E, 0x00], &mut seq);
+    mocks.expect_register_write([0x2E, 0x00, 0x60], &mut seq);
+
+    let mut controller = mocks.into_controller();
+    assert_eq!(ChipVariant::MCP2518FD, controller.detect_variant().unwrap());
+    assert_eq!(ChipVariant::MCP2518FD, controller.variant());
+
+    assert_eq!(ChipVariant::MCP2517FD, controller.detect_variant().unwrap());
+    assert_eq!(ChipVariant::MCP2517FD, controller.variant());
+}
+
+#[test]
+fn test_detect_variant_keeps_mcp251863() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    mocks.mock_register_read::<0x60>([0x3E, 0x00], &mut seq);
+    mocks.expect_register_write([0x2E, 0x00, 0x68], &mut seq);
+    mocks.mock_register_read::<0x68>([0x3E, 0x00], &mut seq);
+    mocks.expect_register_write([0x2E, 0x00, 0x60], &mut seq);
+
+    let mut controller = mocks.into_controller();
+    controller.set_variant(ChipVariant::MCP251863);
+
+    assert_eq!(ChipVariant::MCP251863, controller.detect_variant().unwrap());
+}
+
+#[test]
+fn test_read_device_id() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    mocks.mock_register_read::<0x13>([0x3E, 0x14], &mut seq);
+    mocks.mock_register_read::<0x52>([0x3E, 0x14], &mut seq);
+
+    let mut controller = mocks.into_controller();
+    let device_id = controller.read_device_id().unwrap();
+    assert_eq!(ChipVariant::MCP2518FD, device_id.variant);
+    assert_eq!(3, device_id.revision);
+
+    assert_eq!(CanError::UnknownDevice(0x52), controller.read_device_id().unwrap_err());
+    assert_eq!(ChipVariant::MCP2517FD, controller.variant());
+}
+
+#[test]
+fn test_wake_by_bus_activity() {
+    let clock = TestClock::new(vec![100, 200, 300, 400]);
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // Enable oscillator
+    mocks.expect_register_write([0x2E, 0x0, 0b0000_0000], &mut seq);
+
+    // Oscillator not ready on first check
+    mocks.mock_register_read::<0b0000_0000>([0x3E, 0x1], &mut seq);
+    mocks.mock_register_read::<0b0000_0100>([0x3E, 0x1], &mut seq);
+
+    // WAKIF set and cleared
+    mocks.mock_register_read::<0b0100_0000>([0x30, 0x1D], &mut seq);
+    mocks.expect_register_write([0x20, 0x1D, 0b1011_1111], &mut seq);
+
+    // Request normal CAN FD mode
+    mocks.expect_register_write([0x20, 0x3, 0b0000_1000], &mut seq);
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x2], &mut seq);
+
+    assert!(mocks.into_controller().wake(&clock).unwrap());
+}
+
+#[test]
+fn test_wake_oscillator_timeout() {
+    let clock = TestClock::new(vec![100, 2_101]);
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    mocks.expect_register_write([0x2E, 0x0, 0b0000_0000], &mut seq);
+    mocks.mock_register_read::<0b0000_0000>([0x3E, 0x1], &mut seq);
+
+    let result = mocks.into_controller().wake(&clock);
+    assert_eq!(CanError::OscillatorTimeout, result.unwrap_err());
+}
+
+#[test]
+fn test_set_gpio() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    mocks.mock_register_read::<0b0000_0001>([0x3E, 0x05], &mut seq);
+    mocks.expect_register_write([0x2E, 0x05, 0b0000_0011], &mut seq);
+
+    mocks.mock_register_read::<0b0000_0011>([0x3E, 0x05], &mut seq);
+    mocks.expect_register_write([0x2E, 0x05, 0b0000_0010], &mut seq);
+
+    let mut controller = mocks.into_controller();
+    controller.set_gpio(GpioPin::Gpio1, true).unwrap();
+    controller.set_gpio(GpioPin::Gpio0, false).unwrap();
+}
+
+#[test]
+fn test_read_gpio() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    mocks.mock_register_read::<0b0000_0010>([0x3E, 0x06], &mut seq);
+    mocks.mock_register_read::<0b0000_0010>([0x3E, 0x06], &mut seq);
+
+    let mut controller = mocks.into_controller();
+    assert!(!controller.read_gpio(GpioPin::Gpio0).unwrap());
+    assert!(controller.read_gpio(GpioPin::Gpio1).unwrap());
+}
+
 #[test]
 fn test_read_clock_configuration_correct() {
     let mut mocks = Mocks::default();
@@ -552,6 +1765,41 @@ fn test_filter_disable() {
     assert!(result.is_ok());
 }
 
+/// Mocks a successful configuration using the given settings
+fn expect_configure(mocks: &mut Mocks, config: &Configuration, seq: &mut Sequence) {
+    // Request configuration mode
+    mocks.expect_register_write([0x20, 0x3, 0b0000_1100], seq);
+    mocks.mock_register_read::<0b1001_0100>([0x30, 0x2], seq);
+
+    for write in config.register_writes().unwrap() {
+        match write {
+            RegisterWrite::Byte(register, value) => {
+                let [command_0, command_1] = RegisterOperation::Write.command(register);
+                mocks.expect_register_write([command_0, command_1, value], seq);
+            }
+            RegisterWrite::Word(register, value) => {
+                let [command_0, command_1] = RegisterOperation::Write.command(register);
+                let [value_0, value_1, value_2, value_3] = value.to_le_bytes();
+                mocks.mock_write32([command_0, command_1, value_0, value_1, value_2, value_3], seq);
+            }
+            RegisterWrite::InitializeRam => mocks.expect_ram_initialization(seq),
+        }
+    }
+
+    // Request operation mode
+    let mode = config.mode.to_operation_mode() as u8;
+    mocks.expect_register_write([0x20, 0x3, mode | 0b0000_1000], seq);
+    mocks.mock_register_read_value([0x30, 0x2], mode << 5, seq);
+}
+
+/// Returns a CAN 2.0 message with standard ID and 8 bytes payload
+fn standard_message() -> TxMessage<Can20<8>, 8> {
+    let identifier = StandardId::new(STANDARD_ID).unwrap();
+    let payload = &[1, 2, 3, 4, 5, 6, 7, 8];
+
+    TxMessage::new(Can20::<8> {}, payload, Id::Standard(identifier)).unwrap()
+}
+
 #[derive(Default, Debug, PartialEq)]
 pub(crate) struct Mocks {
     pub(crate) device: MockSPIDevice,
@@ -574,6 +1822,11 @@ impl Mocks {
 
     /// Mocks the reading of a single register byte
     pub fn mock_register_read<const REG: u8>(&mut self, expected_command: [u8; 2], seq: &mut Sequence) {
+        self.mock_register_read_value(expected_command, REG, seq);
+    }
+
+    /// Mocks the reading of a single register byte with the given value
+    pub fn mock_register_read_value(&mut self, expected_command: [u8; 2], value: u8, seq: &mut Sequence) {
         let expected_buffer = [expected_command[0], expected_command[1], 0x0];
 
         self.device
@@ -584,7 +1837,7 @@ impl Mocks {
                 match &mut operation[0] {
                     Operation::TransferInPlace(buff) => {
                         assert_eq!(expected_buffer, *buff);
-                        buff.copy_from_slice(&[0x0, 0x0, REG]);
+                        buff.copy_from_slice(&[0x0, 0x0, value]);
                     }
                     _ => panic!("unexpected operation {:?}", operation[0]),
                 }
@@ -660,6 +1913,81 @@ impl Mocks {
             .in_sequence(sequence);
     }
 
+    /// Mocks zeroing of the entire message RAM in blocks of 64 bytes
+    pub fn expect_ram_initialization(&mut self, seq: &mut Sequence) {
+        for address in (0x400u16..0xC00).step_by(64) {
+            let command = [0x20 | (address >> 8) as u8, address as u8];
+
+            self.device
+                .expect_transaction()
+                .times(1)
+                .returning(move |operation| {
+                    assert_eq!(operation.len(), 2);
+                    match operation[0] {
+                        Operation::Write(write) => assert_eq!(write, command),
+                        _ => panic!("Unexpected operation received {:?}", operation[0]),
+                    }
+                    match operation[1] {
+                        Operation::Write(write) => assert_eq!(write, [0u8; 64]),
+                        _ => panic!("Unexpected operation received {:?}", operation[1]),
+                    }
+                    Ok(())
+                })
+                .in_sequence(seq);
+        }
+    }
+
+    /// Mocks a single write operation
+    pub fn expect_write<const L: usize>(&mut self, expected_write: [u8; L], sequence: &mut Sequence) {
+        self.device
+            .expect_transaction()
+            .times(1)
+            .returning(move |operation| {
+                assert_eq!(operation.len(), 1);
+
+                match operation[0] {
+                    Operation::Write(write) => {
+                        assert_eq!(write, expected_write);
+                    }
+                    _ => panic!("Unexpected operation received {:?}", operation[0]),
+                }
+
+                Ok(())
+            })
+            .in_sequence(sequence);
+    }
+
+    /// Mocks a READ_CRC transaction
+    pub fn expect_crc_read_transaction<const L: usize>(
+        &mut self,
+        command: [u8; 3],
+        received: [u8; L],
+        crc: [u8; 2],
+        seq: &mut Sequence,
+    ) {
+        self.device
+            .expect_transaction()
+            .times(1)
+            .returning(move |operation| {
+                assert_eq!(operation.len(), 3);
+                match operation[0] {
+                    Operation::Write(write) => {
+                        assert_eq!(write, command);
+                    }
+                    _ => panic!("Unexpected operation received {:?}", operation[0]),
+                }
+
+                for (index, received) in [&received[..], &crc].into_iter().enumerate() {
+                    match &mut operation[index + 1] {
+                        Operation::Read(read) => read.copy_from_slice(received),
+                        _ => panic!("Unexpected operation received {:?}", operation[index + 1]),
+                    }
+                }
+                Ok(())
+            })
+            .in_sequence(seq);
+    }
+
     /// Mock write operation to TX FIFO
     pub fn expect_fifo_write_transaction<const L: usize>(
         &mut self,
@@ -689,10 +2017,73 @@ impl Mocks {
             .in_sequence(seq);
     }
 
+    /// Mock read operation of multiple registers
+    pub fn expect_read_transaction<const L: usize>(&mut self, command: [u8; 2], received: [u8; L], seq: &mut Sequence) {
+        self.device
+            .expect_transaction()
+            .times(1)
+            .returning(move |operation| {
+                assert_eq!(operation.len(), 2);
+                match operation[0] {
+                    Operation::Write(write) => {
+                        assert_eq!(write, command);
+                    }
+                    _ => panic!("Unexpected operation received {:?}", operation[0]),
+                }
+
+                match &mut operation[1] {
+                    Operation::Read(read) => {
+                        read.copy_from_slice(&received);
+                    }
+                    _ => panic!("Unexpected operation received {:?}", operation[1]),
+                }
+                Ok(())
+            })
+            .in_sequence(seq);
+    }
+
+    /// Mock read operation of RX FIFO with timestamp enabled
+    pub fn expect_fifo_read_transaction_with_timestamp<const L: usize>(
+        &mut self,
+        command: [u8; 2],
+        header_received: [u8; 8],
+        timestamp_received: [u8; 4],
+        payload_received: [u8; L],
+        seq: &mut Sequence,
+    ) {
+        self.device
+            .expect_transaction()
+            .times(1)
+            .returning(move |operation| {
+                assert_eq!(operation.len(), 4);
+                match operation[0] {
+                    Operation::Write(write) => {
+                        assert_eq!(write, command);
+                    }
+                    _ => panic!("Unexpected operation received {:?}", operation[0]),
+                }
+
+                for (index, received) in [&header_received[..], &timestamp_received, &payload_received]
+                    .into_iter()
+                    .enumerate()
+                {
+                    match &mut operation[index + 1] {
+                        Operation::Read(read) => {
+                            read.copy_from_slice(received);
+                        }
+                        _ => panic!("Unexpected operation received {:?}", operation[index + 1]),
+                    }
+                }
+                Ok(())
+            })
+            .in_sequence(seq);
+    }
+
     /// Mock read operation of RX FIFO
     pub fn expect_fifo_read_transaction<const L: usize>(
         &mut self,
         command: [u8; 2],
+        header_received: [u8; 8],
         payload_received: [u8; L],
         seq: &mut Sequence,
     ) {
@@ -700,7 +2091,7 @@ impl Mocks {
             .expect_transaction()
             .times(1)
             .returning(move |operation| {
-                assert_eq!(operation.len(), 2);
+                assert_eq!(operation.len(), 3);
                 match operation[0] {
                     Operation::Write(write) => {
                         assert_eq!(write, command);
@@ -709,10 +2100,17 @@ impl Mocks {
                 }
 
                 match &mut operation[1] {
+                    Operation::Read(read) => {
+                        read.copy_from_slice(&header_received);
+                    }
+                    _ => panic!("Unexpected operation received {:?}", operation[1]),
+                }
+
+                match &mut operation[2] {
                     Operation::Read(read) => {
                         read.copy_from_slice(&payload_received);
                     }
-                    _ => panic!("Unexpected operation received {:?}", operation[0]),
+                    _ => panic!("Unexpected operation received {:?}", operation[2]),
                 }
                 Ok(())
             })
@@ -735,6 +2133,7 @@ fn test_lib() {
                     clock_output: ClockOutputDivisor::DivideBy10,
                     system_clock: SystemClockDivisor::DivideBy1,
                     disable_clock: false,
+                    low_power_mode: false,
                     pll: PLLSetting::TenTimesPLL,
                 },
                 fifo: FifoConfiguration {
@@ -745,11 +2144,21 @@ fn test_lib() {
                     tx_size: 20,
                     tx_enable: true,
                 },
+                fifo_table: FifoTable::default(),
+                txq: None,
+                tef: None,
+                timestamp: None,
+                interrupts: InterruptConfiguration::default(),
+                io: IoConfiguration::default(),
+                ecc: None,
+                wake_up_filter: None,
+                bus_off_recovery: BusOffRecovery::Automatic,
                 mode: RequestMode::NormalCANFD,
                 bit_rate: BitRateConfig {
                     sys_clk: SysClk::MHz20,
                     can_speed: CanBaudRate::Kpbs500,
                 },
+                data_bit_rate: DataBitRateConfig::default(),
             },
             &clock,
         )
@@ -764,7 +2173,7 @@ fn test_lib() {
     // you can only use Can20::<8> as the message type
     let message_type = Can20::<8> {};
     let payload = [0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8];
-    let pl_bytes = Bytes::copy_from_slice(&payload);
+    let pl_bytes = &payload;
     let can_message = TxMessage::new(message_type, pl_bytes, can_id).unwrap();
 
     // Create and set filter object
@@ -775,8 +2184,7 @@ fn test_lib() {
     controller.transmit(&can_message, true).unwrap();
 
     // Receive CAN message in blocking mode
-    let mut buff = [0u8; 8];
-    let result = controller.receive(&mut buff, true);
-    assert!(result.is_ok());
-    assert_eq!(buff, [0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]);
+    let received = controller.receive::<8>(true).unwrap();
+    assert_eq!(received.id(), can_id);
+    assert_eq!(received.get_payload(), [0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]);
 }
@@ -0,0 +1,363 @@
+use crate::can::CanError;
+use crate::can_async::MCP2517Async;
+use crate::config::{
+    BitRateConfig, BusOffRecovery, ClockConfiguration, ClockOutputDivisor, Configuration, DataBitRateConfig, Fifo,
+    FifoConfiguration, FifoInterrupts, FifoSettings, FifoTable, InterruptConfiguration, IoConfiguration, PLLSetting,
+    PayloadSize, RequestMode, RetransmissionAttempts, SystemClockDivisor,
+};
+use crate::message::{Can20, TxMessage};
+use crate::mocks::{AsyncSPIDevice, TestClock, TestInterruptPin};
+use embassy_futures::block_on;
+use embedded_can::{Id, StandardId};
+
+fn into_controller(device: AsyncSPIDevice) -> MCP2517Async<AsyncSPIDevice, TestInterruptPin, TestClock> {
+    MCP2517Async::new(device, TestInterruptPin::default())
+}
+
+#[test]
+fn test_configure() {
+    let clock = TestClock::new(vec![100, 200, 10_000, 10_100]);
+    let mut device = AsyncSPIDevice::default();
+
+    // Request configuration mode
+    device.expect_register_write([0x20, 0x3, 0b0000_1100]);
+    device.expect_register_read([0x30, 0x2], 0b1001_0100);
+
+    // Shared configuration
+    device.expect_register_write([0x2E, 0x0, 0b0110_0001]);
+    device.expect(&[0x2E, 0x04, 0x03, 0x00, 0x00, 0x03], &[]);
+    device.expect(&[0x20, 0x04, 1, 15, 62, 0], &[]);
+    device.expect(&[0x20, 0x08, 3, 3, 14, 0], &[]);
+    device.expect(&[0x20, 0x0C, 0, 16, 2, 0], &[]);
+    device.expect_register_write([0x20, 0x01, 0b0000_0110]);
+    device.expect_register_write([0x20, 0x02, 0b0000_0001]);
+    device.expect(&[0x20, 0x1C, 0, 0, 0, 0], &[]);
+    device.expect_register_write([0x20, 0x5F, 0b0000_1111]);
+    device.expect_register_write([0x20, 0x6A, 0b0010_1010]);
+    device.expect_register_write([0x20, 0x6B, 0b0001_0011]);
+    device.expect_register_write([0x20, 0x68, 0b1000_0000]);
+    device.expect_register_write([0x21, 0xD0, 0x00]);
+    device.expect_register_write([0x21, 0xD0, 0x01]);
+    device.expect_register_write([0x21, 0xD0, 0b1000_0001]);
+
+    // RX FIFO not empty interrupt
+    device.expect_register_write([0x20, 0x5C, 0b0000_0001]);
+
+    // RXIE and TXIE
+    device.expect_register_write([0x20, 0x1E, 0b0000_0011]);
+
+    // Request normal CAN 2.0B mode
+    device.expect_register_write([0x20, 0x3, 0b0000_1110]);
+    device.expect_register_read([0x30, 0x2], 0b1100_0000);
+
+    let mut controller = into_controller(device);
+    let config = Configuration {
+        clock: ClockConfiguration {
+            clock_output: ClockOutputDivisor::DivideBy10,
+            system_clock: SystemClockDivisor::DivideBy1,
+            disable_clock: false,
+            low_power_mode: false,
+            pll: PLLSetting::TenTimesPLL,
+        },
+        fifo: FifoConfiguration {
+            rx_size: 16,
+            tx_attempts: RetransmissionAttempts::Three,
+            tx_priority: 10,
+            pl_size: PayloadSize::EightBytes,
+            tx_size: 20,
+            tx_enable: true,
+        },
+        fifo_table: FifoTable::default(),
+        txq: None,
+        tef: None,
+        timestamp: None,
+        interrupts: InterruptConfiguration::default(),
+        io: IoConfiguration::default(),
+        ecc: None,
+        wake_up_filter: None,
+        bus_off_recovery: BusOffRecovery::Automatic,
+        mode: RequestMode::NormalCAN2_0,
+        bit_rate: BitRateConfig::default(),
+        data_bit_rate: DataBitRateConfig::default(),
+    };
+    block_on(controller.configure(&config, &clock)).unwrap();
+}
+
+#[test]
+fn test_configure_keeps_rx_fifo_settings() {
+    let clock = TestClock::new(vec![100, 200, 10_000, 10_100]);
+    let mut device = AsyncSPIDevice::default();
+
+    // Request configuration mode
+    device.expect_register_write([0x20, 0x3, 0b0000_1100]);
+    device.expect_register_read([0x30, 0x2], 0b1001_0100);
+
+    // Shared configuration
+    device.expect_register_write([0x2E, 0x0, 0b0110_0001]);
+    device.expect(&[0x2E, 0x04, 0x03, 0x00, 0x00, 0x03], &[]);
+    device.expect(&[0x20, 0x04, 1, 15, 62, 0], &[]);
+    device.expect(&[0x20, 0x08, 3, 3, 14, 0], &[]);
+    device.expect(&[0x20, 0x0C, 0, 16, 2, 0], &[]);
+    device.expect_register_write([0x20, 0x01, 0b0000_0110]);
+    device.expect_register_write([0x20, 0x02, 0b0000_0001]);
+    device.expect(&[0x20, 0x1C, 0, 0, 0, 0], &[]);
+    device.expect_register_write([0x20, 0x6A, 0b0010_1010]);
+    device.expect_register_write([0x20, 0x6B, 0b0001_0011]);
+    device.expect_register_write([0x20, 0x68, 0b1000_0000]);
+
+    // RX FIFO: RXTSEN and RXOVIE of the FIFO table, TFNRFNIE enabled by the driver
+    device.expect(&[0x20, 0x5C, 0b0010_1001, 0x00, 0b0100_0000, 0b0000_1111], &[]);
+
+    device.expect_register_write([0x21, 0xD0, 0x00]);
+    device.expect_register_write([0x21, 0xD0, 0x01]);
+    device.expect_register_write([0x21, 0xD0, 0b1000_0001]);
+
+    // RXIE and TXIE
+    device.expect_register_write([0x20, 0x1E, 0b0000_0011]);
+
+    // Request normal CAN 2.0B mode
+    device.expect_register_write([0x20, 0x3, 0b0000_1110]);
+    device.expect_register_read([0x30, 0x2], 0b1100_0000);
+
+    let mut controller = into_controller(device);
+    let config = Configuration {
+        clock: ClockConfiguration {
+            clock_output: ClockOutputDivisor::DivideBy10,
+            system_clock: SystemClockDivisor::DivideBy1,
+            disable_clock: false,
+            low_power_mode: false,
+            pll: PLLSetting::TenTimesPLL,
+        },
+        fifo: FifoConfiguration {
+            rx_size: 16,
+            tx_attempts: RetransmissionAttempts::Three,
+            tx_priority: 10,
+            pl_size: PayloadSize::EightBytes,
+            tx_size: 20,
+            tx_enable: true,
+        },
+        fifo_table: FifoTable::default().with(
+            Fifo::RX,
+            FifoSettings {
+                size: 16,
+                timestamp: true,
+                interrupts: FifoInterrupts {
+                    overflow: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ),
+        txq: None,
+        tef: None,
+        timestamp: None,
+        interrupts: InterruptConfiguration::default(),
+        io: IoConfiguration::default(),
+        ecc: None,
+        wake_up_filter: None,
+        bus_off_recovery: BusOffRecovery::Automatic,
+        mode: RequestMode::NormalCAN2_0,
+        bit_rate: BitRateConfig::default(),
+        data_bit_rate: DataBitRateConfig::default(),
+    };
+    block_on(controller.configure(&config, &clock)).unwrap();
+}
+
+#[test]
+fn test_transmit_waits_for_fifo_space() {
+    let mut device = AsyncSPIDevice::default();
+
+    // TX FIFO full
+    device.expect_register_read([0x30, 0x6C], 0b0000_0000);
+
+    // Enable TX FIFO not full interrupt
+    device.expect_register_read([0x30, 0x68], 0b1000_0000);
+    device.expect_register_write([0x20, 0x68, 0b1000_0001]);
+
+    // Still full, waiting for interrupt
+    device.expect_register_read([0x30, 0x6C], 0b0000_0000);
+
+    // Not full anymore
+    device.expect_register_read([0x30, 0x6C], 0b0000_0001);
+
+    // Disable TX FIFO not full interrupt
+    device.expect_register_write([0x20, 0x68, 0b1000_0000]);
+
+    // Operation status read
+    device.expect_register_read([0x30, 0x2], 0b1100_0000);
+
+    // User address read
+    device.expect(&[0x30, 0x70], &[0xA2, 0x04, 0x00, 0x00]);
+
+    // Message write
+    let mut expected_write = vec![0x28, 0xA2, 0x55, 0x0, 0x0, 0x0, 0x8, 0x0, 0x0, 0x0];
+    expected_write.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    device.expect(&expected_write, &[]);
+
+    // Request transmission
+    device.expect_register_write([0x20, 0x69, 0x03]);
+
+    let pin = TestInterruptPin::default();
+    let waits = pin.waits.clone();
+    let mut controller: MCP2517Async<_, _, TestClock> = MCP2517Async::new(device, pin);
+
+    let id = Id::Standard(StandardId::new(0x55).unwrap());
+    let message = TxMessage::new(Can20::<8> {}, &[1, 2, 3, 4, 5, 6, 7, 8], id).unwrap();
+    block_on(controller.transmit(&message)).unwrap();
+
+    assert_eq!(1, waits.get());
+}
+
+#[test]
+fn test_flush_fifo_already_empty() {
+    let mut device = AsyncSPIDevice::default();
+
+    // TXREQ cleared
+    device.expect_register_read([0x30, 0x69], 0b0000_0000);
+
+    let mut controller = into_controller(device);
+    block_on(controller.flush()).unwrap();
+}
+
+#[test]
+fn test_flush_waits_for_fifo_empty() {
+    let mut device = AsyncSPIDevice::default();
+
+    // TXREQ set
+    device.expect_register_read([0x30, 0x69], 0b0000_0010);
+
+    // Enable TX FIFO empty interrupt
+    device.expect_register_read([0x30, 0x68], 0b1000_0000);
+    device.expect_register_write([0x20, 0x68, 0b1000_0100]);
+
+    // Not empty
+    device.expect_register_read([0x30, 0x6C], 0b0000_0001);
+
+    // Empty
+    device.expect_register_read([0x30, 0x6C], 0b0000_0101);
+
+    // Disable TX FIFO empty interrupt
+    device.expect_register_write([0x20, 0x68, 0b1000_0000]);
+
+    let pin = TestInterruptPin::default();
+    let waits = pin.waits.clone();
+    let mut controller: MCP2517Async<_, _, TestClock> = MCP2517Async::new(device, pin);
+    block_on(controller.flush()).unwrap();
+
+    assert_eq!(1, waits.get());
+}
+
+#[test]
+fn test_receive_waits_for_message() {
+    let mut device = AsyncSPIDevice::default();
+
+    // RX FIFO empty
+    device.expect_register_read([0x30, 0x60], 0b0000_0000);
+
+    // RX FIFO not empty
+    device.expect_register_read([0x30, 0x60], 0b0000_0001);
+
+    // User address read
+    device.expect(&[0x30, 0x64], &[0x7C, 0x04, 0x00, 0x00]);
+
+    // Message read
+    device.expect(
+        &[0x38, 0x7C],
+        &[0x55, 0x0, 0x0, 0x0, 0x4, 0x0, 0x0, 0x0, 1, 2, 3, 4, 0, 0, 0, 0],
+    );
+
+    // Increment FIFO head
+    device.expect_register_write([0x20, 0x5D, 0b0000_0001]);
+
+    let pin = TestInterruptPin::default();
+    let waits = pin.waits.clone();
+    let mut controller: MCP2517Async<_, _, TestClock> = MCP2517Async::new(device, pin);
+    let message = block_on(controller.receive::<8>()).unwrap();
+
+    assert_eq!(1, waits.get());
+    assert_eq!(message.id(), Id::Standard(StandardId::new(0x55).unwrap()));
+    assert_eq!(message.get_payload(), [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_receive_invalid_buffer_size() {
+    let mut device = AsyncSPIDevice::default();
+    device.expect_register_read([0x30, 0x60], 0b0000_0001);
+    device.expect(&[0x30, 0x64], &[0x7C, 0x04, 0x00, 0x00]);
+
+    let mut controller = into_controller(device);
+    let error = block_on(controller.receive::<7>()).unwrap_err();
+
+    assert!(matches!(error, CanError::InvalidBufferSize(7)));
+}
+
+#[test]
+fn test_transmit_crc() {
+    let mut device = AsyncSPIDevice::default();
+
+    // TX FIFO not full
+    device.expect(&[0xB0, 0x6C, 0x01], &[0b0000_0001, 0x41, 0x67]);
+
+    // Operation status read
+    device.expect(&[0xB0, 0x02, 0x01], &[0b1100_0000, 0x44, 0xB9]);
+
+    // User address read
+    device.expect(&[0xB0, 0x70, 0x04], &[0xA2, 0x04, 0x00, 0x00, 0xCA, 0xF7]);
+
+    // Message write, length in words for RAM access
+    let mut expected_write = vec![0xA8, 0xA2, 0x04, 0x55, 0x0, 0x0, 0x0, 0x8, 0x0, 0x0, 0x0];
+    expected_write.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 0x3D, 0x95]);
+    device.expect(&expected_write, &[]);
+
+    // Request transmission by WRITE_SAFE
+    device.expect(&[0xC0, 0x69, 0x03, 0x77, 0x0C], &[]);
+
+    let mut controller = into_controller(device);
+    controller.set_spi_crc(true);
+
+    let id = Id::Standard(StandardId::new(0x55).unwrap());
+    let message = TxMessage::new(Can20::<8> {}, &[1, 2, 3, 4, 5, 6, 7, 8], id).unwrap();
+    block_on(controller.transmit(&message)).unwrap();
+}
+
+#[test]
+fn test_receive_crc() {
+    let mut device = AsyncSPIDevice::default();
+
+    // RX FIFO not empty
+    device.expect(&[0xB0, 0x60, 0x01], &[0b0000_0001, 0x41, 0x97]);
+
+    // User address read
+    device.expect(&[0xB0, 0x64, 0x04], &[0x7C, 0x04, 0x00, 0x00, 0xB2, 0xC4]);
+
+    // Message read, length in words for RAM access
+    let mut received = vec![0xA5, 0x06, 0x0, 0x0, 0x08, 0x0, 0x0, 0x0];
+    received.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 0xC8, 0x97]);
+    device.expect(&[0xB8, 0x7C, 0x04], &received);
+
+    // Increment FIFO head by WRITE_SAFE
+    device.expect(&[0xC0, 0x5D, 0x01, 0xCF, 0x00], &[]);
+
+    let mut controller = into_controller(device);
+    controller.set_spi_crc(true);
+
+    let message = block_on(controller.receive::<8>()).unwrap();
+
+    assert_eq!(message.id(), Id::Standard(StandardId::new(0x6A5).unwrap()));
+    assert_eq!(message.get_payload(), [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn test_receive_crc_mismatch() {
+    let mut device = AsyncSPIDevice::default();
+
+    // RX FIFO status corrupted during transfer
+    device.expect(&[0xB0, 0x60, 0x01], &[0b0000_0001, 0xC1, 0x92]);
+
+    let mut controller = into_controller(device);
+    controller.set_spi_crc(true);
+
+    let error = block_on(controller.receive::<8>()).unwrap_err();
+
+    assert!(matches!(error, CanError::CrcMismatch));
+}
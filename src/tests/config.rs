@@ -1,8 +1,13 @@
+use crate::bit_timing::{BitTiming, BitTimingError};
 use crate::config::{
-    BitRateConfig, ClockConfiguration, ClockOutputDivisor, FifoConfiguration, PLLSetting, RetransmissionAttempts,
-    SystemClockDivisor,
+    BitRateConfig, CanBaudRate, ClockConfiguration, ClockOutputDivisor, Configuration, DataBaudRate, DataBitRateConfig,
+    EccConfiguration, Fifo, FifoConfiguration, FifoDirection, FifoInterrupts, FifoSettings, FifoTable,
+    InterruptConfiguration, IoConfiguration, OutputDriver, PLLSetting, PayloadSize, PinMode, RetransmissionAttempts,
+    SysClk, SystemClockDivisor, TdcMode, TefConfiguration, TimestampConfiguration, TimestampEdge, TxQueueConfiguration,
+    WakeUpFilterTime,
 };
-use crate::registers::C1NBTCFG;
+use crate::registers::{RegisterWrite, C1DBTCFG, C1NBTCFG, C1TDC};
+use embedded_time::duration::Nanoseconds;
 
 #[test]
 fn test_clock_from_register() {
@@ -35,6 +40,9 @@ fn test_clock_from_register() {
     assert!(ClockConfiguration::from_register(0b0011_0100).disable_clock);
     assert!(!ClockConfiguration::from_register(0b0011_0000).disable_clock);
 
+    assert!(ClockConfiguration::from_register(0b0000_1000).low_power_mode);
+    assert!(!ClockConfiguration::from_register(0b0011_0100).low_power_mode);
+
     assert_eq!(
         PLLSetting::TenTimesPLL,
         ClockConfiguration::from_register(0b0011_0101).pll
@@ -55,6 +63,7 @@ fn test_clock_configuration_to_register() {
             clock_output: ClockOutputDivisor::DivideBy4,
             system_clock: SystemClockDivisor::DivideBy2,
             disable_clock: false,
+            low_power_mode: false,
             pll: PLLSetting::TenTimesPLL
         }
         .as_register()
@@ -66,6 +75,7 @@ fn test_clock_configuration_to_register() {
             clock_output: ClockOutputDivisor::DivideBy10,
             system_clock: SystemClockDivisor::DivideBy1,
             disable_clock: true,
+            low_power_mode: false,
             pll: PLLSetting::DirectXTALOscillator
         }
         .as_register()
@@ -77,17 +87,29 @@ fn test_clock_configuration_to_register() {
             clock_output: ClockOutputDivisor::DivideBy1,
             system_clock: SystemClockDivisor::DivideBy1,
             disable_clock: true,
+            low_power_mode: false,
             pll: PLLSetting::DirectXTALOscillator
         }
         .as_register()
     );
 
+    assert_eq!(
+        0b0000_1001,
+        ClockConfiguration {
+            low_power_mode: true,
+            pll: PLLSetting::TenTimesPLL,
+            ..Default::default()
+        }
+        .as_register()
+    );
+
     assert_eq!(
         0b0010_0100,
         ClockConfiguration {
             clock_output: ClockOutputDivisor::DivideBy2,
             system_clock: SystemClockDivisor::DivideBy1,
             disable_clock: true,
+            low_power_mode: false,
             pll: PLLSetting::DirectXTALOscillator
         }
         .as_register()
@@ -196,6 +218,416 @@ fn test_bit_timing_config_correct() {
     assert_eq!(reg.sjw(), 1);
 }
 
+#[test]
+fn test_bit_timing_config_low_bit_rates() {
+    let timing = |can_speed| BitRateConfig {
+        sys_clk: SysClk::Mhz40,
+        can_speed,
+    };
+
+    assert_eq!([3, 158, 39, 1], timing(CanBaudRate::Kbps50).calculate_values());
+    assert_eq!([15, 198, 49, 1], timing(CanBaudRate::Kbps10).calculate_values());
+    assert_eq!([24, 254, 63, 1], timing(CanBaudRate::Kbps5).calculate_values());
+
+    assert_eq!(50_000, timing(CanBaudRate::Kbps50).timing().bit_rate());
+    assert_eq!(10_000, timing(CanBaudRate::Kbps10).timing().bit_rate());
+    assert_eq!(5_000, timing(CanBaudRate::Kbps5).timing().bit_rate());
+}
+
+#[test]
+fn test_bit_timing_config_custom() {
+    let config = BitRateConfig {
+        sys_clk: SysClk::MHz20,
+        can_speed: CanBaudRate::Custom(BitTiming::nominal(24_000_000, 83_333, 875, 1).unwrap()),
+    };
+
+    assert_eq!(83_333, config.timing().bit_rate());
+    assert_eq!(875, config.timing().sample_point());
+}
+
+#[test]
+fn test_data_bit_timing_config_correct() {
+    let config = DataBitRateConfig {
+        can_speed: DataBaudRate::Mbps2,
+        tdc: TdcMode::Automatic,
+    };
+    let reg = C1DBTCFG::from_bytes(config.calculate_values(40_000_000).unwrap());
+    assert_eq!(reg.brp(), 0x00);
+    assert_eq!(reg.tseg1(), 14);
+    assert_eq!(reg.tseg2(), 3);
+    assert_eq!(reg.sjw(), 3);
+    assert_eq!(0x000E_0303, u32::from(reg));
+}
+
+#[test]
+fn test_data_bit_timing_uses_nominal_sys_clk() {
+    let config = |sys_clk, can_speed| Configuration {
+        bit_rate: BitRateConfig { sys_clk, can_speed },
+        data_bit_rate: DataBitRateConfig {
+            can_speed: DataBaudRate::Mbps2,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let config_40mhz = config(SysClk::Mhz40, CanBaudRate::Kpbs500);
+    assert_eq!(40_000_000, config_40mhz.sys_clk());
+    assert_eq!(40_000_000, config_40mhz.data_bit_timing().unwrap().sys_clk());
+    assert_eq!([0, 14, 3, 3], config_40mhz.data_bit_timing().unwrap().register_values());
+
+    // SYSCLK of a custom nominal timing is used for the data phase
+    let custom = CanBaudRate::Custom(BitTiming::nominal(24_000_000, 500_000, 800, 2).unwrap());
+    let config_24mhz = config(SysClk::MHz20, custom);
+    let data_timing = config_24mhz.data_bit_timing().unwrap();
+    assert_eq!(24_000_000, config_24mhz.sys_clk());
+    assert_eq!(24_000_000, data_timing.sys_clk());
+    assert_eq!(2_000_000, data_timing.bit_rate());
+    assert_eq!(0, data_timing.error_ppm());
+}
+
+#[test]
+fn test_data_bit_timing_custom_sys_clk_mismatch() {
+    let config = DataBitRateConfig {
+        can_speed: DataBaudRate::Custom(BitTiming::data(20_000_000, 2_000_000, 800, 4).unwrap()),
+        ..Default::default()
+    };
+
+    assert!(config.timing(20_000_000).is_ok());
+    assert_eq!(BitTimingError::SysClkMismatch, config.timing(40_000_000).unwrap_err());
+
+    let config = Configuration {
+        bit_rate: BitRateConfig {
+            sys_clk: SysClk::Mhz40,
+            can_speed: CanBaudRate::Kpbs500,
+        },
+        data_bit_rate: config,
+        ..Default::default()
+    };
+    assert_eq!(BitTimingError::SysClkMismatch, config.register_writes().err().unwrap());
+}
+
+#[test]
+fn test_tdc_register_automatic() {
+    let config = DataBitRateConfig {
+        can_speed: DataBaudRate::Kbps500,
+        tdc: TdcMode::Automatic,
+    };
+    let reg = C1TDC::from(config.as_tdc_register(&config.timing(40_000_000).unwrap()));
+
+    assert_eq!(0b10, reg.tdcmod());
+    assert_eq!(63, reg.tdco());
+    assert_eq!(0, reg.tdcv());
+}
+
+#[test]
+fn test_tdc_register_manual() {
+    let config = DataBitRateConfig {
+        tdc: TdcMode::Manual { value: 12, offset: -2 },
+        ..Default::default()
+    };
+
+    assert_eq!(0x0001_7E0C, config.as_tdc_register(&config.timing(20_000_000).unwrap()));
+}
+
+#[test]
+fn test_tdc_register_disabled() {
+    let config = DataBitRateConfig {
+        tdc: TdcMode::Disabled,
+        ..Default::default()
+    };
+
+    assert_eq!(0x0, config.as_tdc_register(&config.timing(20_000_000).unwrap()));
+}
+
+#[test]
+fn test_fifo_handle() {
+    assert_eq!(None, Fifo::new(0));
+    assert_eq!(1, Fifo::new(1).unwrap().index());
+    assert_eq!(31, Fifo::new(31).unwrap().index());
+    assert_eq!(None, Fifo::new(32));
+}
+
+#[test]
+fn test_fifo_settings_as_control_register() {
+    let tx_settings = FifoSettings {
+        direction: FifoDirection::Transmit,
+        size: 40,
+        pl_size: PayloadSize::TwentyFourBytes,
+        priority: 40,
+        attempts: RetransmissionAttempts::Disabled,
+        timestamp: true,
+        auto_rtr: true,
+        interrupts: FifoInterrupts::default(),
+    };
+    assert_eq!(0x9F1F_00C0, tx_settings.as_control_register());
+
+    let tx_settings = FifoSettings {
+        interrupts: FifoInterrupts {
+            full_or_empty: true,
+            overflow: true,
+            attempts_exhausted: true,
+            ..Default::default()
+        },
+        ..tx_settings
+    };
+    assert_eq!(0x9F1F_00D4, tx_settings.as_control_register());
+
+    let rx_settings = FifoSettings {
+        size: 0,
+        timestamp: true,
+        auto_rtr: true,
+        ..Default::default()
+    };
+    assert_eq!(0x0040_0020, rx_settings.as_control_register());
+}
+
+#[test]
+fn test_fifo_table() {
+    let fifo = Fifo::new(7).unwrap();
+    let table = FifoTable::default().with(
+        fifo,
+        FifoSettings {
+            size: 3,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(3, table.get(fifo).unwrap().size);
+    assert!(table.get(Fifo::RX).is_none());
+    assert_eq!(vec![fifo], table.iter().map(|(fifo, _)| fifo).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_configuration_ram_usage() {
+    // 32 RX messages, 32 TX messages and 29 unconfigured FIFOs with 16 bytes each
+    assert_eq!(1488, Configuration::default().ram_usage());
+
+    let fifo_table = FifoTable::default()
+        .with(
+            Fifo::RX,
+            FifoSettings {
+                size: 10,
+                timestamp: true,
+                ..Default::default()
+            },
+        )
+        .with(
+            Fifo::new(3).unwrap(),
+            FifoSettings {
+                direction: FifoDirection::Transmit,
+                size: 2,
+                pl_size: PayloadSize::SixtyFourBytes,
+                timestamp: true,
+                ..Default::default()
+            },
+        );
+    let config = Configuration {
+        fifo_table,
+        ..Default::default()
+    };
+
+    assert_eq!(200 + 512 + 144 + 28 * 16, config.ram_usage());
+}
+
+#[test]
+fn test_txq_configuration() {
+    let txq = TxQueueConfiguration {
+        size: 4,
+        pl_size: PayloadSize::SixteenBytes,
+        priority: 20,
+        attempts: RetransmissionAttempts::Three,
+    };
+    assert_eq!(0x4334_0000, txq.as_control_register());
+
+    let config = Configuration {
+        txq: Some(txq),
+        ..Default::default()
+    };
+    assert_eq!(96 + 1488, config.ram_usage());
+
+    let writes: Vec<_> = config.register_writes().unwrap().collect();
+    assert!(writes.contains(&RegisterWrite::Byte(0x002, 0b0001_0001)));
+    assert!(writes.contains(&RegisterWrite::Word(0x050, 0x4334_0000)));
+}
+
+#[test]
+fn test_tef_configuration() {
+    let tef = TefConfiguration {
+        size: 12,
+        timestamp: true,
+    };
+    assert_eq!(0x0B00_0020, tef.as_control_register());
+
+    let config = Configuration {
+        tef: Some(tef),
+        ..Default::default()
+    };
+    assert_eq!(144 + 1488, config.ram_usage());
+
+    let writes: Vec<_> = config.register_writes().unwrap().collect();
+    assert!(writes.contains(&RegisterWrite::Byte(0x002, 0b0000_1001)));
+    assert!(writes.contains(&RegisterWrite::Word(0x040, 0x0B00_0020)));
+}
+
+#[test]
+fn test_ecc_configuration() {
+    let ecc = EccConfiguration {
+        single_error_interrupt: false,
+        double_error_interrupt: true,
+    };
+    assert_eq!(0b0000_0101, ecc.as_ecccon_register());
+
+    let config = Configuration {
+        ecc: Some(ecc),
+        ..Default::default()
+    };
+
+    let writes: Vec<_> = config.register_writes().unwrap().collect();
+    let ecc_write = writes
+        .iter()
+        .position(|write| *write == RegisterWrite::Word(0xE0C, 0b0000_0101));
+    let ram_write = writes.iter().position(|write| *write == RegisterWrite::InitializeRam);
+    assert_eq!(ecc_write.unwrap() + 1, ram_write.unwrap());
+
+    // RAM is not initialized without ECC
+    assert!(!Configuration::default()
+        .register_writes()
+        .unwrap()
+        .any(|write| write == RegisterWrite::InitializeRam));
+}
+
+#[test]
+fn test_timestamp_configuration() {
+    let timestamp = TimestampConfiguration {
+        prescaler: 40,
+        edge: TimestampEdge::EndOfFrame,
+    };
+    assert_eq!(0x0003_0027, timestamp.as_tscon_register());
+    assert_eq!(Nanoseconds::<u64>::new(1_000), timestamp.to_duration(40_000_000, 1));
+    assert_eq!(
+        Nanoseconds::<u64>::new(4_294_967_295_000),
+        timestamp.to_duration(40_000_000, u32::MAX)
+    );
+
+    let timestamp = TimestampConfiguration {
+        prescaler: 0,
+        edge: TimestampEdge::FdResBit,
+    };
+    assert_eq!(0x0005_0000, timestamp.as_tscon_register());
+    assert_eq!(Nanoseconds::<u64>::new(50), timestamp.to_duration(20_000_000, 1));
+
+    // SYSCLK which is not a multiple of 1 MHz
+    let slow_timestamp = TimestampConfiguration {
+        prescaler: 1024,
+        ..Default::default()
+    };
+    assert_eq!(
+        Nanoseconds::<u64>::new(41_795),
+        slow_timestamp.to_duration(24_500_000, 1)
+    );
+    assert_eq!(
+        Nanoseconds::<u64>::new(179_512_102_452_244),
+        slow_timestamp.to_duration(24_500_000, u32::MAX)
+    );
+
+    let fifo_table = FifoTable::default()
+        .with(
+            Fifo::new(4).unwrap(),
+            FifoSettings {
+                timestamp: true,
+                ..Default::default()
+            },
+        )
+        .with(
+            Fifo::new(5).unwrap(),
+            FifoSettings {
+                direction: FifoDirection::Transmit,
+                timestamp: true,
+                ..Default::default()
+            },
+        );
+    let config = Configuration {
+        fifo_table,
+        timestamp: Some(timestamp),
+        ..Default::default()
+    };
+    assert_eq!(0b1_0000, config.rx_timestamp_fifos());
+
+    let writes: Vec<_> = config.register_writes().unwrap().collect();
+    assert!(writes.contains(&RegisterWrite::Word(0x014, 0x0005_0000)));
+}
+
+#[test]
+fn test_interrupt_configuration() {
+    assert_eq!(0x0, InterruptConfiguration::default().as_int_register());
+
+    let interrupts = InterruptConfiguration {
+        receive: true,
+        transmit_event: true,
+        bus_error: true,
+        invalid_message: true,
+        ..Default::default()
+    };
+    assert_eq!(0xA012_0000, interrupts.as_int_register());
+
+    let config = Configuration {
+        tef: Some(TefConfiguration::default()),
+        interrupts,
+        ..Default::default()
+    };
+
+    let writes: Vec<_> = config.register_writes().unwrap().collect();
+    assert!(writes.contains(&RegisterWrite::Word(0x01C, 0xA012_0000)));
+    // TEF not empty interrupt
+    assert!(writes.contains(&RegisterWrite::Word(0x040, 0x0000_0001)));
+}
+
+#[test]
+fn test_io_configuration_to_register() {
+    assert_eq!(0x0300_0003, IoConfiguration::default().as_iocon_register());
+
+    let config = IoConfiguration {
+        pin0: PinMode::Interrupt,
+        pin1: PinMode::Output(true),
+        interrupt_output: OutputDriver::OpenDrain,
+        start_of_frame: true,
+        ..Default::default()
+    };
+    assert_eq!(0x6200_0201, config.as_iocon_register());
+
+    // GPIO0 is an output if transceiver standby control is enabled
+    let config = IoConfiguration {
+        pin0: PinMode::Interrupt,
+        transceiver_standby: true,
+        tx_can_output: OutputDriver::OpenDrain,
+        ..Default::default()
+    };
+    assert_eq!(0x1300_0042, config.as_iocon_register());
+}
+
+#[test]
+fn test_wake_up_filter_configuration() {
+    // Default configuration keeps the reset value of CiCON byte 1 (WFT = T11, WAKFIL set, BRSDIS cleared)
+    let writes: Vec<_> = Configuration::default().register_writes().unwrap().collect();
+    assert!(writes.contains(&RegisterWrite::Byte(0x001, 0b0000_0111)));
+
+    let config = Configuration {
+        wake_up_filter: None,
+        ..Default::default()
+    };
+
+    let writes: Vec<_> = config.register_writes().unwrap().collect();
+    assert!(writes.contains(&RegisterWrite::Byte(0x001, 0b0000_0110)));
+
+    let config = Configuration {
+        wake_up_filter: Some(WakeUpFilterTime::T10),
+        ..Default::default()
+    };
+
+    let writes: Vec<_> = config.register_writes().unwrap().collect();
+    assert!(writes.contains(&RegisterWrite::Byte(0x001, 0b0000_0101)));
+}
+
 fn fifo_rx_config(rx_size: u8) -> FifoConfiguration {
     FifoConfiguration {
         rx_size,
@@ -0,0 +1,205 @@
+use crate::can::{CanError, SpiError};
+use crate::frame::CanFrame;
+use crate::message::{Can20, TxMessage};
+use crate::mocks::{MockSPIDevice, SPIError};
+use crate::status::TxStatus;
+use crate::tests::can::Mocks;
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use embedded_can::{ErrorKind, ExtendedId, Frame, Id, StandardId};
+use mockall::Sequence;
+
+const EXTENDED_ID: u32 = 0x14C92A2B;
+const STANDARD_ID: u16 = 0x6A5;
+
+#[test]
+fn test_new_data_frame() {
+    let id = StandardId::new(STANDARD_ID).unwrap();
+    let frame = CanFrame::new(id, &[1, 2, 3]).unwrap();
+
+    assert_eq!(frame.id(), Id::Standard(id));
+    assert!(frame.is_standard());
+    assert!(frame.is_data_frame());
+    assert_eq!(frame.dlc(), 3);
+    assert_eq!(frame.data(), &[1, 2, 3]);
+}
+
+#[test]
+fn test_new_data_frame_extended_id() {
+    let id = ExtendedId::new(EXTENDED_ID).unwrap();
+    let frame = CanFrame::new(id, &[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+
+    assert_eq!(frame.id(), Id::Extended(id));
+    assert!(frame.is_extended());
+    assert_eq!(frame.dlc(), 8);
+    assert_eq!(frame.data(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn test_new_data_frame_too_long() {
+    let id = StandardId::new(STANDARD_ID).unwrap();
+
+    assert!(CanFrame::new(id, &[0u8; 9]).is_none());
+}
+
+#[test]
+fn test_new_remote_frame() {
+    let id = StandardId::new(STANDARD_ID).unwrap();
+    let frame = CanFrame::new_remote(id, 4).unwrap();
+
+    assert!(frame.is_remote_frame());
+    assert_eq!(frame.dlc(), 4);
+    assert!(frame.data().is_empty());
+
+    assert!(CanFrame::new_remote(id, 9).is_none());
+}
+
+#[test]
+fn test_error_kind() {
+    let kind = |error: CanError<MockSPIDevice>| embedded_can::Error::kind(&error);
+
+    assert_eq!(
+        ErrorKind::Acknowledge,
+        kind(CanError::BusOff(Some(ErrorKind::Acknowledge)))
+    );
+    assert_eq!(ErrorKind::Bit, kind(CanError::BusOff(Some(ErrorKind::Bit))));
+    assert_eq!(ErrorKind::Other, kind(CanError::BusOff(None)));
+    assert_eq!(
+        ErrorKind::Other,
+        kind(CanError::BusErr(SpiError::BusError(SPIError::Error1)))
+    );
+    assert_eq!(ErrorKind::Other, kind(CanError::CrcMismatch));
+    assert_eq!(ErrorKind::Other, kind(CanError::ConfigurationModeTimeout));
+    assert_eq!(ErrorKind::Other, kind(CanError::TxFifoFullErr));
+    assert_eq!(
+        ErrorKind::Other,
+        kind(CanError::TransmissionFailed(TxStatus::AttemptsExhausted))
+    );
+}
+
+#[test]
+fn test_blocking_transmit() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    let id = Id::Extended(ExtendedId::new(EXTENDED_ID).unwrap());
+    let frame = CanFrame::new(id, &[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+    let tx_message = TxMessage::new(Can20::<8> {}, &[1, 2, 3, 4, 5, 6, 7, 8], id).unwrap();
+
+    // TX fifo not full
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x6C], &mut seq);
+
+    // read operation status
+    mocks.mock_register_read::<0b1100_0000>([0x30, 0x2], &mut seq);
+
+    // fifo user address register read --> address = 0x4A2
+    mocks.mock_read32::<0x00_00_04_A2>([0x30, 0x70], &mut seq);
+
+    let mut cmd_and_header_buffer = [0u8; 10];
+    cmd_and_header_buffer[0] = 0x28;
+    cmd_and_header_buffer[1] = 0xA2;
+    cmd_and_header_buffer[2..].copy_from_slice(&tx_message.header.into_bytes());
+
+    for chunk in cmd_and_header_buffer[2..].chunks_exact_mut(4) {
+        let num = BigEndian::read_u32(chunk);
+        LittleEndian::write_u32(chunk, num);
+    }
+
+    mocks.expect_fifo_write_transaction(cmd_and_header_buffer, [1, 2, 3, 4, 5, 6, 7, 8], &mut seq);
+    mocks.expect_register_write([0x20, 0x6C, 0b0000_1111], &mut seq);
+    mocks.expect_register_write([0x20, 0x69, 0x03], &mut seq);
+
+    // txreq cleared -> message transmitted
+    mocks.mock_register_read::<0x00>([0x30, 0x69], &mut seq);
+    mocks.mock_register_read::<0x01>([0x30, 0x6C], &mut seq);
+
+    embedded_can::blocking::Can::transmit(&mut mocks.into_controller(), &frame).unwrap();
+}
+
+#[test]
+fn test_blocking_transmit_attempts_exhausted() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    let id = Id::Extended(ExtendedId::new(EXTENDED_ID).unwrap());
+    let frame = CanFrame::new(id, &[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+    let tx_message = TxMessage::new(Can20::<8> {}, &[1, 2, 3, 4, 5, 6, 7, 8], id).unwrap();
+
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x6C], &mut seq);
+    mocks.mock_register_read::<0b1100_0000>([0x30, 0x2], &mut seq);
+    mocks.mock_read32::<0x00_00_04_A2>([0x30, 0x70], &mut seq);
+
+    let mut cmd_and_header_buffer = [0u8; 10];
+    cmd_and_header_buffer[0] = 0x28;
+    cmd_and_header_buffer[1] = 0xA2;
+    cmd_and_header_buffer[2..].copy_from_slice(&tx_message.header.into_bytes());
+
+    for chunk in cmd_and_header_buffer[2..].chunks_exact_mut(4) {
+        let num = BigEndian::read_u32(chunk);
+        LittleEndian::write_u32(chunk, num);
+    }
+
+    mocks.expect_fifo_write_transaction(cmd_and_header_buffer, [1, 2, 3, 4, 5, 6, 7, 8], &mut seq);
+    mocks.expect_register_write([0x20, 0x6C, 0b0000_1111], &mut seq);
+    mocks.expect_register_write([0x20, 0x69, 0x03], &mut seq);
+
+    // txreq cleared -> attempts exhausted (TXERR, TXATIF)
+    mocks.mock_register_read::<0x00>([0x30, 0x69], &mut seq);
+    mocks.mock_register_read::<0b0011_0001>([0x30, 0x6C], &mut seq);
+
+    let error = embedded_can::blocking::Can::transmit(&mut mocks.into_controller(), &frame).unwrap_err();
+
+    assert_eq!(CanError::TransmissionFailed(TxStatus::AttemptsExhausted), error);
+}
+
+#[test]
+fn test_blocking_receive() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // RX fifo not empty
+    mocks.mock_register_read::<0b0000_0001>([0x30, 0x60], &mut seq);
+
+    // user address register read
+    mocks.mock_read32::<0x00_00_04_7C>([0x30, 0x64], &mut seq);
+
+    // Header: standard ID, DLC 2, CAN 2.0 frame
+    let header = [0xA5, 0x06, 0x0, 0x0, 0x02, 0x0, 0x0, 0x0];
+    mocks.expect_fifo_read_transaction([0x38, 0x7C], header, [1, 2, 0, 0, 0, 0, 0, 0], &mut seq);
+
+    mocks.expect_register_write([0x20, 0x5D, 0b0000_0001], &mut seq);
+
+    let frame = embedded_can::blocking::Can::receive(&mut mocks.into_controller()).unwrap();
+
+    assert_eq!(frame.id(), Id::Standard(StandardId::new(STANDARD_ID).unwrap()));
+    assert!(frame.is_data_frame());
+    assert_eq!(frame.dlc(), 2);
+    assert_eq!(frame.data(), &[1, 2]);
+}
+
+#[test]
+fn test_nb_transmit_would_block() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    let frame = CanFrame::new(StandardId::new(STANDARD_ID).unwrap(), &[1, 2, 3]).unwrap();
+
+    // TX fifo full
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x6C], &mut seq);
+
+    let result = embedded_can::nb::Can::transmit(&mut mocks.into_controller(), &frame);
+
+    assert_eq!(result.unwrap_err(), nb::Error::WouldBlock);
+}
+
+#[test]
+fn test_nb_receive_would_block() {
+    let mut mocks = Mocks::default();
+    let mut seq = Sequence::new();
+
+    // RX fifo empty
+    mocks.mock_register_read::<0b0000_0000>([0x30, 0x60], &mut seq);
+
+    let result = embedded_can::nb::Can::receive(&mut mocks.into_controller());
+
+    assert!(matches!(result.unwrap_err(), nb::Error::WouldBlock));
+}
@@ -1,5 +1,4 @@
-use crate::message::{Can20, CanFd, MessageError, TxMessage, DLC};
-use bytes::Bytes;
+use crate::message::{Can20, CanFd, MessageError, RxHeader, RxMessage, TxMessage, DLC};
 use embedded_can::Id;
 use embedded_can::{ExtendedId, StandardId};
 
@@ -9,7 +8,7 @@ const STANDARD_ID: u16 = 0x6A5;
 
 #[test]
 fn test_extended_id() {
-    let payload_bytes = Bytes::copy_from_slice(&[0u8; 8]);
+    let payload_bytes = &[0u8; 8];
     let extended_id = ExtendedId::new(EXTENDED_ID).unwrap();
 
     let msg_type = Can20::<8> {};
@@ -23,7 +22,7 @@ fn test_extended_id() {
 
 #[test]
 fn test_standard_id() {
-    let payload_bytes = Bytes::copy_from_slice(&[0u8; 8]);
+    let payload_bytes = &[0u8; 8];
     let standard_id = StandardId::new(STANDARD_ID).unwrap();
 
     let msg_type = Can20::<8> {};
@@ -37,7 +36,7 @@ fn test_standard_id() {
 
 #[test]
 fn test_dlc_success() {
-    let payload_bytes = Bytes::copy_from_slice(&[0u8; 13]);
+    let payload_bytes = &[0u8; 13];
     let standard_id = StandardId::new(STANDARD_ID).unwrap();
 
     let msg_type = CanFd::<16> { bitrate_switch: false };
@@ -57,8 +56,8 @@ fn test_dlc_error() {
     let data_2_0 = [0u8; 10];
     let data_fd = [0u8; 65];
 
-    let payload_bytes_2_0 = Bytes::copy_from_slice(&data_2_0);
-    let payload_bytes_fd = Bytes::copy_from_slice(&data_fd);
+    let payload_bytes_2_0 = &data_2_0;
+    let payload_bytes_fd = &data_fd;
 
     let can_msg_20 = Can20::<8> {};
     let can_msg_fd = CanFd::<64> { bitrate_switch: false };
@@ -77,8 +76,8 @@ fn test_message_size_divisible_by_four_error() {
     let data_2_0 = [0u8; 6];
     let data_fd = [0u8; 26];
 
-    let payload_bytes_2_0 = Bytes::copy_from_slice(&data_2_0);
-    let payload_bytes_fd = Bytes::copy_from_slice(&data_fd);
+    let payload_bytes_2_0 = &data_2_0;
+    let payload_bytes_fd = &data_fd;
 
     let can_msg_20 = Can20::<6> {};
     let can_msg_fd = CanFd::<26> { bitrate_switch: false };
@@ -97,8 +96,8 @@ fn test_payload_greater_than_generic_type_args() {
     let data_2_0 = [0u8; 5];
     let data_fd = [0u8; 23];
 
-    let payload_bytes_2_0 = Bytes::copy_from_slice(&data_2_0);
-    let payload_bytes_fd = Bytes::copy_from_slice(&data_fd);
+    let payload_bytes_2_0 = &data_2_0;
+    let payload_bytes_fd = &data_fd;
 
     let can_msg_20 = Can20::<4> {};
     let can_msg_fd = CanFd::<20> { bitrate_switch: false };
@@ -114,7 +113,7 @@ fn test_payload_greater_than_generic_type_args() {
 
 #[test]
 fn test_get_payload() {
-    let payload_bytes = Bytes::copy_from_slice(&[1u8; 8]);
+    let payload_bytes = &[1u8; 8];
     let standard_id = StandardId::new(STANDARD_ID).unwrap();
 
     let msg_type = Can20::<8> {};
@@ -123,3 +122,130 @@ fn test_get_payload() {
 
     assert_eq!(message.get_payload(), &[1u8; 8]);
 }
+
+#[test]
+fn test_payload_buffer_zero_padded() {
+    let standard_id = StandardId::new(STANDARD_ID).unwrap();
+
+    let message = TxMessage::new(
+        CanFd::<16> { bitrate_switch: false },
+        &[1u8; 10],
+        Id::Standard(standard_id),
+    )
+    .unwrap();
+
+    assert_eq!(message.get_payload(), &[1u8; 10]);
+    assert_eq!(DLC::Twelve, message.header.data_length_code());
+    assert_eq!([1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0], message.buff);
+}
+
+#[test]
+fn test_dlc_to_length() {
+    assert_eq!(DLC::Zero.to_length(), 0);
+    assert_eq!(DLC::Eight.to_length(), 8);
+    assert_eq!(DLC::Twelve.to_length(), 12);
+    assert_eq!(DLC::FortyEight.to_length(), 48);
+    assert_eq!(DLC::SixtyFour.to_length(), 64);
+}
+
+#[test]
+fn test_rx_message_payload_limited_by_dlc() {
+    let standard_id = StandardId::new(STANDARD_ID).unwrap();
+    let message = RxMessage {
+        header: RxHeader::new_test_cfg(Id::Standard(standard_id)).with_data_length_code(DLC::Three),
+        timestamp: None,
+        buff: [1, 2, 3, 4, 5, 6, 7, 8],
+    };
+
+    assert_eq!(message.id(), Id::Standard(standard_id));
+    assert_eq!(message.get_payload(), &[1, 2, 3]);
+}
+
+#[test]
+fn test_rx_message_payload_limited_by_buffer() {
+    let extended_id = ExtendedId::new(EXTENDED_ID).unwrap();
+    let message = RxMessage {
+        header: RxHeader::new_test_cfg(Id::Extended(extended_id)).with_data_length_code(DLC::Sixteen),
+        timestamp: None,
+        buff: [1, 2, 3, 4, 5, 6, 7, 8],
+    };
+
+    assert_eq!(message.id(), Id::Extended(extended_id));
+    assert_eq!(message.get_payload(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn test_rx_message_classic_payload_limited_to_8_bytes() {
+    let standard_id = StandardId::new(STANDARD_ID).unwrap();
+    let mut message = RxMessage {
+        header: RxHeader::new_test_cfg(Id::Standard(standard_id)).with_data_length_code(DLC::Sixteen),
+        timestamp: None,
+        buff: [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16],
+    };
+
+    assert_eq!(message.get_payload(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+    message.header.set_fd_frame(true);
+    assert_eq!(
+        message.get_payload(),
+        &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]
+    );
+}
+
+#[test]
+fn test_rx_header_accessors() {
+    let standard_id = StandardId::new(STANDARD_ID).unwrap();
+    let header = RxHeader::new_test_cfg(Id::Standard(standard_id))
+        .with_data_length_code(DLC::Twelve)
+        .with_filhit(5)
+        .with_fd_frame(true)
+        .with_bit_rate_switch(true)
+        .with_error_status_indicator(true);
+
+    assert_eq!(header.get_id(), Id::Standard(standard_id));
+    assert_eq!(header.dlc(), DLC::Twelve);
+    assert_eq!(header.filter_hit(), 5);
+    assert!(header.is_fd_frame());
+    assert!(header.is_bit_rate_switched());
+    assert!(header.is_error_passive());
+    assert!(!header.is_remote_frame());
+}
+
+#[test]
+fn test_new_remote() {
+    let extended_id = ExtendedId::new(EXTENDED_ID).unwrap();
+    let message = TxMessage::new_remote(Can20::<8> {}, DLC::Four, Id::Extended(extended_id)).unwrap();
+
+    assert!(message.is_remote_frame());
+    assert!(message.header.remote_transmission_request());
+    assert!(!message.header.fd_frame());
+    assert_eq!(message.header.data_length_code(), DLC::Four);
+    assert_eq!(message.get_id(), Id::Extended(extended_id));
+}
+
+#[test]
+fn test_new_remote_invalid_dlc() {
+    let standard_id = StandardId::new(STANDARD_ID).unwrap();
+
+    let result = TxMessage::new_remote(Can20::<8> {}, DLC::Twelve, Id::Standard(standard_id));
+    assert_eq!(result.unwrap_err(), MessageError::InvalidLength(12));
+
+    let result = TxMessage::new_remote(Can20::<4> {}, DLC::Eight, Id::Standard(standard_id));
+    assert_eq!(result.unwrap_err(), MessageError::InvalidLength(8));
+}
+
+#[test]
+fn test_rx_message_remote_frame_has_no_payload() {
+    let standard_id = StandardId::new(STANDARD_ID).unwrap();
+    let message = RxMessage {
+        header: RxHeader::new_test_cfg(Id::Standard(standard_id))
+            .with_data_length_code(DLC::Six)
+            .with_remote_transmission_request(true),
+        timestamp: None,
+        buff: [1, 2, 3, 4, 5, 6, 7, 8],
+    };
+
+    assert!(message.is_remote_frame());
+    assert_eq!(message.dlc(), DLC::Six);
+    assert!(message.get_payload().is_empty());
+}
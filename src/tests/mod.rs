@@ -1,6 +1,15 @@
+mod bit_timing;
+mod bus;
 mod can;
+#[cfg(feature = "async")]
+mod can_async;
 mod config;
 mod filter;
+mod frame;
 mod message;
 mod registers;
+mod simulator;
+#[cfg(feature = "socketcan")]
+mod socketcan;
 mod status;
+mod typestate;
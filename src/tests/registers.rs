@@ -39,3 +39,16 @@ fn test_fifo_control_reg3() {
 fn test_fifo_status_reg0() {
     assert_eq!([0b0000_0001], FifoStatusReg0::new().with_tfnrfnif(true).into_bytes());
 }
+
+#[test]
+fn test_crc16() {
+    assert_eq!(0xAEE7, crc16(CRC16_INIT, b"123456789"));
+}
+
+#[test]
+fn test_crc_command() {
+    // SFR length in bytes
+    assert_eq!([0xB0, 0x10, 0x04], Operation::ReadCrc.crc_command(0x010, 4));
+    // RAM length in words
+    assert_eq!([0xA4, 0x00, 0x04], Operation::WriteCrc.crc_command(0x400, 16));
+}
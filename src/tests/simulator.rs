@@ -0,0 +1,460 @@
+use crate::can::{CanController, CanError, MCP2517};
+use crate::config::{
+    ClockConfiguration, Configuration, Fifo, FifoConfiguration, FifoDirection, FifoSettings, FifoTable, GpioPin,
+    IoConfiguration, PayloadSize, PinMode, RequestMode, TefConfiguration, TxQueueConfiguration,
+};
+use crate::filter::Filter;
+use crate::message::{Can20, CanFd, TxMessage};
+use crate::simulator::{SimFrame, SimulatedClock, SimulatedDevice, Simulator};
+use crate::status::{ChipVariant, Interrupt, OperationMode};
+use alloc::vec;
+use embedded_can::{ExtendedId, Id, StandardId};
+use embedded_hal::spi::SpiDevice;
+
+fn standard_id(id: u16) -> Id {
+    Id::Standard(StandardId::new(id).unwrap())
+}
+
+fn message(id: Id, payload: &[u8]) -> TxMessage<Can20<8>, 8> {
+    TxMessage::new(Can20::<8> {}, payload, id).unwrap()
+}
+
+/// Returns a controller configured with the given settings
+fn configured(simulator: &Simulator, config: &Configuration) -> MCP2517<SimulatedDevice, SimulatedClock> {
+    let mut controller = MCP2517::new(simulator.device());
+    controller.configure(config, &SimulatedClock::default()).unwrap();
+
+    controller
+}
+
+fn config(mode: RequestMode) -> Configuration {
+    Configuration {
+        mode,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_mode_transition() {
+    let simulator = Simulator::new();
+    assert_eq!(OperationMode::Configuration, simulator.operation_mode());
+
+    let mut controller = configured(&simulator, &config(RequestMode::ListenOnly));
+
+    assert_eq!(OperationMode::ListenOnly, simulator.operation_mode());
+    assert_eq!(
+        OperationMode::ListenOnly,
+        controller.read_operation_status().unwrap().mode
+    );
+}
+
+#[test]
+fn test_reset() {
+    let simulator = Simulator::new();
+    let mut controller = configured(&simulator, &config(RequestMode::NormalCANFD));
+
+    controller.reset().unwrap();
+
+    assert_eq!(OperationMode::Configuration, simulator.operation_mode());
+}
+
+#[test]
+fn test_internal_loopback() {
+    let simulator = Simulator::new();
+    let mut controller = configured(&simulator, &config(RequestMode::InternalLoopback));
+
+    let id = Id::Extended(ExtendedId::new(0x1234_5678).unwrap());
+    controller.transmit(&message(id, &[1, 2, 3, 4, 5]), true).unwrap();
+
+    let received = controller.receive::<8>(false).unwrap();
+    assert_eq!(id, received.id());
+    assert_eq!([1, 2, 3, 4, 5], received.get_payload());
+
+    // Internal loopback does not transmit to the bus
+    assert!(simulator.take_transmitted().is_empty());
+    assert_eq!(CanError::RxFifoEmptyErr, controller.receive::<8>(false).unwrap_err());
+}
+
+#[test]
+fn test_external_loopback() {
+    let simulator = Simulator::new();
+    let mut controller = configured(&simulator, &config(RequestMode::ExternalLoopback));
+
+    controller.transmit(&message(standard_id(0x55), &[1, 2]), true).unwrap();
+
+    assert_eq!([1, 2], controller.receive::<8>(false).unwrap().get_payload());
+    assert_eq!(
+        vec![SimFrame::new(standard_id(0x55), &[1, 2])],
+        simulator.take_transmitted()
+    );
+}
+
+#[test]
+fn test_transmit_receive_normal_mode() {
+    let simulator = Simulator::new();
+    let config = Configuration {
+        fifo: FifoConfiguration {
+            pl_size: PayloadSize::TwentyBytes,
+            rx_size: 8,
+            tx_size: 8,
+            ..Default::default()
+        },
+        ..config(RequestMode::NormalCANFD)
+    };
+    let mut controller = configured(&simulator, &config);
+
+    let payload = [0xAB; 20];
+    let fd_message = TxMessage::new(CanFd::<20> { bitrate_switch: true }, &payload, standard_id(0x100)).unwrap();
+    controller.transmit(&fd_message, true).unwrap();
+
+    let transmitted = simulator.take_transmitted();
+    assert_eq!(1, transmitted.len());
+    assert_eq!(standard_id(0x100), transmitted[0].id);
+    assert_eq!(payload.to_vec(), transmitted[0].data);
+    assert!(transmitted[0].fd);
+    assert!(transmitted[0].bit_rate_switch);
+
+    // Frame received from the bus
+    assert!(simulator.receive(&SimFrame::new(standard_id(0x200), &[9, 8, 7])));
+
+    let received = controller.receive::<20>(true).unwrap();
+    assert_eq!(standard_id(0x200), received.id());
+    assert_eq!([9, 8, 7], received.get_payload());
+}
+
+#[test]
+fn test_listen_only_does_not_transmit() {
+    let simulator = Simulator::new();
+    let mut controller = configured(&simulator, &config(RequestMode::ListenOnly));
+
+    controller.transmit(&message(standard_id(0x55), &[1]), false).unwrap();
+
+    assert!(simulator.take_transmitted().is_empty());
+    assert!(simulator.read_register(0x030) & (1 << 2) != 0);
+}
+
+#[test]
+fn test_filter() {
+    let simulator = Simulator::new();
+    let mut controller = configured(&simulator, &config(RequestMode::NormalCANFD));
+
+    let mut filter = Filter::new(standard_id(0x120), 0).unwrap();
+    filter.set_mask_standard_id(0x7F0);
+    controller.set_filter_object(filter).unwrap();
+    controller.enable_filter(1, 0).unwrap();
+
+    assert!(simulator.receive(&SimFrame::new(standard_id(0x12F), &[1])));
+    assert!(!simulator.receive(&SimFrame::new(standard_id(0x130), &[2])));
+
+    let received = controller.receive::<8>(false).unwrap();
+    assert_eq!(standard_id(0x12F), received.id());
+    assert_eq!(0, received.filter_hit());
+    assert_eq!(CanError::RxFifoEmptyErr, controller.receive::<8>(false).unwrap_err());
+}
+
+#[test]
+fn test_receive_overflow() {
+    let simulator = Simulator::new();
+    let config = Configuration {
+        fifo: FifoConfiguration {
+            rx_size: 2,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut controller = configured(&simulator, &config);
+
+    for index in 0..3 {
+        simulator.receive(&SimFrame::new(standard_id(0x10), &[index]));
+    }
+
+    let interrupts = controller.read_interrupts().unwrap();
+    assert!(interrupts.contains(Interrupt::ReceiveOverflow));
+
+    controller.clear_interrupt(Interrupt::ReceiveOverflow).unwrap();
+    assert!(!controller.read_interrupts().unwrap().contains(Interrupt::ReceiveOverflow));
+
+    // Oldest messages are kept
+    assert_eq!([0], controller.receive::<8>(false).unwrap().get_payload());
+    assert_eq!([1], controller.receive::<8>(false).unwrap().get_payload());
+    assert_eq!(CanError::RxFifoEmptyErr, controller.receive::<8>(false).unwrap_err());
+}
+
+#[test]
+fn test_fifo_table_and_txq() {
+    let simulator = Simulator::new();
+    let config = Configuration {
+        fifo_table: FifoTable::default().with(
+            Fifo::new(3).unwrap(),
+            FifoSettings {
+                direction: FifoDirection::Transmit,
+                size: 4,
+                ..Default::default()
+            },
+        ),
+        txq: Some(TxQueueConfiguration::default()),
+        ..Default::default()
+    };
+    let mut controller = configured(&simulator, &config);
+
+    controller
+        .transmit_fifo(Fifo::new(3).unwrap(), &message(standard_id(0x3), &[3]), true)
+        .unwrap();
+    controller.transmit_txq(&message(standard_id(0x7), &[7]), true).unwrap();
+
+    let transmitted = simulator.take_transmitted();
+    assert_eq!(
+        vec![
+            SimFrame::new(standard_id(0x3), &[3]),
+            SimFrame::new(standard_id(0x7), &[7])
+        ],
+        transmitted
+    );
+}
+
+#[test]
+fn test_auto_rtr_response() {
+    let simulator = Simulator::new();
+    let response_fifo = Fifo::new(3).unwrap();
+    let config = Configuration {
+        fifo_table: FifoTable::default().with(
+            response_fifo,
+            FifoSettings {
+                direction: FifoDirection::Transmit,
+                auto_rtr: true,
+                ..Default::default()
+            },
+        ),
+        ..Default::default()
+    };
+    let mut controller = configured(&simulator, &config);
+
+    controller
+        .set_filter_object(Filter::new(standard_id(0x321), 0).unwrap())
+        .unwrap();
+    controller.enable_filter(3, 0).unwrap();
+    controller
+        .load_fifo(response_fifo, &message(standard_id(0x321), &[5, 6]))
+        .unwrap();
+
+    assert!(simulator.take_transmitted().is_empty());
+
+    // Remote frame requests transmission of the loaded message
+    assert!(simulator.receive(&SimFrame::new_remote(standard_id(0x321), 2)));
+
+    assert_eq!(
+        vec![SimFrame::new(standard_id(0x321), &[5, 6])],
+        simulator.take_transmitted()
+    );
+    assert_eq!(CanError::RxFifoEmptyErr, controller.receive::<8>(false).unwrap_err());
+}
+
+#[test]
+fn test_receive_remote_frame() {
+    let simulator = Simulator::new();
+    let mut controller = configured(&simulator, &config(RequestMode::NormalCANFD));
+
+    assert!(simulator.receive(&SimFrame::new_remote(standard_id(0x42), 3)));
+
+    let received = controller.receive::<8>(false).unwrap();
+    assert!(received.is_remote_frame());
+    assert_eq!(3, received.dlc().to_length());
+    assert!(received.get_payload().is_empty());
+}
+
+#[test]
+fn test_transmit_event() {
+    let simulator = Simulator::new();
+    let config = Configuration {
+        tef: Some(TefConfiguration::default()),
+        ..Default::default()
+    };
+    let mut controller = configured(&simulator, &config);
+
+    controller.transmit(&message(standard_id(0x1), &[1]), true).unwrap();
+    controller.transmit(&message(standard_id(0x2), &[2]), true).unwrap();
+
+    // TEF with a single event overflows
+    let event = controller.read_transmit_event().unwrap();
+    assert_eq!(0, event.sequence());
+    assert_eq!(standard_id(0x1), event.id());
+    assert_eq!(CanError::TefEmptyErr, controller.read_transmit_event().unwrap_err());
+}
+
+#[test]
+fn test_sleep_and_wake() {
+    let simulator = Simulator::new();
+    let clock = SimulatedClock::default();
+    let mut controller = configured(&simulator, &config(RequestMode::NormalCANFD));
+
+    controller.enter_sleep(&clock).unwrap();
+    assert_eq!(OperationMode::Sleep, simulator.operation_mode());
+    assert!(!controller.read_oscillator_status().unwrap().clock_ready);
+
+    assert!(!controller.wake(&clock).unwrap());
+    assert_eq!(OperationMode::NormalCANFD, simulator.operation_mode());
+    assert!(controller.read_oscillator_status().unwrap().clock_ready);
+
+    controller.transmit(&message(standard_id(0x10), &[1]), true).unwrap();
+    assert_eq!(1, simulator.take_transmitted().len());
+}
+
+#[test]
+fn test_wake_up_by_received_frame() {
+    let simulator = Simulator::new();
+    let clock = SimulatedClock::default();
+    let mut controller = configured(&simulator, &config(RequestMode::NormalCANFD));
+
+    controller.enter_sleep(&clock).unwrap();
+
+    // Frame waking the controller is lost
+    assert!(!simulator.receive(&SimFrame::new(standard_id(0x10), &[1])));
+    assert_eq!(OperationMode::Configuration, simulator.operation_mode());
+    assert!(controller.read_interrupts().unwrap().contains(Interrupt::WakeUp));
+
+    assert!(controller.wake(&clock).unwrap());
+    assert!(!controller.read_interrupts().unwrap().contains(Interrupt::WakeUp));
+    assert_eq!(CanError::RxFifoEmptyErr, controller.receive::<8>(false).unwrap_err());
+}
+
+#[test]
+fn test_low_power_mode() {
+    let simulator = Simulator::new();
+    let clock = SimulatedClock::default();
+    let config = Configuration {
+        clock: ClockConfiguration {
+            low_power_mode: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    simulator.set_variant(ChipVariant::MCP2518FD);
+
+    let mut controller = MCP2517::new(simulator.device());
+    assert_eq!(ChipVariant::MCP2518FD, controller.detect_variant().unwrap());
+    controller.configure(&config, &clock).unwrap();
+
+    controller.enter_sleep(&clock).unwrap();
+    assert_eq!(OperationMode::Sleep, simulator.operation_mode());
+
+    // Controller is reset on wake-up
+    assert!(!controller.wake(&clock).unwrap());
+    assert_eq!(OperationMode::Configuration, simulator.operation_mode());
+
+    controller.configure(&config, &clock).unwrap();
+    assert_eq!(OperationMode::NormalCANFD, simulator.operation_mode());
+}
+
+#[test]
+fn test_gpio() {
+    let simulator = Simulator::new();
+    let config = Configuration {
+        io: IoConfiguration {
+            pin0: PinMode::Output(false),
+            pin1: PinMode::Input,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut controller = configured(&simulator, &config);
+
+    assert!(!controller.read_gpio(GpioPin::Gpio0).unwrap());
+    controller.set_gpio(GpioPin::Gpio0, true).unwrap();
+    assert!(controller.read_gpio(GpioPin::Gpio0).unwrap());
+
+    assert!(!controller.read_gpio(GpioPin::Gpio1).unwrap());
+    simulator.set_gpio_input(GpioPin::Gpio1, true);
+    assert!(controller.read_gpio(GpioPin::Gpio1).unwrap());
+
+    // Latch of input pins does not change the level
+    controller.set_gpio(GpioPin::Gpio1, false).unwrap();
+    assert!(controller.read_gpio(GpioPin::Gpio1).unwrap());
+}
+
+#[test]
+fn test_transceiver_standby() {
+    let simulator = Simulator::new();
+    let clock = SimulatedClock::default();
+    let config = Configuration {
+        io: IoConfiguration {
+            transceiver_standby: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut controller = configured(&simulator, &config);
+
+    assert!(!controller.read_gpio(GpioPin::Gpio0).unwrap());
+
+    controller.enter_sleep(&clock).unwrap();
+    assert!(controller.read_gpio(GpioPin::Gpio0).unwrap());
+
+    controller.wake(&clock).unwrap();
+    assert!(!controller.read_gpio(GpioPin::Gpio0).unwrap());
+}
+
+#[test]
+fn test_device_id() {
+    let simulator = Simulator::new();
+    let mut controller = MCP2517::<_, SimulatedClock>::new(simulator.device());
+
+    assert_eq!(ChipVariant::MCP2517FD, controller.detect_variant().unwrap());
+    assert_eq!(0x60, simulator.read_register(0xE00) & 0xFF);
+
+    simulator.set_variant(ChipVariant::MCP2518FD);
+    let controller = MCP2517::<_, SimulatedClock>::detect(simulator.device()).unwrap();
+    assert_eq!(ChipVariant::MCP2518FD, controller.variant());
+    assert_eq!(0x60, simulator.read_register(0xE00) & 0xFF);
+
+    let mut controller = MCP2517::<_, SimulatedClock>::new(simulator.device());
+    simulator.set_variant(ChipVariant::MCP251863);
+    let device_id = controller.read_device_id().unwrap();
+    assert_eq!(ChipVariant::MCP2518FD, device_id.variant);
+    assert_eq!(0, device_id.revision);
+}
+
+#[test]
+fn test_spi_crc() {
+    let simulator = Simulator::new();
+    let mut controller = MCP2517::new(simulator.device());
+    controller.set_spi_crc(true);
+    controller
+        .configure(&config(RequestMode::InternalLoopback), &SimulatedClock::default())
+        .unwrap();
+
+    controller.transmit(&message(standard_id(0x42), &[4, 2]), true).unwrap();
+
+    assert_eq!([4, 2], controller.receive::<8>(false).unwrap().get_payload());
+}
+
+#[test]
+fn test_write_safe_crc_mismatch() {
+    let simulator = Simulator::new();
+    let mut device = simulator.device();
+
+    // WRITE_SAFE of OSC register with invalid CRC is discarded
+    device.write(&[0xCE, 0x00, 0x01, 0x00, 0x00]).unwrap();
+
+    assert_eq!(0x60, simulator.read_register(0xE00) & 0xFF);
+    assert_eq!(1 << 16, simulator.read_register(0xE08) & (1 << 16));
+}
+
+#[test]
+fn test_clear_spi_crc_interrupt() {
+    let simulator = Simulator::new();
+    let mut device = simulator.device();
+
+    // CRCERRIE and FERRIE
+    device.write(&[0x2E, 0x0B, 0b0000_0011]).unwrap();
+
+    // WRITE_SAFE with invalid CRC sets CRCERRIF
+    device.write(&[0xCE, 0x00, 0x01, 0x00, 0x00]).unwrap();
+
+    let mut controller = MCP2517::<_, SimulatedClock>::new(device);
+    assert!(controller.read_interrupts().unwrap().contains(Interrupt::SpiCrc));
+
+    controller.clear_interrupt(Interrupt::SpiCrc).unwrap();
+
+    assert!(!controller.read_interrupts().unwrap().contains(Interrupt::SpiCrc));
+    assert_eq!(0b11 << 24, simulator.read_register(0xE08) & 0xFFFF_0000);
+}
@@ -0,0 +1,132 @@
+use crate::message::{RxHeader, RxMessage, DLC};
+use crate::socketcan::{to_frame, SocketCan, SocketMessage};
+use core::mem;
+use embedded_can::{ExtendedId, Id, StandardId};
+use libc::{canfd_frame, CANFD_MTU, CAN_MTU};
+
+const EXTENDED_ID: u32 = 0x14C92A2B;
+
+const STANDARD_ID: u16 = 0x6A5;
+
+fn frame(can_id: u32, flags: u8, payload: &[u8]) -> canfd_frame {
+    let mut frame: canfd_frame = unsafe { mem::zeroed() };
+    frame.can_id = can_id;
+    frame.flags = flags;
+    frame.len = payload.len() as u8;
+    frame.data[..payload.len()].copy_from_slice(payload);
+
+    frame
+}
+
+#[test]
+fn test_to_frame_classic() {
+    let message = RxMessage {
+        header: RxHeader::new_test_cfg(Id::Standard(StandardId::new(STANDARD_ID).unwrap()))
+            .with_data_length_code(DLC::Three),
+        timestamp: None,
+        buff: [1, 2, 3, 4, 5, 6, 7, 8],
+    };
+
+    let (frame, size) = to_frame(&message);
+
+    assert_eq!(CAN_MTU, size);
+    assert_eq!(STANDARD_ID as u32, frame.can_id);
+    assert_eq!(3, frame.len);
+    assert_eq!(0, frame.flags);
+    assert_eq!([1, 2, 3, 0], frame.data[..4]);
+}
+
+#[test]
+fn test_to_frame_remote() {
+    let message = RxMessage {
+        header: RxHeader::new_test_cfg(Id::Standard(StandardId::new(STANDARD_ID).unwrap()))
+            .with_remote_transmission_request(true),
+        timestamp: None,
+        buff: [0u8; 8],
+    };
+
+    let (frame, size) = to_frame(&message);
+
+    assert_eq!(CAN_MTU, size);
+    assert_eq!(STANDARD_ID as u32 | libc::CAN_RTR_FLAG, frame.can_id);
+    assert_eq!(8, frame.len);
+}
+
+#[test]
+fn test_to_frame_fd_padded() {
+    let message = RxMessage {
+        header: RxHeader::new_test_cfg(Id::Extended(ExtendedId::new(EXTENDED_ID).unwrap()))
+            .with_data_length_code(DLC::Twelve)
+            .with_fd_frame(true)
+            .with_bit_rate_switch(true)
+            .with_error_status_indicator(true),
+        timestamp: None,
+        buff: [1, 2, 3, 4, 5, 6, 7, 8],
+    };
+
+    let (frame, size) = to_frame(&message);
+
+    assert_eq!(CANFD_MTU, size);
+    assert_eq!(EXTENDED_ID | libc::CAN_EFF_FLAG, frame.can_id);
+    assert_eq!(12, frame.len);
+    assert_eq!((libc::CANFD_FDF | libc::CANFD_BRS | libc::CANFD_ESI) as u8, frame.flags);
+    assert_eq!([1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0], frame.data[..12]);
+}
+
+#[test]
+fn test_from_frame_classic() {
+    let frame = frame(STANDARD_ID as u32, 0, &[1, 2, 3]);
+
+    let SocketMessage::Can20(message) = SocketMessage::from_frame(&frame, false).unwrap() else {
+        panic!("Unexpected message type");
+    };
+
+    assert_eq!(Id::Standard(StandardId::new(STANDARD_ID).unwrap()), message.get_id());
+    assert_eq!([1, 2, 3], message.get_payload());
+    assert_eq!(DLC::Three, message.header.data_length_code());
+    assert!(!message.header.remote_transmission_request());
+}
+
+#[test]
+fn test_from_frame_remote() {
+    let mut frame = frame(EXTENDED_ID | libc::CAN_EFF_FLAG | libc::CAN_RTR_FLAG, 0, &[]);
+    frame.len = 4;
+
+    let SocketMessage::Can20(message) = SocketMessage::from_frame(&frame, false).unwrap() else {
+        panic!("Unexpected message type");
+    };
+
+    assert_eq!(Id::Extended(ExtendedId::new(EXTENDED_ID).unwrap()), message.get_id());
+    assert!(message.header.remote_transmission_request());
+    assert_eq!(DLC::Four, message.header.data_length_code());
+}
+
+#[test]
+fn test_from_frame_fd() {
+    let payload = [0xAB; 20];
+    let frame = frame(
+        EXTENDED_ID | libc::CAN_EFF_FLAG,
+        (libc::CANFD_FDF | libc::CANFD_BRS) as u8,
+        &payload,
+    );
+
+    let message = SocketMessage::from_frame(&frame, true).unwrap();
+    assert_eq!(Id::Extended(ExtendedId::new(EXTENDED_ID).unwrap()), message.id());
+    assert_eq!(payload, message.get_payload());
+
+    let SocketMessage::CanFd(message) = message else {
+        panic!("Unexpected message type");
+    };
+    assert!(message.header.fd_frame());
+    assert!(message.header.bit_rate_switch());
+    assert_eq!(DLC::Twenty, message.header.data_length_code());
+}
+
+#[test]
+fn test_open_unknown_interface() {
+    assert!(SocketCan::open("unknown-can").is_err());
+    assert_eq!(
+        std::io::ErrorKind::InvalidInput,
+        SocketCan::open("vcan\0").unwrap_err().kind()
+    );
+}
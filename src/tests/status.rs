@@ -1,5 +1,10 @@
+use crate::config::Fifo;
 use crate::status::OperationMode::NormalCANFD;
-use crate::status::{OperationMode, OperationStatus, OscillatorStatus};
+use crate::status::{
+    BusDiagnostics, BusState, ChipVariant, DeviceId, EccStatus, ErrorState, Interrupt, Interrupts, OperationMode,
+    OperationStatus, OscillatorStatus,
+};
+use embedded_can::ErrorKind;
 use OperationMode::{
     Configuration, ExternalLoopback, InternalLoopback, ListenOnly, NormalCAN2_0, RestrictedOperation, Sleep,
 };
@@ -42,3 +47,130 @@ fn test_oscillator_status_from_register() {
     assert!(OscillatorStatus::from_register(0b0001_0101).pll_ready);
     assert!(!OscillatorStatus::from_register(0b0000_0100).pll_ready);
 }
+
+#[test]
+fn test_device_id_from_register() {
+    assert_eq!(
+        Some(DeviceId {
+            variant: ChipVariant::MCP2517FD,
+            revision: 0,
+        }),
+        DeviceId::from_register(0x00)
+    );
+    assert_eq!(
+        Some(DeviceId {
+            variant: ChipVariant::MCP2518FD,
+            revision: 4,
+        }),
+        DeviceId::from_register(0x14)
+    );
+    assert_eq!(None, DeviceId::from_register(0x20));
+
+    assert!(!ChipVariant::MCP2517FD.supports_low_power_mode());
+    assert!(ChipVariant::MCP2518FD.supports_low_power_mode());
+    assert!(ChipVariant::MCP251863.supports_low_power_mode());
+}
+
+#[test]
+fn test_interrupts_from_registers() {
+    // FIFO 3 interrupt, filter hit 4, TXQ + FIFO 5 TX, FIFO 3 RX, RXIF + TXIF + MODIF + CERRIF
+    let interrupts = Interrupts::from_registers([0x0000_0403, 0x0000_200B, 0x0000_0008, 0x0000_0021]);
+
+    assert_eq!(Some(Interrupt::Receive(Fifo::new(3).unwrap())), interrupts.highest());
+    assert_eq!(4, interrupts.filter_hit());
+    assert!(!interrupts.is_empty());
+    assert!(interrupts.contains(Interrupt::BusError));
+    assert!(!interrupts.contains(Interrupt::WakeUp));
+    assert_eq!(
+        vec![
+            Interrupt::TransmitQueue,
+            Interrupt::Transmit(Fifo::new(5).unwrap()),
+            Interrupt::Receive(Fifo::new(3).unwrap()),
+            Interrupt::ModeChange,
+            Interrupt::BusError,
+        ],
+        interrupts.iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_interrupts_highest() {
+    let highest = |vector, tx_fifos| Interrupts::from_registers([vector, 0, 0, tx_fifos]).highest();
+
+    assert_eq!(None, highest(0x40, 0));
+    assert_eq!(Some(Interrupt::TransmitQueue), highest(0x00, 0b1));
+    assert_eq!(Some(Interrupt::Transmit(Fifo::TX)), highest(0x02, 0b100));
+    assert_eq!(Some(Interrupt::Receive(Fifo::RX)), highest(0x01, 0b100));
+    assert_eq!(Some(Interrupt::BusError), highest(0x41, 0));
+    assert_eq!(Some(Interrupt::WakeUp), highest(0x42, 0));
+    assert_eq!(Some(Interrupt::SystemError), highest(0x45, 0));
+    assert_eq!(Some(Interrupt::TransmitEvent), highest(0x49, 0));
+    assert_eq!(Some(Interrupt::TransmitAttempt), highest(0x4A, 0));
+}
+
+#[test]
+fn test_interrupts_empty() {
+    let interrupts = Interrupts::from_registers([0x40, 0x0, 0x0, 0x0]);
+
+    assert!(interrupts.is_empty());
+    assert_eq!(None, interrupts.highest());
+}
+
+#[test]
+fn test_error_state_from_register() {
+    let state = ErrorState::from_register(0x0000_0A03);
+    assert_eq!(10, state.tec);
+    assert_eq!(3, state.rec);
+    assert_eq!(BusState::ErrorActive, state.bus_state);
+    assert!(!state.error_warning);
+
+    let state = ErrorState::from_register(0x000B_0080);
+    assert_eq!(BusState::ErrorPassive, state.bus_state);
+    assert!(state.error_warning);
+    assert!(state.rx_warning);
+    assert!(state.rx_error_passive);
+    assert!(!state.tx_error_passive);
+
+    let state = ErrorState::from_register(0x0035_FF00);
+    assert_eq!(BusState::BusOff, state.bus_state);
+    assert_eq!(255, state.tec);
+    assert!(state.tx_error_passive);
+}
+
+#[test]
+fn test_bus_diagnostics_from_registers() {
+    let diagnostics = BusDiagnostics::from_registers(0x0403_0201, 0x2084_0010);
+
+    assert_eq!(1, diagnostics.nominal_rx_errors);
+    assert_eq!(2, diagnostics.nominal_tx_errors);
+    assert_eq!(3, diagnostics.data_rx_errors);
+    assert_eq!(4, diagnostics.data_tx_errors);
+    assert_eq!(16, diagnostics.error_free_messages);
+    assert!(diagnostics.data_crc_error);
+    assert!(diagnostics.bus_off);
+    assert!(diagnostics.nominal_ack_error);
+    assert!(!diagnostics.nominal_crc_error);
+    assert!(!diagnostics.dlc_mismatch);
+}
+
+#[test]
+fn test_bus_diagnostics_error_kind() {
+    let kind = |diag1: u32| BusDiagnostics::from_registers(0, diag1).error_kind();
+
+    assert_eq!(None, kind(0x0080_0010));
+    assert_eq!(Some(ErrorKind::Acknowledge), kind(0x2084_0010));
+    assert_eq!(Some(ErrorKind::Bit), kind(0x0001_0000));
+    assert_eq!(Some(ErrorKind::Bit), kind(0x0200_0000));
+    assert_eq!(Some(ErrorKind::Stuff), kind(0x1000_0000));
+    assert_eq!(Some(ErrorKind::Crc), kind(0x0020_0000));
+    assert_eq!(Some(ErrorKind::Form), kind(0x0808_0000));
+}
+
+#[test]
+fn test_ecc_status_from_register() {
+    let status = EccStatus::from_register(0x0BFC_0002);
+
+    assert!(status.single_error);
+    assert!(!status.double_error);
+    assert_eq!(0xBFC, status.error_address);
+}
@@ -0,0 +1,148 @@
+use crate::can::{CanController, CanError};
+use crate::config::{ClockConfiguration, Configuration, FifoConfiguration, PayloadSize, RequestMode};
+use crate::message::{Can20, TxMessage};
+use crate::simulator::{SimFrame, SimulatedClock, SimulatedDevice, Simulator};
+use crate::status::{ChipVariant, OperationMode};
+use crate::typestate::{Controller, Normal, WakeUp};
+use embedded_can::{Id, StandardId};
+
+fn standard_id(id: u16) -> Id {
+    Id::Standard(StandardId::new(id).unwrap())
+}
+
+fn message(payload: &[u8]) -> TxMessage<Can20<8>, 8> {
+    TxMessage::new(Can20::<8> {}, payload, standard_id(0x55)).unwrap()
+}
+
+/// Returns a controller configured with the given settings
+fn configured(simulator: &Simulator, config: &Configuration) -> Controller<SimulatedDevice, SimulatedClock, Normal> {
+    Controller::new(simulator.device())
+        .configure(config, &SimulatedClock::default())
+        .unwrap()
+}
+
+#[test]
+fn test_configure() {
+    let simulator = Simulator::new();
+    let config = Configuration {
+        mode: RequestMode::InternalLoopback,
+        ..Default::default()
+    };
+    let mut controller = configured(&simulator, &config);
+
+    assert_eq!(OperationMode::InternalLoopback, simulator.operation_mode());
+
+    controller.transmit(&message(&[1, 2, 3]), true).unwrap();
+    assert_eq!([1, 2, 3], controller.receive::<8>(false).unwrap().get_payload());
+}
+
+#[test]
+fn test_configure_failed() {
+    let simulator = Simulator::new();
+    let config = Configuration {
+        fifo: FifoConfiguration {
+            pl_size: PayloadSize::SixtyFourBytes,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let error = Controller::new(simulator.device())
+        .configure(&config, &SimulatedClock::default())
+        .unwrap_err();
+    assert_eq!(CanError::RamSizeExceeded(5072), error.error);
+    assert_eq!(OperationMode::Configuration, simulator.operation_mode());
+
+    // Handle is returned in configuration mode
+    error
+        .controller
+        .configure(&Configuration::default(), &SimulatedClock::default())
+        .unwrap();
+    assert_eq!(OperationMode::NormalCANFD, simulator.operation_mode());
+}
+
+#[test]
+fn test_enter_configuration() {
+    let simulator = Simulator::new();
+    let clock = SimulatedClock::default();
+    let controller = configured(&simulator, &Configuration::default());
+
+    let controller = controller.enter_configuration(&clock).unwrap();
+    assert_eq!(OperationMode::Configuration, simulator.operation_mode());
+
+    let config = Configuration {
+        mode: RequestMode::ListenOnly,
+        ..Default::default()
+    };
+    controller.configure(&config, &clock).unwrap();
+    assert_eq!(OperationMode::ListenOnly, simulator.operation_mode());
+}
+
+#[test]
+fn test_sleep_and_wake() {
+    let simulator = Simulator::new();
+    let clock = SimulatedClock::default();
+    let controller = configured(&simulator, &Configuration::default());
+
+    let mut controller = controller.enter_sleep(&clock).unwrap();
+    assert_eq!(OperationMode::Sleep, simulator.operation_mode());
+    assert!(!controller.read_oscillator_status().unwrap().clock_ready);
+
+    let WakeUp::Normal {
+        mut controller,
+        bus_activity,
+    } = controller.wake(&clock).unwrap()
+    else {
+        panic!("Controller was reset");
+    };
+
+    assert!(!bus_activity);
+    assert_eq!(OperationMode::NormalCANFD, simulator.operation_mode());
+
+    controller.transmit(&message(&[1]), true).unwrap();
+    assert_eq!(1, simulator.take_transmitted().len());
+}
+
+#[test]
+fn test_wake_up_by_bus_activity() {
+    let simulator = Simulator::new();
+    let clock = SimulatedClock::default();
+    let controller = configured(&simulator, &Configuration::default());
+
+    let controller = controller.enter_sleep(&clock).unwrap();
+    simulator.receive(&SimFrame::new(standard_id(0x10), &[1]));
+
+    let WakeUp::Normal { bus_activity, .. } = controller.wake(&clock).unwrap() else {
+        panic!("Controller was reset");
+    };
+
+    assert!(bus_activity);
+    assert_eq!(OperationMode::NormalCANFD, simulator.operation_mode());
+}
+
+#[test]
+fn test_wake_from_low_power_mode() {
+    let simulator = Simulator::new();
+    let clock = SimulatedClock::default();
+    let config = Configuration {
+        clock: ClockConfiguration {
+            low_power_mode: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    simulator.set_variant(ChipVariant::MCP2518FD);
+
+    let controller = Controller::detect(simulator.device()).unwrap();
+    let controller = controller.configure(&config, &clock).unwrap();
+
+    let controller = controller.enter_sleep(&clock).unwrap();
+
+    let WakeUp::Reset(controller) = controller.wake(&clock).unwrap() else {
+        panic!("Controller was not reset");
+    };
+    assert_eq!(OperationMode::Configuration, simulator.operation_mode());
+
+    controller.configure(&config, &clock).unwrap();
+    assert_eq!(OperationMode::NormalCANFD, simulator.operation_mode());
+}
@@ -0,0 +1,402 @@
+//! # Typestate controller
+//! [Controller] wraps [MCP2517] and encodes the operation mode of the device in its type. Mode transitions
+//! consume the controller and return a handle of the new mode, so calling e.g. `transmit` before
+//! configuration is a compile error instead of a runtime failure.
+//!
+//! * [Config]: Configuration mode after reset, the controller is configured by [Controller::configure]
+//! * [Normal]: Configured operation mode (normal, loopback or listen only), messages are transmitted and received
+//! * [Sleep]: Sleep mode or low-power mode, the controller is woken up by [Controller::wake]
+//!
+//! On failure, a transition returns the error along with the unchanged handle.
+//!```
+//!# use mcp2517::can::CanController;
+//!# use mcp2517::config::Configuration;
+//!# use mcp2517::example::*;
+//!# use mcp2517::message::{Can20, TxMessage};
+//!# use mcp2517::typestate::Controller;
+//!# use embedded_can::{Id, StandardId};
+//!#
+//! let clock = ExampleClock::default();
+//! let controller = Controller::new(ExampleSPIDevice::default());
+//!
+//! // Transmission is not available in configuration mode
+//! let mut controller = controller.configure(&Configuration::default(), &clock).unwrap();
+//!
+//! let id = Id::Standard(StandardId::new(0x55).unwrap());
+//! let message = TxMessage::new(Can20::<8> {}, &[1, 2, 3], id).unwrap();
+//! controller.transmit(&message, true).unwrap();
+//!```
+use crate::can::{CanController, CanError, MCP2517};
+use crate::config::{ClockConfiguration, Configuration, Fifo, GpioPin};
+use crate::filter::Filter;
+use crate::message::{MessageType, RxMessage, TransmitEvent, TxMessage};
+use crate::status::{
+    AbortedTransmissions, BusDiagnostics, ChipVariant, DeviceId, EccStatus, ErrorState, Interrupt, Interrupts,
+    OperationMode, OperationStatus, OscillatorStatus, TxOutcome,
+};
+use core::fmt::{self, Debug, Formatter};
+use core::marker::PhantomData;
+use embedded_hal::spi::SpiDevice;
+use embedded_time::duration::Milliseconds;
+use embedded_time::Clock;
+
+/// Configuration mode
+#[derive(Debug, Default)]
+pub struct Config;
+
+/// Configured operation mode (normal, loopback or listen only)
+#[derive(Debug, Default)]
+pub struct Normal;
+
+/// Sleep mode or low-power mode
+#[derive(Debug, Default)]
+pub struct Sleep;
+
+/// MCP2517 controller in the operation mode `MODE`
+pub struct Controller<D: SpiDevice<u8>, CLK: Clock, MODE> {
+    /// Untyped driver
+    inner: MCP2517<D, CLK>,
+
+    /// Operation mode, which is only encoded in the type
+    mode: PhantomData<MODE>,
+}
+
+impl<D: SpiDevice<u8>, CLK: Clock, MODE: Debug + Default> Debug for Controller<D, CLK, MODE> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Controller")
+            .field("mode", &MODE::default())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Failed mode transition, the controller remains in the previous mode
+pub struct TransitionError<D: SpiDevice<u8>, CLK: Clock, MODE> {
+    /// Cause of the failed transition
+    pub error: CanError<D>,
+
+    /// Handle of the previous mode
+    pub controller: Controller<D, CLK, MODE>,
+}
+
+impl<D: SpiDevice<u8> + Debug, CLK: Clock, MODE> Debug for TransitionError<D, CLK, MODE> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransitionError")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Controller woken up from sleep mode
+pub enum WakeUp<D: SpiDevice<u8>, CLK: Clock> {
+    /// Controller returned to the configured operation mode
+    Normal {
+        controller: Controller<D, CLK, Normal>,
+        /// True if the controller was woken up by bus activity
+        bus_activity: bool,
+    },
+
+    /// Controller was reset on wake-up from low-power mode and must be configured again
+    Reset(Controller<D, CLK, Config>),
+}
+
+impl<D, CLK, MODE> Controller<D, CLK, MODE>
+where
+    D: SpiDevice<u8>,
+    CLK: Clock,
+{
+    /// Changes the mode of the handle
+    fn into_mode<NEXT>(self) -> Controller<D, CLK, NEXT> {
+        Controller {
+            inner: self.inner,
+            mode: PhantomData,
+        }
+    }
+
+    /// Returns a transition error keeping this handle
+    fn transition_error(self, error: CanError<D>) -> TransitionError<D, CLK, MODE> {
+        TransitionError {
+            error,
+            controller: self,
+        }
+    }
+
+    /// Resets the controller, which enters configuration mode
+    pub fn reset(mut self) -> Result<Controller<D, CLK, Config>, TransitionError<D, CLK, MODE>> {
+        match self.inner.reset() {
+            Ok(()) => Ok(self.into_mode()),
+            Err(error) => Err(self.transition_error(error)),
+        }
+    }
+
+    /// Releases the untyped driver
+    pub fn into_inner(self) -> MCP2517<D, CLK> {
+        self.inner
+    }
+
+    /// See [MCP2517::variant]
+    pub fn variant(&self) -> ChipVariant {
+        self.inner.variant()
+    }
+
+    /// See [MCP2517::read_device_id]
+    pub fn read_device_id(&mut self) -> Result<DeviceId, CanError<D>> {
+        self.inner.read_device_id()
+    }
+
+    /// See [MCP2517::read_operation_status]
+    pub fn read_operation_status(&mut self) -> Result<OperationStatus, CanError<D>> {
+        self.inner.read_operation_status()
+    }
+
+    /// See [MCP2517::read_interrupts]
+    pub fn read_interrupts(&mut self) -> Result<Interrupts, CanError<D>> {
+        self.inner.read_interrupts()
+    }
+
+    /// See [MCP2517::clear_interrupt]
+    pub fn clear_interrupt(&mut self, interrupt: Interrupt) -> Result<(), CanError<D>> {
+        self.inner.clear_interrupt(interrupt)
+    }
+
+    /// See [MCP2517::read_oscillator_status]
+    pub fn read_oscillator_status(&mut self) -> Result<OscillatorStatus, CanError<D>> {
+        self.inner.read_oscillator_status()
+    }
+
+    /// See [MCP2517::read_clock_configuration]
+    pub fn read_clock_configuration(&mut self) -> Result<ClockConfiguration, CanError<D>> {
+        self.inner.read_clock_configuration()
+    }
+
+    /// See [MCP2517::set_gpio]
+    pub fn set_gpio(&mut self, pin: GpioPin, level: bool) -> Result<(), CanError<D>> {
+        self.inner.set_gpio(pin, level)
+    }
+
+    /// See [MCP2517::read_gpio]
+    pub fn read_gpio(&mut self, pin: GpioPin) -> Result<bool, CanError<D>> {
+        self.inner.read_gpio(pin)
+    }
+}
+
+impl<D, CLK> Controller<D, CLK, Config>
+where
+    D: SpiDevice<u8>,
+    CLK: Clock,
+{
+    /// Creates a new driver of a controller in reset state. Blocking operations wait without timeout.
+    pub fn new(spi_dev: D) -> Self {
+        Self {
+            inner: MCP2517::new(spi_dev),
+            mode: PhantomData,
+        }
+    }
+
+    /// Creates a new driver of a controller in reset state and detects the controller variant.
+    /// See [MCP2517::detect].
+    pub fn detect(spi_dev: D) -> Result<Self, CanError<D>> {
+        Ok(Self {
+            inner: MCP2517::detect(spi_dev)?,
+            mode: PhantomData,
+        })
+    }
+
+    /// Creates a new driver of a controller in reset state with bounded blocking operations.
+    /// See [MCP2517::with_timeout].
+    pub fn with_timeout(spi_dev: D, clock: CLK, timeout: Milliseconds) -> Self {
+        Self {
+            inner: MCP2517::with_timeout(spi_dev, clock, timeout),
+            mode: PhantomData,
+        }
+    }
+
+    /// See [MCP2517::set_variant]
+    pub fn set_variant(&mut self, variant: ChipVariant) {
+        self.inner.set_variant(variant);
+    }
+
+    /// See [MCP2517::detect_variant]
+    pub fn detect_variant(&mut self) -> Result<ChipVariant, CanError<D>> {
+        self.inner.detect_variant()
+    }
+
+    /// See [MCP2517::set_spi_crc]
+    pub fn set_spi_crc(&mut self, enabled: bool) {
+        self.inner.set_spi_crc(enabled);
+    }
+
+    /// Configures the controller, which enters the configured operation mode
+    pub fn configure(
+        mut self,
+        config: &Configuration,
+        clock: &CLK,
+    ) -> Result<Controller<D, CLK, Normal>, TransitionError<D, CLK, Config>> {
+        match self.inner.configure(config, clock) {
+            Ok(()) => Ok(self.into_mode()),
+            Err(error) => Err(self.transition_error(error)),
+        }
+    }
+}
+
+impl<D, CLK> Controller<D, CLK, Normal>
+where
+    D: SpiDevice<u8>,
+    CLK: Clock,
+{
+    /// Enters configuration mode, all pending transmissions are aborted
+    pub fn enter_configuration(
+        mut self,
+        clock: &CLK,
+    ) -> Result<Controller<D, CLK, Config>, TransitionError<D, CLK, Normal>> {
+        match self
+            .inner
+            .enable_mode(OperationMode::Configuration, clock, CanError::ConfigurationModeTimeout)
+        {
+            Ok(()) => Ok(self.into_mode()),
+            Err(error) => Err(self.transition_error(error)),
+        }
+    }
+
+    /// Enters sleep mode or low-power mode, see [MCP2517::enter_sleep]
+    pub fn enter_sleep(mut self, clock: &CLK) -> Result<Controller<D, CLK, Sleep>, TransitionError<D, CLK, Normal>> {
+        match self.inner.enter_sleep(clock) {
+            Ok(()) => Ok(self.into_mode()),
+            Err(error) => Err(self.transition_error(error)),
+        }
+    }
+
+    /// See [MCP2517::next_sequence]
+    pub fn next_sequence(&self) -> u8 {
+        self.inner.next_sequence()
+    }
+
+    /// See [MCP2517::read_transmit_event]
+    pub fn read_transmit_event(&mut self) -> Result<TransmitEvent, CanError<D>> {
+        self.inner.read_transmit_event()
+    }
+
+    /// See [MCP2517::load_fifo]
+    pub fn load_fifo<const L: usize, T: MessageType<L>>(
+        &mut self,
+        fifo: Fifo,
+        message: &TxMessage<T, L>,
+    ) -> Result<(), CanError<D>> {
+        self.inner.load_fifo(fifo, message)
+    }
+
+    /// See [MCP2517::abort_fifo]
+    pub fn abort_fifo(&mut self, fifo: Fifo, clock: &CLK) -> Result<bool, CanError<D>> {
+        self.inner.abort_fifo(fifo, clock)
+    }
+
+    /// See [MCP2517::abort_all]
+    pub fn abort_all(&mut self, clock: &CLK) -> Result<AbortedTransmissions, CanError<D>> {
+        self.inner.abort_all(clock)
+    }
+
+    /// See [MCP2517::enable_filter]
+    pub fn enable_filter(&mut self, fifo_index: u8, filter_index: u8) -> Result<(), CanError<D>> {
+        self.inner.enable_filter(fifo_index, filter_index)
+    }
+
+    /// See [MCP2517::disable_filter]
+    pub fn disable_filter(&mut self, filter_index: u8) -> Result<(), CanError<D>> {
+        self.inner.disable_filter(filter_index)
+    }
+
+    /// See [MCP2517::read_error_state]
+    pub fn read_error_state(&mut self) -> Result<ErrorState, CanError<D>> {
+        self.inner.read_error_state()
+    }
+
+    /// See [MCP2517::read_bus_diagnostics]
+    pub fn read_bus_diagnostics(&mut self) -> Result<BusDiagnostics, CanError<D>> {
+        self.inner.read_bus_diagnostics()
+    }
+
+    /// See [MCP2517::clear_bus_diagnostics]
+    pub fn clear_bus_diagnostics(&mut self) -> Result<(), CanError<D>> {
+        self.inner.clear_bus_diagnostics()
+    }
+
+    /// See [MCP2517::recover_bus_off]
+    pub fn recover_bus_off(&mut self, clock: &CLK) -> Result<(), CanError<D>> {
+        self.inner.recover_bus_off(clock)
+    }
+
+    /// See [MCP2517::read_ecc_status]
+    pub fn read_ecc_status(&mut self) -> Result<EccStatus, CanError<D>> {
+        self.inner.read_ecc_status()
+    }
+
+    /// See [MCP2517::read_time_base]
+    pub fn read_time_base(&mut self) -> Result<u32, CanError<D>> {
+        self.inner.read_time_base()
+    }
+}
+
+impl<D, CLK> Controller<D, CLK, Sleep>
+where
+    D: SpiDevice<u8>,
+    CLK: Clock,
+{
+    /// Wakes the controller, see [MCP2517::wake]. The controller returns to the configured operation mode,
+    /// unless it is reset on wake-up from low-power mode.
+    pub fn wake(mut self, clock: &CLK) -> Result<WakeUp<D, CLK>, TransitionError<D, CLK, Sleep>> {
+        let low_power_mode = self.inner.low_power_mode();
+
+        match self.inner.wake(clock) {
+            Ok(_) if low_power_mode => Ok(WakeUp::Reset(self.into_mode())),
+            Ok(bus_activity) => Ok(WakeUp::Normal {
+                controller: self.into_mode(),
+                bus_activity,
+            }),
+            Err(error) => Err(self.transition_error(error)),
+        }
+    }
+}
+
+impl<D, CLK> CanController for Controller<D, CLK, Normal>
+where
+    D: SpiDevice<u8>,
+    CLK: Clock,
+{
+    type Error = CanError<D>;
+
+    fn transmit<const L: usize, T: MessageType<L>>(
+        &mut self,
+        message: &TxMessage<T, L>,
+        blocking: bool,
+    ) -> Result<TxOutcome, Self::Error> {
+        self.inner.transmit(message, blocking)
+    }
+
+    fn transmit_fifo<const L: usize, T: MessageType<L>>(
+        &mut self,
+        fifo: Fifo,
+        message: &TxMessage<T, L>,
+        blocking: bool,
+    ) -> Result<TxOutcome, Self::Error> {
+        self.inner.transmit_fifo(fifo, message, blocking)
+    }
+
+    fn transmit_txq<const L: usize, T: MessageType<L>>(
+        &mut self,
+        message: &TxMessage<T, L>,
+        blocking: bool,
+    ) -> Result<TxOutcome, Self::Error> {
+        self.inner.transmit_txq(message, blocking)
+    }
+
+    fn receive<const L: usize>(&mut self, blocking: bool) -> Result<RxMessage<L>, Self::Error> {
+        self.inner.receive(blocking)
+    }
+
+    fn receive_fifo<const L: usize>(&mut self, fifo: Fifo, blocking: bool) -> Result<RxMessage<L>, Self::Error> {
+        self.inner.receive_fifo(fifo, blocking)
+    }
+
+    fn set_filter_object(&mut self, filter: Filter) -> Result<(), Self::Error> {
+        self.inner.set_filter_object(filter)
+    }
+}